@@ -0,0 +1,21 @@
+use std::env;
+
+// libvlc-sys detects the libvlc version, pass its `vlc_*` cfg flags on to this crate,
+// and to the crates depending on it, which do not see those of libvlc-sys.
+fn main() {
+    if let Ok(cfgs) = env::var("DEP_VLC_CHECK_CFGS") {
+        for cfg in cfgs.split(',').filter(|c| !c.is_empty()) {
+            println!("cargo:rustc-check-cfg=cfg({})", cfg);
+        }
+        println!("cargo:check_cfgs={}", cfgs);
+    }
+    if let Ok(cfgs) = env::var("DEP_VLC_CFGS") {
+        for cfg in cfgs.split(',').filter(|c| !c.is_empty()) {
+            println!("cargo:rustc-cfg={}", cfg);
+        }
+        println!("cargo:cfgs={}", cfgs);
+    }
+    if let Ok(version) = env::var("DEP_VLC_VERSION") {
+        println!("cargo:version={}", version);
+    }
+}
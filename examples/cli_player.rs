@@ -6,7 +6,9 @@ extern crate vlc;
 
 use std::sync::mpsc::channel;
 
-use vlc::{Instance, Media, MediaPlayer, Event, EventType, State};
+use vlc::{Instance, Media, MediaPlayer, EventType};
+#[cfg(not(feature = "libvlc4"))]
+use vlc::{Event, State};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
@@ -25,7 +27,9 @@ fn main() {
     
     let (tx, rx) = channel::<()>();
     
+    #[cfg(not(feature = "libvlc4"))]
     let em = md.event_manager();
+    #[cfg(not(feature = "libvlc4"))]
     let _ = em.attach(EventType::MediaStateChanged, move |e, _| {
         match e {
             Event::MediaStateChanged(s) => {
@@ -37,6 +41,14 @@ fn main() {
             _ => (),
         }
     });
+
+    // libvlc 4 has no media state events, the player stops by itself at the end.
+    #[cfg(feature = "libvlc4")]
+    let em = mdp.event_manager();
+    #[cfg(feature = "libvlc4")]
+    let _ = em.attach(EventType::MediaPlayerStopping, move |_, _| {
+        tx.send(()).unwrap();
+    });
     
     mdp.set_media(&md);
     
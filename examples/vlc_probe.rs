@@ -0,0 +1,145 @@
+// This file is an example for vlc-rs, licensed under CC0.
+// https://creativecommons.org/publicdomain/zero/1.0/deed
+
+// Print the tracks and meta data of media as JSON, one document per input, like ffprobe.
+// Playlists and directories are expanded through their subitems.
+//
+// cargo run --features serde --example vlc-probe -- [--timeout SECS] [--depth N] [--network] [--pretty] PATH_OR_MRL...
+
+extern crate vlc;
+
+use std::path::Path;
+use std::process;
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+use serde_json::{json, Map, Value};
+use vlc::{EventType, Instance, Media, Meta, ParseFlag};
+
+// Every meta data field, in libvlc order.
+const META: [Meta; 23] = [
+    Meta::Title, Meta::Artist, Meta::Genre, Meta::Copyright, Meta::Album, Meta::TrackNumber,
+    Meta::Description, Meta::Rating, Meta::Date, Meta::Setting, Meta::URL, Meta::Language,
+    Meta::NowPlaying, Meta::Publisher, Meta::EncodedBy, Meta::ArtworkURL, Meta::TrackID,
+    Meta::TrackTotal, Meta::Director, Meta::Season, Meta::Episode, Meta::ShowName, Meta::Actors,
+];
+
+struct Options {
+    timeout: Duration,
+    depth: u32,
+    flags: Vec<ParseFlag>,
+    pretty: bool,
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: vlc-probe [--timeout SECS] [--depth N] [--network] [--pretty] PATH_OR_MRL...");
+    process::exit(2);
+}
+
+// Parse the media and wait for the result, returning the parsed status or "timeout".
+fn parse(instance: &Instance, md: &Media, opts: &Options) -> Value {
+    let (tx, rx) = channel();
+    let em = md.event_manager();
+    let handle = em.attach(EventType::MediaParsedChanged, move |_, _| {
+        let _ = tx.send(());
+    }).unwrap();
+
+    let timeout_ms = opts.timeout.as_millis().clamp(1, i32::MAX as u128) as i32;
+    let status = if md.parse_request(instance, &opts.flags, timeout_ms).is_err() {
+        json!("failed")
+    } else if rx.recv_timeout(opts.timeout + Duration::from_secs(1)).is_err() {
+        json!("timeout")
+    } else {
+        serde_json::to_value(md.parsed_status()).unwrap()
+    };
+
+    em.detach(EventType::MediaParsedChanged, handle);
+    status
+}
+
+fn probe(instance: &Instance, md: &Media, depth: u32, opts: &Options) -> Value {
+    let mut doc = Map::new();
+    doc.insert("mrl".to_owned(), json!(md.mrl()));
+    doc.insert("status".to_owned(), parse(instance, md, opts));
+    doc.insert("length_ms".to_owned(), json!(md.length().map(|d| d.as_millis() as u64)));
+
+    let meta: Map<String, Value> = META.iter()
+        .filter_map(|&m| md.get_meta(m).map(|v| (serde_json::to_value(m).unwrap(), v)))
+        .map(|(k, v)| (k.as_str().unwrap().to_owned(), json!(v)))
+        .collect();
+    doc.insert("meta".to_owned(), Value::Object(meta));
+
+    let tracks: Vec<Value> = md.tracks().unwrap_or_default().iter().map(|track| {
+        let mut value = serde_json::to_value(track).unwrap();
+        value["fourcc"] = json!(track.codec_fourcc().to_string());
+        #[cfg(vlc_3_0)]
+        { value["codec_description"] = json!(track.codec_description()); }
+        value
+    }).collect();
+    doc.insert("tracks".to_owned(), json!(tracks));
+
+    if let Some(list) = md.subitems() {
+        let items: Vec<Media> = list.iter().collect();
+        if !items.is_empty() {
+            let subitems: Vec<Value> = if depth < opts.depth {
+                items.iter().map(|item| probe(instance, item, depth + 1, opts)).collect()
+            } else {
+                items.iter().map(|item| json!({ "mrl": item.mrl() })).collect()
+            };
+            doc.insert("subitems".to_owned(), json!(subitems));
+        }
+    }
+
+    Value::Object(doc)
+}
+
+fn main() {
+    let mut opts = Options {
+        timeout: Duration::from_secs(5),
+        depth: 4,
+        flags: vec![ParseFlag::ParseLocal, ParseFlag::FetchLocal],
+        pretty: false,
+    };
+    let mut inputs = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--timeout" => {
+                let secs: f64 = args.next().and_then(|s| s.parse().ok()).unwrap_or_else(|| usage());
+                opts.timeout = Duration::from_secs_f64(secs);
+            },
+            "--depth" => opts.depth = args.next().and_then(|s| s.parse().ok()).unwrap_or_else(|| usage()),
+            "--network" => opts.flags.extend([ParseFlag::ParseNetwork, ParseFlag::FetchNetwork]),
+            "--pretty" => opts.pretty = true,
+            "-h" | "--help" => usage(),
+            _ => inputs.push(arg),
+        }
+    }
+    if inputs.is_empty() {
+        usage();
+    }
+
+    let args = vec!["--quiet".to_owned(), "--no-video-title-show".to_owned()];
+    let instance = Instance::with_args(Some(args)).expect("failed to create an instance");
+
+    for input in inputs {
+        let md = if Path::new(&input).exists() {
+            Media::new_path(&instance, &input)
+        } else {
+            Media::new_location(&instance, &input)
+        };
+
+        let mut doc = match md {
+            Some(md) => probe(&instance, &md, 0, &opts),
+            None => json!({ "status": "failed" }),
+        };
+        doc["input"] = json!(input);
+
+        if opts.pretty {
+            println!("{}", serde_json::to_string_pretty(&doc).unwrap());
+        } else {
+            println!("{}", doc);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 use pkg_config;
 
 #[cfg(feature = "bindgen")]
@@ -20,7 +21,7 @@ fn generate_bindings() {
         .parse_callbacks(Box::new(bindgen::CargoCallbacks));
 
     // Set header include paths
-    let pkg_config_library = pkg_config::Config::new().probe("libvlc").unwrap();
+    let pkg_config_library = libvlc_config().probe("libvlc").unwrap();
     for include_path in &pkg_config_library.include_paths {
         bindings = bindings.clang_arg(format!("-I{}", include_path.display()));
     }
@@ -36,8 +37,6 @@ fn generate_bindings() {
 #[cfg(not(feature = "bindgen"))]
 fn copy_pregenerated_bindings()
 {
-    use std::fs;
-
     let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
     let crate_path = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
     fs::copy(
@@ -47,13 +46,136 @@ fn copy_pregenerated_bindings()
     .expect("Couldn't find pregenerated bindings!");
 }
 
+// Replace the libvlc_* extern declarations by functions calling through a table that
+// src/dynamic.rs fills at runtime, so nothing links against libvlc.
+#[cfg(feature = "dynamic")]
+fn generate_dynamic_bindings() {
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let bindings = fs::read_to_string(out_path.join("bindings.rs"))
+        .expect("Couldn't read bindings!");
+
+    let mut kept = String::new();
+    let mut functions = Vec::new();
+    let mut lines = bindings.lines();
+    while let Some(line) = lines.next() {
+        if line != "extern \"C\" {" {
+            kept.push_str(line);
+            kept.push('\n');
+            continue;
+        }
+
+        let block: Vec<&str> = lines.by_ref().take_while(|l| *l != "}").collect();
+        let decls: String = block.iter()
+            .filter(|l| !l.trim_start().starts_with("#["))
+            .map(|l| l.trim())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let decls: Vec<&str> = decls.split(';').map(str::trim).filter(|d| !d.is_empty()).collect();
+
+        if decls.iter().all(|d| d.starts_with("pub fn libvlc_")) {
+            functions.extend(decls.iter().filter_map(|d| parse_function(d)));
+        } else {
+            kept.push_str(line);
+            kept.push('\n');
+            for l in block {
+                kept.push_str(l);
+                kept.push('\n');
+            }
+            kept.push_str("}\n");
+        }
+    }
+
+    let mut table = String::from("pub(crate) struct Api {\n");
+    let mut load = String::from(
+        "impl Api {\n    pub(crate) unsafe fn load(lib: &libloading::Library) -> Api {\n        Api {\n");
+    let mut wrappers = String::new();
+    for (name, params, ret) in &functions {
+        let args: Vec<&str> = params.iter().map(|(n, _)| n.as_str()).collect();
+        let params: Vec<String> = params.iter().map(|(n, t)| format!("{}: {}", n, t)).collect();
+        let params = params.join(", ");
+        let ret = ret.as_ref().map(|r| format!(" -> {}", r)).unwrap_or_default();
+
+        table.push_str(&format!(
+            "    {}: Option<unsafe extern \"C\" fn({}){}>,\n", name, params, ret));
+        load.push_str(&format!(
+            "            {0}: lib.get(b\"{0}\\0\").ok().map(|s| *s),\n", name));
+        wrappers.push_str(&format!(
+            "#[inline]\npub unsafe fn {0}({1}){2} {{\n    \
+             (crate::dynamic::api().ok().and_then(|api| api.{0})\n        \
+             .expect(\"{0} called without libvlc loaded, or not available in it\"))({3})\n}}\n",
+            name, params, ret, args.join(", ")));
+    }
+    table.push_str("}\n");
+    load.push_str("        }\n    }\n}\n");
+
+    fs::write(out_path.join("bindings.rs"), kept).expect("Couldn't write bindings!");
+    fs::write(out_path.join("dynamic_fns.rs"), table + &load + &wrappers)
+        .expect("Couldn't write dynamic bindings!");
+}
+
+// Split `pub fn name(a: A, b: B) -> R` into its name, parameters and return type.
+// Variadic functions cannot be forwarded and are skipped.
+#[cfg(feature = "dynamic")]
+fn parse_function(decl: &str) -> Option<(String, Vec<(String, String)>, Option<String>)> {
+    let decl = decl.strip_prefix("pub fn ")?;
+    let open = decl.find('(')?;
+    let name = decl[..open].trim().to_owned();
+
+    let mut depth = 0;
+    let mut close = None;
+    let mut params = Vec::new();
+    let mut start = open + 1;
+    for (i, c) in decl.char_indices().skip(open) {
+        match c {
+            '(' | '<' => depth += 1,
+            '>' if !decl[..i].ends_with('-') => depth -= 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(i);
+                    params.push(&decl[start..i]);
+                    break;
+                }
+            }
+            ',' if depth == 1 => {
+                params.push(&decl[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let close = close?;
+
+    let mut parsed = Vec::new();
+    for param in params.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        if param == "..." {
+            return None;
+        }
+        let colon = param.find(':')?;
+        parsed.push((param[..colon].trim().to_owned(), param[colon + 1..].trim().to_owned()));
+    }
+
+    let ret = decl[close + 1..].trim().strip_prefix("->").map(|r| r.trim().to_owned());
+    Some((name, parsed, ret))
+}
+
+// pkg-config settings for the libvlc version selected by the features.
+fn libvlc_config() -> pkg_config::Config {
+    let mut config = pkg_config::Config::new();
+    if cfg!(feature = "libvlc4") {
+        config.atleast_version("4.0");
+    }
+    config
+}
+
+#[cfg(not(feature = "dynamic"))]
 fn link_vlc_with_pkgconfig() -> Result<pkg_config::Library, pkg_config::Error> {
-    pkg_config::Config::new()
+    libvlc_config()
         .print_system_libs(false)
         .probe("libvlc")
 }
 
-#[cfg(target_os = "windows")]
+#[cfg(all(target_os = "windows", not(feature = "dynamic")))]
 mod windows {
     #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
     compile_error!("Only x86 and x86_64 are supported at the moment. Adding support for other architectures should be trivial.");
@@ -170,14 +292,104 @@ fn main() {
     #[cfg(not(feature = "bindgen"))]
     copy_pregenerated_bindings();
 
-    // Link
-    if let Err(err) = link_vlc_with_pkgconfig() {
-        #[cfg(target_os = "windows")]
-        windows::link_vlc();
+    // libvlc is loaded at runtime instead of being linked
+    #[cfg(feature = "dynamic")]
+    generate_dynamic_bindings();
 
-        #[cfg(not(target_os = "windows"))]
-        panic!("libvlc not found: {:?}", err);
-    }
+    #[cfg(feature = "dynamic")]
+    let library = libvlc_config().cargo_metadata(false).probe("libvlc").ok();
+
+    #[cfg(not(feature = "dynamic"))]
+    let library = link();
+
+    emit_version_cfgs(detect_version(library.as_ref()));
+}
+
+#[cfg(not(feature = "dynamic"))]
+fn link() -> Option<pkg_config::Library> {
+    let library = match link_vlc_with_pkgconfig() {
+        Ok(library) => Some(library),
+        Err(err) => {
+            #[cfg(target_os = "windows")]
+            {
+                let _ = err;
+                windows::link_vlc();
+                None
+            }
+
+            #[cfg(not(target_os = "windows"))]
+            panic!("libvlc not found: {:?}", err);
+        }
+    };
 
     println!("cargo:rustc-link-lib=vlc");
+    library
+}
+
+// Release series a `vlc_X_Y` cfg exists for, oldest first.
+const KNOWN_VERSIONS: &[(u32, u32)] = &[(2, 0), (2, 1), (2, 2), (3, 0), (4, 0)];
+
+// Version of the libvlc we build against, from pkg-config or from libvlc_version.h.
+// Without either, assume the version the bindings were generated for.
+fn detect_version(library: Option<&pkg_config::Library>) -> (u32, u32, u32) {
+    let mut include_dirs: Vec<PathBuf> = library.map(|l| l.include_paths.clone()).unwrap_or_default();
+    if let Some(dir) = env::var_os("VLC_LIB_DIR") {
+        include_dirs.push(Path::new(&dir).join("sdk").join("include"));
+    }
+    println!("cargo:rerun-if-env-changed=VLC_LIB_DIR");
+
+    library.and_then(|l| parse_version(&l.version))
+        .or_else(|| include_dirs.iter().find_map(|dir| version_from_header(dir)))
+        .unwrap_or(if cfg!(feature = "libvlc4") { (4, 0, 0) } else { (3, 0, 0) })
+}
+
+fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = version.split(|c: char| !c.is_ascii_digit()).map(|p| p.parse().ok());
+    Some((parts.next()??, parts.next()??, parts.next().flatten().unwrap_or(0)))
+}
+
+// Read the LIBVLC_VERSION_* macros, e.g. `#   define LIBVLC_VERSION_MAJOR    (3)`.
+fn version_from_header(include_dir: &Path) -> Option<(u32, u32, u32)> {
+    let header = fs::read_to_string(include_dir.join("vlc").join("libvlc_version.h")).ok()?;
+    let number = |name: &str| -> Option<u32> {
+        header.lines()
+            .map(|l| l.trim_start_matches(|c: char| c == '#' || c.is_whitespace()))
+            .filter_map(|l| l.strip_prefix("define "))
+            .find_map(|l| l.trim_start().strip_prefix(name))
+            .and_then(|v| v.trim().trim_start_matches('(').trim_end_matches(')').trim().parse().ok())
+    };
+    Some((number("LIBVLC_VERSION_MAJOR")?, number("LIBVLC_VERSION_MINOR")?, number("LIBVLC_VERSION_REVISION").unwrap_or(0)))
+}
+
+// Emit `vlc_X` and `vlc_X_Y` for every known release the version reaches, and `vlc_X_Y_x` for
+// its own series. They are also passed to dependents as DEP_VLC_CFGS.
+fn emit_version_cfgs((major, minor, revision): (u32, u32, u32)) {
+    let mut known = Vec::new();
+    let mut cfgs = Vec::new();
+    for &(maj, min) in KNOWN_VERSIONS {
+        known.extend([format!("vlc_{}_{}", maj, min), format!("vlc_{}_{}_x", maj, min)]);
+        if !known.contains(&format!("vlc_{}", maj)) {
+            known.push(format!("vlc_{}", maj));
+        }
+
+        if (major, minor) >= (maj, min) {
+            cfgs.push(format!("vlc_{}_{}", maj, min));
+            if !cfgs.contains(&format!("vlc_{}", maj)) {
+                cfgs.push(format!("vlc_{}", maj));
+            }
+        }
+        if (major, minor) == (maj, min) {
+            cfgs.push(format!("vlc_{}_{}_x", maj, min));
+        }
+    }
+
+    for cfg in &known {
+        println!("cargo:rustc-check-cfg=cfg({})", cfg);
+    }
+    for cfg in &cfgs {
+        println!("cargo:rustc-cfg={}", cfg);
+    }
+    println!("cargo:version={}.{}.{}", major, minor, revision);
+    println!("cargo:cfgs={}", cfgs.join(","));
+    println!("cargo:check_cfgs={}", known.join(","));
 }
@@ -0,0 +1,111 @@
+// Runtime loading of libvlc, used instead of linking when the `dynamic` feature is enabled.
+
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use libloading::Library;
+
+use crate::Api;
+
+#[cfg(target_os = "windows")]
+const LIBRARY_NAMES: &[&str] = &["libvlc.dll"];
+#[cfg(target_os = "macos")]
+const LIBRARY_NAMES: &[&str] = &["libvlc.dylib", "libvlc.5.dylib"];
+#[cfg(all(not(any(target_os = "windows", target_os = "macos")), not(feature = "libvlc4")))]
+const LIBRARY_NAMES: &[&str] = &["libvlc.so.5", "libvlc.so"];
+#[cfg(all(not(any(target_os = "windows", target_os = "macos")), feature = "libvlc4"))]
+const LIBRARY_NAMES: &[&str] = &["libvlc.so.12", "libvlc.so"];
+
+static LIBVLC: OnceLock<(Library, Api)> = OnceLock::new();
+static LOAD_LOCK: Mutex<()> = Mutex::new(());
+
+/// Error returned when libvlc cannot be loaded.
+#[derive(Clone, Debug)]
+pub struct LoadError {
+    /// Every candidate that was tried, with the loader's message for it.
+    pub attempts: Vec<(OsString, String)>,
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "libvlc not found")?;
+        for (candidate, err) in &self.attempts {
+            write!(f, "\n  {}: {}", candidate.to_string_lossy(), err)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// Load libvlc, unless it is already loaded.
+///
+/// Each entry of `search_path` may be the library file itself or a directory containing it.
+/// They are tried first, then the directory in the `VLC_LIB_DIR` environment variable, then
+/// the system library search path.
+pub fn load<P: AsRef<Path>>(search_path: &[P]) -> Result<(), LoadError> {
+    if LIBVLC.get().is_some() {
+        return Ok(());
+    }
+    let _guard = LOAD_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if LIBVLC.get().is_some() {
+        return Ok(());
+    }
+
+    let mut candidates: Vec<OsString> = Vec::new();
+    let env_dir = env::var_os("VLC_LIB_DIR").map(PathBuf::from);
+    for entry in search_path.iter().map(|p| p.as_ref()).chain(env_dir.as_deref()) {
+        if entry.is_file() {
+            candidates.push(entry.into());
+        } else {
+            candidates.extend(LIBRARY_NAMES.iter().map(|name| entry.join(name).into()));
+        }
+    }
+    candidates.extend(LIBRARY_NAMES.iter().map(OsString::from));
+
+    let mut attempts = Vec::new();
+    for candidate in candidates {
+        match open(&candidate) {
+            Ok(lib) => {
+                let api = unsafe { Api::load(&lib) };
+                let _ = LIBVLC.set((lib, api));
+                return Ok(());
+            }
+            Err(err) => attempts.push((candidate, err.to_string())),
+        }
+    }
+
+    Err(LoadError { attempts })
+}
+
+/// Whether libvlc has been loaded.
+pub fn is_loaded() -> bool {
+    LIBVLC.get().is_some()
+}
+
+#[cfg(target_os = "windows")]
+fn open(path: &OsString) -> Result<Library, libloading::Error> {
+    use libloading::os::windows;
+
+    // Resolve libvlccore.dll next to libvlc.dll rather than in the process directory.
+    // The flag is only defined for absolute paths.
+    let flags = if Path::new(path).is_absolute() { windows::LOAD_WITH_ALTERED_SEARCH_PATH } else { 0 };
+    unsafe { windows::Library::load_with_flags(path, flags).map(Library::from) }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn open(path: &OsString) -> Result<Library, libloading::Error> {
+    unsafe { Library::new(path) }
+}
+
+// Function table of the loaded library, loading it from the default locations on first use.
+pub(crate) fn api() -> Result<&'static Api, LoadError> {
+    if let Some((_, api)) = LIBVLC.get() {
+        return Ok(api);
+    }
+    load::<&Path>(&[])?;
+    LIBVLC.get().map(|(_, api)| api).ok_or(LoadError { attempts: Vec::new() })
+}
@@ -1,6 +1,16 @@
 #![allow(non_upper_case_globals)]
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
-#![no_std]
+#![cfg_attr(not(feature = "dynamic"), no_std)]
+
+// With the `dynamic` feature, the functions load libvlc on first use, and panic if it cannot be
+// loaded or lacks them. Call `load` first to handle a missing libvlc.
+#[cfg(feature = "dynamic")]
+mod dynamic;
+#[cfg(feature = "dynamic")]
+pub use dynamic::{is_loaded, load, LoadError};
 
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+
+#[cfg(feature = "dynamic")]
+include!(concat!(env!("OUT_DIR"), "/dynamic_fns.rs"));
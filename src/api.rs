@@ -0,0 +1,193 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+//! Traits over the playback objects, so that code driving them can run against
+//! the libvlc wrappers or against the fakes in `vlc::mock`.
+
+use std::time::Duration;
+
+use crate::{Event, EventHandle, EventManager, EventType, Meta, PlaybackMode, State, VLCObject};
+use crate::{CrossfadePlayer, Media, MediaList, MediaListPlayer, MediaPlayer, MediaPlayerAudioEx};
+
+/// Callback registration, implemented by [EventManager].
+pub trait EventManagerApi {
+    /// Identifies a registered callback for `detach`, which frees it.
+    type Handle;
+
+    fn attach<F>(&self, event_type: EventType, callback: F) -> Result<Self::Handle, ()>
+        where F: Fn(Event, VLCObject) + Send + 'static;
+    fn detach(&self, event_type: EventType, handle: Self::Handle);
+}
+
+/// The media surface, implemented by [Media].
+pub trait MediaApi: Clone {
+    type EventManager<'a>: EventManagerApi where Self: 'a;
+
+    fn mrl(&self) -> Option<String>;
+    fn get_meta(&self, meta: Meta) -> Option<String>;
+    fn set_meta(&self, meta: Meta, value: &str);
+    fn add_option(&self, option: &str);
+    fn state(&self) -> State;
+    fn length(&self) -> Option<Duration>;
+    fn event_manager(&self) -> Self::EventManager<'_>;
+}
+
+/// The playback surface, implemented by [MediaPlayer].
+pub trait MediaPlayerApi {
+    type Media: MediaApi;
+    type EventManager<'a>: EventManagerApi where Self: 'a;
+
+    fn set_media(&self, md: &Self::Media);
+    fn get_media(&self) -> Option<Self::Media>;
+    fn event_manager(&self) -> Self::EventManager<'_>;
+    fn is_playing(&self) -> bool;
+    fn play(&self) -> Result<(), ()>;
+    fn set_pause(&self, do_pause: bool);
+    fn pause(&self);
+    fn stop(&self);
+    fn time(&self) -> Option<Duration>;
+    fn seek(&self, time: Duration);
+    fn get_position(&self) -> Option<f32>;
+    fn set_position(&self, pos: f32);
+    fn get_rate(&self) -> f32;
+    fn set_rate(&self, rate: f32) -> Result<(), ()>;
+    fn state(&self) -> State;
+    fn is_seekable(&self) -> bool;
+    fn can_pause(&self) -> bool;
+}
+
+/// The media list surface, implemented by [MediaList].
+pub trait MediaListApi {
+    type Media: MediaApi;
+    type EventManager<'a>: EventManagerApi where Self: 'a;
+
+    fn add_media(&self, md: &Self::Media) -> Result<(), ()>;
+    fn insert_media(&self, md: &Self::Media, pos: i32) -> Result<(), ()>;
+    fn remove_index(&self, pos: i32) -> Result<(), ()>;
+    fn count(&self) -> i32;
+    fn item_at_index(&self, pos: i32) -> Option<Self::Media>;
+    fn index_of_item(&self, md: &Self::Media) -> Option<i32>;
+    fn is_readonly(&self) -> bool;
+    fn event_manager(&self) -> Self::EventManager<'_>;
+}
+
+/// The list playback surface, implemented by [MediaListPlayer].
+pub trait MediaListPlayerApi {
+    type MediaPlayer: MediaPlayerApi;
+    type MediaList: MediaListApi;
+
+    fn get_media_player(&self) -> Option<Self::MediaPlayer>;
+    fn set_media_list(&self, media_list: &Self::MediaList);
+    fn play(&self);
+    fn set_pause(&self, pause: bool);
+    fn is_playing(&self) -> bool;
+    fn play_item_at_index(&self, index: i32) -> Result<(), ()>;
+    fn stop(&self);
+    fn next(&self) -> Result<(), ()>;
+    fn previous(&self) -> Result<(), ()>;
+    fn set_playback_mode(&self, mode: PlaybackMode);
+}
+
+impl<'b> EventManagerApi for EventManager<'b> {
+    type Handle = EventHandle;
+
+    fn attach<F>(&self, event_type: EventType, callback: F) -> Result<EventHandle, ()>
+        where F: Fn(Event, VLCObject) + Send + 'static
+    {
+        let raw = EventManager::attach(self, event_type, callback)?;
+        Ok(EventHandle { em: self.ptr, event_type, raw })
+    }
+
+    /// Detach and free the callback. Does nothing if it was not attached here for `event_type`.
+    fn detach(&self, event_type: EventType, handle: EventHandle) {
+        if handle.em == self.ptr && handle.event_type == event_type {
+            // The handle was made by attach on this event manager, and is consumed.
+            unsafe { self.detach_and_free(event_type, handle.raw) };
+        }
+    }
+}
+
+impl MediaApi for Media {
+    type EventManager<'a> = EventManager<'a>;
+
+    fn mrl(&self) -> Option<String> { Media::mrl(self) }
+    fn get_meta(&self, meta: Meta) -> Option<String> { Media::get_meta(self, meta) }
+    fn set_meta(&self, meta: Meta, value: &str) { Media::set_meta(self, meta, value) }
+    fn add_option(&self, option: &str) { Media::add_option(self, option) }
+    fn state(&self) -> State { Media::state(self) }
+    fn length(&self) -> Option<Duration> { Media::length(self) }
+    fn event_manager(&self) -> EventManager<'_> { Media::event_manager(self) }
+}
+
+impl MediaPlayerApi for MediaPlayer {
+    type Media = Media;
+    type EventManager<'a> = EventManager<'a>;
+
+    fn set_media(&self, md: &Media) { MediaPlayer::set_media(self, md) }
+    fn get_media(&self) -> Option<Media> { MediaPlayer::get_media(self) }
+    fn event_manager(&self) -> EventManager<'_> { MediaPlayer::event_manager(self) }
+    fn is_playing(&self) -> bool { MediaPlayer::is_playing(self) }
+    fn play(&self) -> Result<(), ()> { MediaPlayer::play(self) }
+    fn set_pause(&self, do_pause: bool) { MediaPlayer::set_pause(self, do_pause) }
+    fn pause(&self) { MediaPlayer::pause(self) }
+    fn stop(&self) { MediaPlayer::stop(self) }
+    fn time(&self) -> Option<Duration> { MediaPlayer::time(self) }
+    fn seek(&self, time: Duration) { MediaPlayer::seek(self, time) }
+    fn get_position(&self) -> Option<f32> { MediaPlayer::get_position(self) }
+    fn set_position(&self, pos: f32) { MediaPlayer::set_position(self, pos) }
+    fn get_rate(&self) -> f32 { MediaPlayer::get_rate(self) }
+    fn set_rate(&self, rate: f32) -> Result<(), ()> { MediaPlayer::set_rate(self, rate) }
+    fn state(&self) -> State { MediaPlayer::state(self) }
+    fn is_seekable(&self) -> bool { MediaPlayer::is_seekable(self) }
+    fn can_pause(&self) -> bool { MediaPlayer::can_pause(self) }
+}
+
+impl MediaListApi for MediaList {
+    type Media = Media;
+    type EventManager<'a> = EventManager<'a>;
+
+    fn add_media(&self, md: &Media) -> Result<(), ()> { MediaList::add_media(self, md) }
+    fn insert_media(&self, md: &Media, pos: i32) -> Result<(), ()> { MediaList::insert_media(self, md, pos) }
+    fn remove_index(&self, pos: i32) -> Result<(), ()> { MediaList::remove_index(self, pos) }
+    fn count(&self) -> i32 { MediaList::count(self) }
+    fn item_at_index(&self, pos: i32) -> Option<Media> { MediaList::item_at_index(self, pos) }
+    fn index_of_item(&self, md: &Media) -> Option<i32> { MediaList::index_of_item(self, md) }
+    fn is_readonly(&self) -> bool { MediaList::is_readonly(self) }
+    fn event_manager(&self) -> EventManager<'_> { MediaList::event_manager(self) }
+}
+
+impl MediaListPlayerApi for MediaListPlayer {
+    type MediaPlayer = MediaPlayer;
+    type MediaList = MediaList;
+
+    fn get_media_player(&self) -> Option<MediaPlayer> { MediaListPlayer::get_media_player(self) }
+    fn set_media_list(&self, media_list: &MediaList) { MediaListPlayer::set_media_list(self, media_list) }
+    fn play(&self) { MediaListPlayer::play(self) }
+    fn set_pause(&self, pause: bool) { MediaListPlayer::set_pause(self, pause) }
+    fn is_playing(&self) -> bool { MediaListPlayer::is_playing(self) }
+    fn play_item_at_index(&self, index: i32) -> Result<(), ()> { MediaListPlayer::play_item_at_index(self, index) }
+    fn stop(&self) { MediaListPlayer::stop(self) }
+    fn next(&self) -> Result<(), ()> { MediaListPlayer::next(self) }
+    fn previous(&self) -> Result<(), ()> { MediaListPlayer::previous(self) }
+    fn set_playback_mode(&self, mode: PlaybackMode) { MediaListPlayer::set_playback_mode(self, mode) }
+}
+
+impl<P, L> MediaListPlayerApi for CrossfadePlayer<P, L>
+    where P: MediaPlayerApi + MediaPlayerAudioEx + Clone + Send + Sync + 'static,
+          L: MediaListApi<Media = P::Media> + Clone + Send + 'static
+{
+    type MediaPlayer = P;
+    type MediaList = L;
+
+    fn get_media_player(&self) -> Option<P> { CrossfadePlayer::get_media_player(self) }
+    fn set_media_list(&self, media_list: &L) { CrossfadePlayer::set_media_list(self, media_list) }
+    fn play(&self) { CrossfadePlayer::play(self) }
+    fn set_pause(&self, pause: bool) { CrossfadePlayer::set_pause(self, pause) }
+    fn is_playing(&self) -> bool { CrossfadePlayer::is_playing(self) }
+    fn play_item_at_index(&self, index: i32) -> Result<(), ()> { CrossfadePlayer::play_item_at_index(self, index) }
+    fn stop(&self) { CrossfadePlayer::stop(self) }
+    fn next(&self) -> Result<(), ()> { CrossfadePlayer::next(self) }
+    fn previous(&self) -> Result<(), ()> { CrossfadePlayer::previous(self) }
+    fn set_playback_mode(&self, mode: PlaybackMode) { CrossfadePlayer::set_playback_mode(self, mode) }
+}
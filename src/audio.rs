@@ -2,7 +2,10 @@
 // This file is part of vlc-rs.
 // Licensed under the MIT license, see the LICENSE file.
 
+#[cfg(not(feature = "libvlc4"))]
 use crate::tools::from_cstr;
+#[cfg(feature = "libvlc4")]
+use crate::TrackType;
 use crate::MediaPlayer;
 use crate::TrackDescription;
 use vlc_sys as sys;
@@ -46,6 +49,19 @@ impl MediaPlayerAudioEx for MediaPlayer {
             }
         }
     }
+    #[cfg(feature = "libvlc4")]
+    fn get_audio_track_description(&self) -> Option<Vec<TrackDescription>> {
+        self.track_descriptions(TrackType::Audio)
+    }
+    #[cfg(feature = "libvlc4")]
+    fn get_audio_track(&self) -> Option<i32> {
+        self.selected_track_id(TrackType::Audio)
+    }
+    #[cfg(feature = "libvlc4")]
+    fn set_audio_track(&self, track: i32) {
+        self.select_track_by_es_id(TrackType::Audio, track);
+    }
+    #[cfg(not(feature = "libvlc4"))]
     fn get_audio_track_description(&self) -> Option<Vec<TrackDescription>> {
         unsafe {
             let p0 = sys::libvlc_audio_get_track_description(self.ptr);
@@ -67,6 +83,7 @@ impl MediaPlayerAudioEx for MediaPlayer {
             Some(td)
         }
     }
+    #[cfg(not(feature = "libvlc4"))]
     fn get_audio_track(&self) -> Option<i32> {
         unsafe {
             let track = sys::libvlc_audio_get_track(self.ptr);
@@ -77,6 +94,7 @@ impl MediaPlayerAudioEx for MediaPlayer {
             }
         }
     }
+    #[cfg(not(feature = "libvlc4"))]
     fn set_audio_track(&self, track: i32) {
         unsafe {
             sys::libvlc_audio_set_track(self.ptr, track);
@@ -3,6 +3,7 @@
 // Licensed under the MIT license, see the LICENSE file.
 
 use std::ptr;
+use std::fmt;
 use std::borrow::Cow;
 use std::marker::PhantomData;
 use std::ffi::CString;
@@ -12,33 +13,157 @@ use libc::{c_void, c_char, c_int};
 use vlc_sys as sys;
 use crate::tools::{to_cstr, from_cstr, from_cstr_ref};
 use crate::enums::*;
+use crate::Media;
+#[cfg(feature = "libvlc4")]
+use crate::Picture;
+use crate::Error;
+#[cfg(feature = "dynamic")]
+use std::path::Path;
+
+/// Load libvlc at runtime. Only available with the `dynamic` feature.
+///
+/// Each entry of `search_path` may be the library file or a directory containing it. They are
+/// tried before the `VLC_LIB_DIR` environment variable and the system library search path.
+/// Calling this is optional, libvlc is otherwise loaded from the default locations on first use.
+#[cfg(feature = "dynamic")]
+pub fn load_library<P: AsRef<Path>>(search_path: &[P]) -> Result<(), Error> {
+    sys::load(search_path).map_err(|e| Error::LibraryNotFound(e.to_string()))
+}
+
+/// Whether libvlc has been loaded. Only available with the `dynamic` feature.
+#[cfg(feature = "dynamic")]
+pub fn is_library_loaded() -> bool {
+    sys::is_loaded()
+}
+
+// Load libvlc from the default locations with the `dynamic` feature, unless it is already loaded.
+// Calls into libvlc which do not go through an instance must be preceded by it.
+pub(crate) fn ensure_loaded() -> Result<(), Error> {
+    #[cfg(feature = "dynamic")]
+    load_library::<&Path>(&[])?;
+    Ok(())
+}
 
 /// Retrieve libvlc version.
+/// With the `dynamic` feature, empty if libvlc cannot be loaded, see [try_version].
 pub fn version() -> String {
-    unsafe{
-        from_cstr_ref(sys::libvlc_get_version()).unwrap().into_owned()
-    }
+    try_version().unwrap_or_default()
+}
+
+/// Retrieve libvlc version, failing with the `dynamic` feature if libvlc cannot be loaded.
+pub fn try_version() -> Result<String, Error> {
+    ensure_loaded()?;
+    Ok(unsafe{
+        from_cstr_ref(sys::libvlc_get_version()).unwrap_or_default().into_owned()
+    })
 }
 
 /// Retrieve libvlc compiler version.
+/// With the `dynamic` feature, empty if libvlc cannot be loaded, see [try_compiler].
 pub fn compiler() -> String {
-    unsafe{
-        from_cstr_ref(sys::libvlc_get_compiler()).unwrap().into_owned()
+    try_compiler().unwrap_or_default()
+}
+
+/// Retrieve libvlc compiler version, failing with the `dynamic` feature if libvlc cannot be loaded.
+pub fn try_compiler() -> Result<String, Error> {
+    ensure_loaded()?;
+    Ok(unsafe{
+        from_cstr_ref(sys::libvlc_get_compiler()).unwrap_or_default().into_owned()
+    })
+}
+
+/// Retrieve the libvlc version as numbers, to check for features of a release at runtime.
+pub fn version_info() -> Option<VersionInfo> {
+    VersionInfo::parse(&try_version().ok()?)
+}
+
+/// A libvlc version such as `3.0.16 Vetinari`. Versions compare by their numbers first.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VersionInfo {
+    pub major: u32,
+    pub minor: u32,
+    pub revision: u32,
+    /// Suffix of the version number, e.g. `-dev` or `.1`.
+    pub extra: Option<String>,
+    /// Release name, e.g. `Vetinari`.
+    pub codename: Option<String>,
+}
+
+impl VersionInfo {
+    /// Parse a version string as returned by [version].
+    pub fn parse(s: &str) -> Option<VersionInfo> {
+        let s = s.trim();
+        let (number, codename) = match s.find(' ') {
+            Some(i) => (&s[..i], Some(s[i + 1..].trim().to_owned())),
+            None => (s, None),
+        };
+
+        let mut rest = number;
+        let mut parts = [0u32; 3];
+        for (i, part) in parts.iter_mut().enumerate() {
+            if i > 0 {
+                match rest.strip_prefix('.') {
+                    Some(r) if r.starts_with(|c: char| c.is_ascii_digit()) => rest = r,
+                    // The revision is optional.
+                    _ if i == 2 => break,
+                    _ => return None,
+                }
+            }
+            let end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+            *part = rest[..end].parse().ok()?;
+            rest = &rest[end..];
+        }
+
+        Some(VersionInfo{
+            major: parts[0],
+            minor: parts[1],
+            revision: parts[2],
+            extra: if rest.is_empty() { None } else { Some(rest.to_owned()) },
+            codename: codename.filter(|c| !c.is_empty()),
+        })
+    }
+
+    /// Whether this is at least version `major.minor.revision`.
+    pub fn at_least(&self, major: u32, minor: u32, revision: u32) -> bool {
+        (self.major, self.minor, self.revision) >= (major, minor, revision)
+    }
+}
+
+impl fmt::Display for VersionInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.revision)?;
+        if let Some(ref extra) = self.extra {
+            f.write_str(extra)?;
+        }
+        if let Some(ref codename) = self.codename {
+            write!(f, " {}", codename)?;
+        }
+        Ok(())
     }
 }
 
 pub struct Instance {
     pub(crate) ptr: *mut sys::libvlc_instance_t,
-
 }
 
 unsafe impl Send for Instance {}
 
+// libvlc instances are reference counted and internally synchronized.
+unsafe impl Sync for Instance {}
+
 impl Instance {
     /// Create and initialize a libvlc instance with specified args.
     /// Note: args.len() has to be less or equal to i32::MAX
     /// Note: libvlc discourages using arguments as these are not guaranteed to be stable between different versions of libvlc
     pub fn with_args(args: Option<Vec<String>>) -> Option<Instance> {
+        Instance::try_with_args(args).ok()
+    }
+
+    /// Like [with_args](Instance::with_args), with the reason the instance could not be created.
+    pub fn try_with_args(args: Option<Vec<String>>) -> Result<Instance, Error> {
+        ensure_loaded()?;
+
         let args_c_ptr: Vec<*const c_char> ;
         let args_c: Vec<CString>;
         if let Some(argv) = args {
@@ -58,10 +183,10 @@ impl Instance {
             };
 
             if p.is_null() {
-                return None;
+                return Err(Error::Libvlc(errmsg().unwrap_or_else(|| "libvlc could not be initialized".to_owned())));
             }
 
-            Some(Instance{ptr: p})
+            Ok(Instance{ptr: p})
         }
     }
 
@@ -92,6 +217,7 @@ impl Instance {
     }
 
     /// Waits until an interface causes the instance to exit.
+    #[cfg(not(feature = "libvlc4"))]
     pub fn wait(&self) {
         unsafe{ sys::libvlc_wait(self.ptr) };
     }
@@ -123,6 +249,7 @@ impl Instance {
     }
 
     /// Returns the VLM event manager
+    #[cfg(not(feature = "libvlc4"))]
     pub fn vlm_event_manager<'a>(&'a self) -> EventManager<'a> {
         unsafe{
             let p = sys::libvlc_vlm_get_event_manager(self.ptr);
@@ -146,6 +273,14 @@ impl Instance {
     }
 }
 
+impl Clone for Instance {
+    /// Retain the instance. The underlying libvlc instance is released when the last clone is dropped.
+    fn clone(&self) -> Instance {
+        unsafe{ sys::libvlc_retain(self.ptr) };
+        Instance{ptr: self.ptr}
+    }
+}
+
 impl Drop for Instance {
     fn drop(&mut self) {
         unsafe{
@@ -201,6 +336,7 @@ pub struct ModuleDescriptionListIter<'a> {
 /// Description of a module.
 /// The strings are owned.
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModuleDescription {
     pub name:      Option<String>,
     pub shortname: Option<String>,
@@ -249,23 +385,40 @@ impl<'a> ModuleDescriptionRef<'a> {
     }
 }
 
+/// The last error reported by libvlc in this thread, if any.
 pub fn errmsg() -> Option<String> {
+    ensure_loaded().ok()?;
     unsafe{ from_cstr(sys::libvlc_errmsg()) }
 }
 
 pub fn clearerr() {
-    unsafe{ sys::libvlc_clearerr() };
+    if ensure_loaded().is_ok() {
+        unsafe{ sys::libvlc_clearerr() };
+    }
 }
 
+/// With the `serde` feature, serialized with the event type in snake case and its data if any,
+/// e.g. `{"type": "media_player_position_changed", "data": 0.5}` or `{"type": "media_player_playing"}`.
+/// The picture of MediaThumbnailGenerated is not serialized, and the media of MediaSubItemAdded
+/// is serialized as its MRL; both are deserialized as None.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "data", rename_all = "snake_case"))]
 pub enum Event {
     MediaMetaChanged(Meta),
-    MediaSubItemAdded,
+    /// The new subitem.
+    #[cfg_attr(feature = "serde", serde(with = "crate::media::media_mrl"))]
+    MediaSubItemAdded(Option<Media>),
     MediaDurationChanged(i64),
     MediaParsedChanged(i32),
+    #[cfg(not(feature = "libvlc4"))]
     MediaFreed,
+    #[cfg(not(feature = "libvlc4"))]
     MediaStateChanged(State),
     MediaSubItemTreeAdded,
+    #[cfg(feature = "libvlc4")]
+    #[cfg_attr(feature = "serde", serde(with = "crate::picture::no_picture"))]
+    MediaThumbnailGenerated(Option<Picture>),
 
     MediaPlayerMediaChanged,
     MediaPlayerNothingSpecial,
@@ -276,17 +429,25 @@ pub enum Event {
     MediaPlayerStopped,
     MediaPlayerForward,
     MediaPlayerBackward,
+    #[cfg(feature = "libvlc4")]
+    MediaPlayerStopping,
+    #[cfg(not(feature = "libvlc4"))]
     MediaPlayerEndReached,
     MediaPlayerEncounteredError,
-    MediaPlayerTimeChanged,
+    MediaPlayerTimeChanged(i64),
     MediaPlayerPositionChanged(f32),
     MediaPlayerSeekableChanged,
     MediaPlayerPausableChanged,
+    #[cfg(not(feature = "libvlc4"))]
     MediaPlayerTitleChanged,
     MediaPlayerSnapshotTaken,
     MediaPlayerLengthChanged,
     MediaPlayerVout,
+    /// The new volume, 1.0 being 100%.
+    MediaPlayerAudioVolume(f32),
+    #[cfg(not(feature = "libvlc4"))]
     MediaPlayerScrambledChanged,
+    #[cfg(not(feature = "libvlc4"))]
     MediaPlayerTeletextChanged(i32),
 
     MediaListItemAdded,
@@ -294,31 +455,136 @@ pub enum Event {
     MediaListItemDeleted,
     MediaListWillDeleteItem,
 
+    #[cfg(not(feature = "libvlc4"))]
     MediaListViewItemAdded,
+    #[cfg(not(feature = "libvlc4"))]
     MediaListViewWillAddItem,
+    #[cfg(not(feature = "libvlc4"))]
     MediaListViewItemDeleted,
+    #[cfg(not(feature = "libvlc4"))]
     MediaListViewWillDeleteItem,
 
     MediaListPlayerPlayed,
     MediaListPlayerNextItemSet,
     MediaListPlayerStopped,
 
+    #[cfg(not(feature = "libvlc4"))]
     MediaDiscovererStarted,
+    #[cfg(not(feature = "libvlc4"))]
     MediaDiscovererEnded,
 
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaAdded(Option<String>, Option<String>),
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaRemoved(Option<String>, Option<String>),
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaChanged(Option<String>, Option<String>),
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaInstanceStarted(Option<String>, Option<String>),
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaInstanceStopped(Option<String>, Option<String>),
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaInstanceStatusInit(Option<String>, Option<String>),
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaInstanceStatusOpening(Option<String>, Option<String>),
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaInstanceStatusPlaying(Option<String>, Option<String>),
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaInstanceStatusPause(Option<String>, Option<String>),
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaInstanceStatusEnd(Option<String>, Option<String>),
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaInstanceStatusError(Option<String>, Option<String>)
 }
 
+impl Event {
+    /// The type of this event, as passed to [EventManager::attach].
+    pub fn event_type(&self) -> EventType {
+        match self {
+            Event::MediaMetaChanged(..) => EventType::MediaMetaChanged,
+            Event::MediaSubItemAdded(_) => EventType::MediaSubItemAdded,
+            Event::MediaDurationChanged(..) => EventType::MediaDurationChanged,
+            Event::MediaParsedChanged(..) => EventType::MediaParsedChanged,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::MediaFreed => EventType::MediaFreed,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::MediaStateChanged(..) => EventType::MediaStateChanged,
+            Event::MediaSubItemTreeAdded => EventType::MediaSubItemTreeAdded,
+            #[cfg(feature = "libvlc4")]
+            Event::MediaThumbnailGenerated(..) => EventType::MediaThumbnailGenerated,
+            Event::MediaPlayerMediaChanged => EventType::MediaPlayerMediaChanged,
+            Event::MediaPlayerNothingSpecial => EventType::MediaPlayerNothingSpecial,
+            Event::MediaPlayerOpening => EventType::MediaPlayerOpening,
+            Event::MediaPlayerBuffering(..) => EventType::MediaPlayerBuffering,
+            Event::MediaPlayerPlaying => EventType::MediaPlayerPlaying,
+            Event::MediaPlayerPaused => EventType::MediaPlayerPaused,
+            Event::MediaPlayerStopped => EventType::MediaPlayerStopped,
+            Event::MediaPlayerForward => EventType::MediaPlayerForward,
+            Event::MediaPlayerBackward => EventType::MediaPlayerBackward,
+            #[cfg(feature = "libvlc4")]
+            Event::MediaPlayerStopping => EventType::MediaPlayerStopping,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::MediaPlayerEndReached => EventType::MediaPlayerEndReached,
+            Event::MediaPlayerEncounteredError => EventType::MediaPlayerEncounteredError,
+            Event::MediaPlayerTimeChanged(..) => EventType::MediaPlayerTimeChanged,
+            Event::MediaPlayerPositionChanged(..) => EventType::MediaPlayerPositionChanged,
+            Event::MediaPlayerSeekableChanged => EventType::MediaPlayerSeekableChanged,
+            Event::MediaPlayerPausableChanged => EventType::MediaPlayerPausableChanged,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::MediaPlayerTitleChanged => EventType::MediaPlayerTitleChanged,
+            Event::MediaPlayerSnapshotTaken => EventType::MediaPlayerSnapshotTaken,
+            Event::MediaPlayerLengthChanged => EventType::MediaPlayerLengthChanged,
+            Event::MediaPlayerVout => EventType::MediaPlayerVout,
+            Event::MediaPlayerAudioVolume(..) => EventType::MediaPlayerAudioVolume,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::MediaPlayerScrambledChanged => EventType::MediaPlayerScrambledChanged,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::MediaPlayerTeletextChanged(..) => EventType::MediaPlayerTeletextChanged,
+            Event::MediaListItemAdded => EventType::MediaListItemAdded,
+            Event::MediaListWillAddItem => EventType::MediaListWillAddItem,
+            Event::MediaListItemDeleted => EventType::MediaListItemDeleted,
+            Event::MediaListWillDeleteItem => EventType::MediaListWillDeleteItem,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::MediaListViewItemAdded => EventType::MediaListViewItemAdded,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::MediaListViewWillAddItem => EventType::MediaListViewWillAddItem,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::MediaListViewItemDeleted => EventType::MediaListViewItemDeleted,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::MediaListViewWillDeleteItem => EventType::MediaListViewWillDeleteItem,
+            Event::MediaListPlayerPlayed => EventType::MediaListPlayerPlayed,
+            Event::MediaListPlayerNextItemSet => EventType::MediaListPlayerNextItemSet,
+            Event::MediaListPlayerStopped => EventType::MediaListPlayerStopped,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::MediaDiscovererStarted => EventType::MediaDiscovererStarted,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::MediaDiscovererEnded => EventType::MediaDiscovererEnded,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::VlmMediaAdded(..) => EventType::VlmMediaAdded,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::VlmMediaRemoved(..) => EventType::VlmMediaRemoved,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::VlmMediaChanged(..) => EventType::VlmMediaChanged,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::VlmMediaInstanceStarted(..) => EventType::VlmMediaInstanceStarted,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::VlmMediaInstanceStopped(..) => EventType::VlmMediaInstanceStopped,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::VlmMediaInstanceStatusInit(..) => EventType::VlmMediaInstanceStatusInit,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::VlmMediaInstanceStatusOpening(..) => EventType::VlmMediaInstanceStatusOpening,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::VlmMediaInstanceStatusPlaying(..) => EventType::VlmMediaInstanceStatusPlaying,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::VlmMediaInstanceStatusPause(..) => EventType::VlmMediaInstanceStatusPause,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::VlmMediaInstanceStatusEnd(..) => EventType::VlmMediaInstanceStatusEnd,
+            #[cfg(not(feature = "libvlc4"))]
+            Event::VlmMediaInstanceStatusError(..) => EventType::VlmMediaInstanceStatusError,
+        }
+    }
+}
+
 pub struct EventManager<'a> {
     pub(crate) ptr: *mut sys::libvlc_event_manager_t,
     pub(crate) _phantomdata: ::std::marker::PhantomData<&'a sys::libvlc_event_manager_t>,
@@ -351,12 +617,29 @@ impl<'a> EventManager<'a> {
         }
     }
 
+    /// Detach a callback and free it.
+    /// `registered_callback` must have been returned by `attach` on this event manager.
+    pub(crate) unsafe fn detach_and_free(&self, event_type: EventType, registered_callback: *mut c_void) {
+        self.detach(event_type, registered_callback);
+        // libvlc holds the event manager lock while dispatching, so the callback is no longer running.
+        drop(Box::from_raw(registered_callback as *mut Box<dyn Fn(Event, VLCObject) + Send + 'static>));
+    }
+
     /// Returns raw pointer
     pub fn raw(&self) -> *mut sys::libvlc_event_manager_t {
         self.ptr
     }
 }
 
+/// A callback attached through [EventManagerApi](crate::EventManagerApi), freed when it is detached.
+pub struct EventHandle {
+    pub(crate) em: *mut sys::libvlc_event_manager_t,
+    pub(crate) event_type: EventType,
+    pub(crate) raw: *mut c_void,
+}
+
+unsafe impl Send for EventHandle {}
+
 unsafe extern "C" fn event_manager_callback(pe: *const sys::libvlc_event_t, data: *mut c_void) {
     let f: &Box<dyn Fn(Event, VLCObject) + Send + 'static> = ::std::mem::transmute(data);
 
@@ -374,7 +657,9 @@ fn conv_event(pe: *const sys::libvlc_event_t) -> Event {
             }
         },
         EventType::MediaSubItemAdded => {
-            Event::MediaSubItemAdded
+            unsafe{
+                Event::MediaSubItemAdded(Media::retain((*pe).u.media_subitem_added.new_child))
+            }
         },
         EventType::MediaDurationChanged => {
             unsafe{
@@ -386,9 +671,11 @@ fn conv_event(pe: *const sys::libvlc_event_t) -> Event {
                 Event::MediaParsedChanged((*pe).u.media_parsed_changed.new_status)
             }
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::MediaFreed => {
             Event::MediaFreed
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::MediaStateChanged => {
             unsafe{
                 let new_state: sys::libvlc_state_t = (*pe).u.media_state_changed.new_state.try_into().unwrap();
@@ -398,6 +685,12 @@ fn conv_event(pe: *const sys::libvlc_event_t) -> Event {
         EventType::MediaSubItemTreeAdded => {
             Event::MediaSubItemTreeAdded
         },
+        #[cfg(feature = "libvlc4")]
+        EventType::MediaThumbnailGenerated => {
+            unsafe{
+                Event::MediaThumbnailGenerated(Picture::retain((*pe).u.media_thumbnail_generated.p_thumbnail))
+            }
+        },
         EventType::MediaPlayerMediaChanged => {
             Event::MediaPlayerMediaChanged
         },
@@ -427,18 +720,30 @@ fn conv_event(pe: *const sys::libvlc_event_t) -> Event {
         EventType::MediaPlayerBackward => {
             Event::MediaPlayerBackward
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::MediaPlayerEndReached => {
             Event::MediaPlayerEndReached
         },
+        #[cfg(feature = "libvlc4")]
+        EventType::MediaPlayerStopping => {
+            Event::MediaPlayerStopping
+        },
         EventType::MediaPlayerEncounteredError => {
             Event::MediaPlayerEncounteredError
         },
         EventType::MediaPlayerTimeChanged => {
-            Event::MediaPlayerTimeChanged
+            unsafe{
+                Event::MediaPlayerTimeChanged((*pe).u.media_player_time_changed.new_time)
+            }
         },
         EventType::MediaPlayerPositionChanged => {
             unsafe{
-                Event::MediaPlayerPositionChanged((*pe).u.media_player_position_changed.new_position)
+                #[cfg(not(feature = "libvlc4"))]
+                let pos = (*pe).u.media_player_position_changed.new_position;
+                // libvlc 4 reports the position as a double.
+                #[cfg(feature = "libvlc4")]
+                let pos = (*pe).u.media_player_position_changed.new_position as f32;
+                Event::MediaPlayerPositionChanged(pos)
             }
         },
         EventType::MediaPlayerSeekableChanged => {
@@ -447,6 +752,7 @@ fn conv_event(pe: *const sys::libvlc_event_t) -> Event {
         EventType::MediaPlayerPausableChanged => {
             Event::MediaPlayerPausableChanged
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::MediaPlayerTitleChanged => {
             Event::MediaPlayerTitleChanged
         },
@@ -459,9 +765,16 @@ fn conv_event(pe: *const sys::libvlc_event_t) -> Event {
         EventType::MediaPlayerVout => {
             Event::MediaPlayerVout
         },
+        EventType::MediaPlayerAudioVolume => {
+            unsafe{
+                Event::MediaPlayerAudioVolume((*pe).u.media_player_audio_volume.volume)
+            }
+        },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::MediaPlayerScrambledChanged => {
             Event::MediaPlayerScrambledChanged
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::MediaPlayerTeletextChanged => {
             unsafe {
                 Event::MediaPlayerTeletextChanged((*pe).u.media_player_teletext_active_page_changed.page)
@@ -479,15 +792,19 @@ fn conv_event(pe: *const sys::libvlc_event_t) -> Event {
         EventType::MediaListWillDeleteItem => {
             Event::MediaListWillDeleteItem
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::MediaListViewItemAdded => {
             Event::MediaListViewItemAdded
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::MediaListViewWillAddItem => {
             Event::MediaListViewWillAddItem
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::MediaListViewItemDeleted => {
             Event::MediaListViewItemDeleted
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::MediaListViewWillDeleteItem => {
             Event::MediaListViewWillDeleteItem
         },
@@ -500,62 +817,75 @@ fn conv_event(pe: *const sys::libvlc_event_t) -> Event {
         EventType::MediaListPlayerStopped => {
             Event::MediaListPlayerStopped
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::MediaDiscovererStarted => {
             Event::MediaDiscovererStarted
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::MediaDiscovererEnded => {
             Event::MediaDiscovererEnded
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::VlmMediaAdded => {
             unsafe {
                 Event::VlmMediaAdded(from_cstr((*pe).u.vlm_media_event.psz_instance_name), from_cstr((*pe).u.vlm_media_event.psz_media_name))
             }
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::VlmMediaRemoved => {
             unsafe {
                 Event::VlmMediaRemoved(from_cstr((*pe).u.vlm_media_event.psz_instance_name), from_cstr((*pe).u.vlm_media_event.psz_media_name))
             }
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::VlmMediaChanged => {
             unsafe {
                 Event::VlmMediaChanged(from_cstr((*pe).u.vlm_media_event.psz_instance_name), from_cstr((*pe).u.vlm_media_event.psz_media_name))
             }
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::VlmMediaInstanceStarted => {
             unsafe {
                 Event::VlmMediaInstanceStarted(from_cstr((*pe).u.vlm_media_event.psz_instance_name), from_cstr((*pe).u.vlm_media_event.psz_media_name))
             }
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::VlmMediaInstanceStopped => {
             unsafe {
                 Event::VlmMediaInstanceStopped(from_cstr((*pe).u.vlm_media_event.psz_instance_name), from_cstr((*pe).u.vlm_media_event.psz_media_name))
             }
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::VlmMediaInstanceStatusInit => {
             unsafe {
                 Event::VlmMediaInstanceStatusInit(from_cstr((*pe).u.vlm_media_event.psz_instance_name), from_cstr((*pe).u.vlm_media_event.psz_media_name))
             }
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::VlmMediaInstanceStatusOpening => {
             unsafe {
                 Event::VlmMediaInstanceStatusOpening(from_cstr((*pe).u.vlm_media_event.psz_instance_name), from_cstr((*pe).u.vlm_media_event.psz_media_name))
             }
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::VlmMediaInstanceStatusPlaying => {
             unsafe {
                 Event::VlmMediaInstanceStatusPlaying(from_cstr((*pe).u.vlm_media_event.psz_instance_name), from_cstr((*pe).u.vlm_media_event.psz_media_name))
             }
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::VlmMediaInstanceStatusPause => {
             unsafe {
                 Event::VlmMediaInstanceStatusPause(from_cstr((*pe).u.vlm_media_event.psz_instance_name), from_cstr((*pe).u.vlm_media_event.psz_media_name))
             }
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::VlmMediaInstanceStatusEnd => {
             unsafe {
                 Event::VlmMediaInstanceStatusEnd(from_cstr((*pe).u.vlm_media_event.psz_instance_name), from_cstr((*pe).u.vlm_media_event.psz_media_name))
             }
         },
+        #[cfg(not(feature = "libvlc4"))]
         EventType::VlmMediaInstanceStatusError => {
             unsafe {
                 Event::VlmMediaInstanceStatusError(from_cstr((*pe).u.vlm_media_event.psz_instance_name), from_cstr((*pe).u.vlm_media_event.psz_media_name))
@@ -565,7 +895,7 @@ fn conv_event(pe: *const sys::libvlc_event_t) -> Event {
 }
 
 pub struct VLCObject {
-    ptr: *mut c_void,
+    pub(crate) ptr: *mut c_void,
 }
 
 impl VLCObject {
@@ -0,0 +1,429 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::f32::consts::FRAC_PI_2;
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::{Instance, MediaList, MediaPlayer, MediaPlayerAudioEx, PlaybackMode, State};
+use crate::{MediaApi, MediaListApi, MediaPlayerApi};
+
+// How often the players are checked and their volumes updated.
+const TICK: Duration = Duration::from_millis(40);
+
+/// How volumes change during a crossfade.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FadeCurve {
+    Linear,
+    /// Keeps the total power constant, so that the loudness does not dip in the middle.
+    #[default]
+    EqualPower,
+    /// Slow at both ends and fast in the middle.
+    SCurve,
+}
+
+impl FadeCurve {
+    /// The gain of the incoming item at `progress`, from 0 to 1.
+    /// The outgoing item has the gain at `1 - progress`.
+    pub fn gain(self, progress: f32) -> f32 {
+        let p = progress.clamp(0.0, 1.0);
+        match self {
+            FadeCurve::Linear => p,
+            FadeCurve::EqualPower => (p * FRAC_PI_2).sin(),
+            FadeCurve::SCurve => p * p * (3.0 - 2.0 * p),
+        }
+    }
+}
+
+// The item started on the other player before the current one ends.
+struct Incoming {
+    index: i32,
+    fading: bool,
+}
+
+struct FadeState<L> {
+    list: Option<L>,
+    mode: PlaybackMode,
+    fade: Duration,
+    preroll: Duration,
+    curve: FadeCurve,
+    volume: i32,
+    // Which of the two players is playing the current item.
+    current: usize,
+    index: Option<i32>,
+    active: bool,
+    paused: bool,
+    // Whether the current player started, so that it being stopped means it ended.
+    started: bool,
+    incoming: Option<Incoming>,
+}
+
+impl<L: MediaListApi> FadeState<L> {
+    // The item `offset` away from the current one, wrapping around in loop mode.
+    fn relative_index(&self, offset: i32) -> Option<i32> {
+        let count = self.list.as_ref().map_or(0, |list| list.count());
+        if count == 0 {
+            return None;
+        }
+        let index = match self.index {
+            Some(index) => index + offset,
+            None => 0,
+        };
+        if (0..count).contains(&index) {
+            Some(index)
+        } else if self.mode == PlaybackMode::Loop {
+            Some(index.rem_euclid(count))
+        } else {
+            None
+        }
+    }
+
+    // The item to play when the current one ends.
+    fn following(&self) -> Option<i32> {
+        match (self.mode, self.index) {
+            (PlaybackMode::Repeat, Some(index)) => Some(index),
+            _ => self.relative_index(1),
+        }
+    }
+
+    fn scaled(&self, gain: f32) -> i32 {
+        (self.volume as f32 * gain).round() as i32
+    }
+}
+
+struct Shared<P, L> {
+    players: [P; 2],
+    state: Mutex<FadeState<L>>,
+}
+
+impl<P, L> Shared<P, L>
+    where P: MediaPlayerApi + MediaPlayerAudioEx, L: MediaListApi<Media = P::Media>
+{
+    fn lock(&self) -> MutexGuard<'_, FadeState<L>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn drop_incoming(&self, st: &mut FadeState<L>) {
+        if st.incoming.take().is_some() {
+            self.players[1 - st.current].stop();
+        }
+    }
+
+    // Switch to the item at `index` right away.
+    fn start(&self, st: &mut FadeState<L>, index: i32) -> Result<(), ()> {
+        let md = st.list.as_ref().and_then(|list| list.item_at_index(index)).ok_or(())?;
+        self.drop_incoming(st);
+        let player = &self.players[st.current];
+        player.set_media(&md);
+        let _ = player.set_volume(st.volume);
+        st.index = Some(index);
+        st.active = true;
+        st.paused = false;
+        st.started = false;
+        player.play()
+    }
+
+    // Open the next item on the other player, muted, to be paused once it plays and rewound when the fade begins.
+    fn prepare(&self, st: &mut FadeState<L>, index: i32) {
+        let md = match st.list.as_ref().and_then(|list| list.item_at_index(index)) {
+            Some(md) => md,
+            None => return,
+        };
+        let player = &self.players[1 - st.current];
+        player.set_media(&md);
+        let _ = player.set_volume(0);
+        let _ = player.play();
+        st.incoming = Some(Incoming { index, fading: false });
+    }
+
+    // The current item ended: the incoming one takes over, or the next one starts.
+    fn advance(&self, st: &mut FadeState<L>) {
+        let ended = st.current;
+        match st.incoming.take() {
+            Some(incoming) => {
+                st.current = 1 - ended;
+                st.index = Some(incoming.index);
+                st.started = false;
+                let player = &self.players[st.current];
+                let _ = player.set_volume(st.volume);
+                player.set_pause(false);
+            },
+            None => {
+                let started = match st.following() {
+                    Some(index) => self.start(st, index).is_ok(),
+                    None => false,
+                };
+                if !started {
+                    st.active = false;
+                }
+            },
+        }
+        if st.current != ended {
+            self.players[ended].stop();
+        }
+    }
+
+    fn tick(&self) {
+        let mut st = self.lock();
+        let other = &self.players[1 - st.current];
+        if !st.active {
+            return;
+        }
+        if st.paused {
+            // An incoming item still opening must not play behind the pause.
+            if st.incoming.as_ref().is_some_and(|i| !i.fading) && other.state() == State::Playing {
+                other.set_pause(true);
+            }
+            return;
+        }
+
+        let player = &self.players[st.current];
+        match player.state() {
+            State::Opening | State::Buffering | State::Playing | State::Paused => st.started = true,
+            State::Error => return self.advance(&mut st),
+            _ if st.started => return self.advance(&mut st),
+            _ => return,
+        }
+
+        // Streams without a length are only switched when they end.
+        let remaining = match (player.get_media().and_then(|md| md.length()), player.time()) {
+            (Some(length), Some(time)) => length.saturating_sub(time),
+            _ => return,
+        };
+        if st.incoming.is_none() && remaining <= st.fade + st.preroll {
+            if let Some(index) = st.following() {
+                self.prepare(&mut st, index);
+            }
+        }
+
+        let fade = st.fade;
+        let Some(incoming) = st.incoming.as_mut() else { return };
+        if !incoming.fading {
+            if remaining > fade {
+                if other.state() == State::Playing {
+                    other.set_pause(true);
+                }
+                return;
+            }
+            incoming.fading = true;
+            // It played muted until it was paused.
+            if matches!(other.time(), Some(time) if time > Duration::ZERO) {
+                other.seek(Duration::ZERO);
+            }
+            other.set_pause(false);
+        }
+
+        let progress = if fade.is_zero() {
+            1.0
+        } else {
+            1.0 - remaining.as_secs_f32() / fade.as_secs_f32()
+        };
+        let _ = player.set_volume(st.scaled(st.curve.gain(1.0 - progress)));
+        let _ = other.set_volume(st.scaled(st.curve.gain(progress)));
+    }
+}
+
+/// Plays a media list on two players, starting each item before the previous one ends and crossfading them.
+///
+/// Once the current item has less than the fade duration plus the preroll left, the next item is opened
+/// muted and paused on the other player. The fade starts it over from its beginning when the fade duration is left,
+/// ramping the volumes on a [FadeCurve]. With no fade, the next item starts as soon as the current one ends, without
+/// waiting for it to open.
+///
+/// The fade is computed from the media length and the player time, so items without a length are not crossfaded.
+/// Skipping with [next](CrossfadePlayer::next), [previous](CrossfadePlayer::previous) or
+/// [play_item_at_index](CrossfadePlayer::play_item_at_index) switches at once.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use vlc::{CrossfadePlayer, Instance, Media, MediaList};
+///
+/// let instance = Instance::new().unwrap();
+/// let list = MediaList::new(&instance).unwrap();
+/// for path in &["a.ogg", "b.ogg"] {
+///     list.add_media(&Media::new_path(&instance, path).unwrap()).unwrap();
+/// }
+/// let player = CrossfadePlayer::new(&instance).unwrap();
+/// player.set_fade(Duration::from_secs(6));
+/// player.set_media_list(&list);
+/// player.play();
+/// ```
+pub struct CrossfadePlayer<P = MediaPlayer, L = MediaList> {
+    shared: Arc<Shared<P, L>>,
+    quit: Sender<()>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl CrossfadePlayer {
+    /// Create a crossfading player with two media players on `instance`.
+    pub fn new(instance: &Instance) -> Option<CrossfadePlayer> {
+        let a = MediaPlayer::new(instance)?;
+        let b = MediaPlayer::new(instance)?;
+        Some(CrossfadePlayer::with_players(a, b))
+    }
+}
+
+impl<P, L> CrossfadePlayer<P, L>
+    where P: MediaPlayerApi + MediaPlayerAudioEx + Clone + Send + Sync + 'static,
+          L: MediaListApi<Media = P::Media> + Clone + Send + 'static
+{
+    /// Create a crossfading player alternating between `a` and `b`.
+    /// The default fade is 5 seconds, with a preroll of 3 seconds.
+    pub fn with_players(a: P, b: P) -> CrossfadePlayer<P, L> {
+        let shared = Arc::new(Shared {
+            players: [a, b],
+            state: Mutex::new(FadeState {
+                list: None,
+                mode: PlaybackMode::Default,
+                fade: Duration::from_secs(5),
+                preroll: Duration::from_secs(3),
+                curve: FadeCurve::default(),
+                volume: 100,
+                current: 0,
+                index: None,
+                active: false,
+                paused: false,
+                started: false,
+                incoming: None,
+            }),
+        });
+
+        let (quit, rx) = channel();
+        let worker_shared = shared.clone();
+        let worker = thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = rx.recv_timeout(TICK) {
+                worker_shared.tick();
+            }
+        });
+
+        CrossfadePlayer { shared, quit, worker: Some(worker) }
+    }
+
+    /// The player of the current item.
+    pub fn get_media_player(&self) -> Option<P> {
+        let st = self.shared.lock();
+        Some(self.shared.players[st.current].clone())
+    }
+
+    /// Replace the media list. The current item goes on, and the next one is taken from the new list.
+    pub fn set_media_list(&self, media_list: &L) {
+        let mut st = self.shared.lock();
+        self.shared.drop_incoming(&mut st);
+        st.list = Some(media_list.clone());
+        st.index = None;
+    }
+
+    /// Resume, or play the current item, or the first one.
+    pub fn play(&self) {
+        let mut st = self.shared.lock();
+        if st.paused {
+            drop(st);
+            self.set_pause(false);
+        } else if !st.active {
+            let index = st.index.unwrap_or(0);
+            let _ = self.shared.start(&mut st, index);
+        }
+    }
+
+    pub fn set_pause(&self, pause: bool) {
+        let mut st = self.shared.lock();
+        if !st.active || st.paused == pause {
+            return;
+        }
+        st.paused = pause;
+        let players = &self.shared.players;
+        players[st.current].set_pause(pause);
+        if st.incoming.as_ref().is_some_and(|i| i.fading || pause) {
+            players[1 - st.current].set_pause(pause);
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        let st = self.shared.lock();
+        st.active && !st.paused && self.shared.players.iter().any(|p| p.is_playing())
+    }
+
+    pub fn play_item_at_index(&self, index: i32) -> Result<(), ()> {
+        let mut st = self.shared.lock();
+        self.shared.start(&mut st, index)
+    }
+
+    pub fn stop(&self) {
+        let mut st = self.shared.lock();
+        self.shared.drop_incoming(&mut st);
+        self.shared.players[st.current].stop();
+        st.active = false;
+        st.paused = false;
+        st.started = false;
+    }
+
+    pub fn next(&self) -> Result<(), ()> {
+        let mut st = self.shared.lock();
+        let index = st.relative_index(1).ok_or(())?;
+        self.shared.start(&mut st, index)
+    }
+
+    pub fn previous(&self) -> Result<(), ()> {
+        let mut st = self.shared.lock();
+        let index = st.relative_index(-1).ok_or(())?;
+        self.shared.start(&mut st, index)
+    }
+
+    pub fn set_playback_mode(&self, mode: PlaybackMode) {
+        self.shared.lock().mode = mode;
+    }
+
+    /// Index in the media list of the current item.
+    pub fn current_index(&self) -> Option<i32> {
+        self.shared.lock().index
+    }
+
+    /// How long the items overlap. Zero switches without overlap.
+    pub fn set_fade(&self, fade: Duration) {
+        self.shared.lock().fade = fade;
+    }
+
+    pub fn fade(&self) -> Duration {
+        self.shared.lock().fade
+    }
+
+    /// How long before the fade the next item is opened, to be buffered when it starts.
+    pub fn set_preroll(&self, preroll: Duration) {
+        self.shared.lock().preroll = preroll;
+    }
+
+    pub fn set_curve(&self, curve: FadeCurve) {
+        self.shared.lock().curve = curve;
+    }
+
+    /// Set the volume in percent, from 0 to 200, the fades scaling from it.
+    pub fn set_volume(&self, volume: i32) -> Result<(), ()> {
+        if !(0..=200).contains(&volume) {
+            return Err(());
+        }
+        let mut st = self.shared.lock();
+        st.volume = volume;
+        if st.incoming.as_ref().map_or(true, |i| !i.fading) {
+            self.shared.players[st.current].set_volume(volume)?;
+        }
+        Ok(())
+    }
+
+    pub fn get_volume(&self) -> i32 {
+        self.shared.lock().volume
+    }
+}
+
+impl<P, L> Drop for CrossfadePlayer<P, L> {
+    fn drop(&mut self) {
+        let _ = self.quit.send(());
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
@@ -4,12 +4,19 @@
 
 use vlc_sys as sys;
 
+// With the `serde` feature, variants are serialized by name in snake case, e.g. `"nothing_special"`,
+// or by the name given after `as`.
 macro_rules! define_enum {
-    ($enum_name:ident, $original_type:ident; $($value:ident = $c_value:ident,)*) => {
+    ($(#[$enum_attr:meta])* $enum_name:ident, $original_type:ident; $($(#[$attr:meta])* $value:ident = $c_value:ident $(as $rename:literal)?,)*) => {
         #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
         #[repr(C)]
+        $(#[$enum_attr])*
         pub enum $enum_name {
             $(
+                $(#[$attr])*
+                $(#[cfg_attr(feature = "serde", serde(rename = $rename))])?
                 $value = sys::$c_value as isize,
             )*
         }
@@ -18,6 +25,7 @@ macro_rules! define_enum {
             fn from(a: sys::$original_type) -> Self {
                 match a {
                     $(
+                        $(#[$attr])*
                         sys::$c_value => Self::$value,
                     )*
                     _ => unreachable!(),
@@ -47,13 +55,13 @@ define_enum!(
     Rating = libvlc_meta_t_libvlc_meta_Rating,
     Date = libvlc_meta_t_libvlc_meta_Date,
     Setting = libvlc_meta_t_libvlc_meta_Setting,
-    URL = libvlc_meta_t_libvlc_meta_URL,
+    URL = libvlc_meta_t_libvlc_meta_URL as "url",
     Language = libvlc_meta_t_libvlc_meta_Language,
     NowPlaying = libvlc_meta_t_libvlc_meta_NowPlaying,
     Publisher = libvlc_meta_t_libvlc_meta_Publisher,
     EncodedBy = libvlc_meta_t_libvlc_meta_EncodedBy,
-    ArtworkURL = libvlc_meta_t_libvlc_meta_ArtworkURL,
-    TrackID = libvlc_meta_t_libvlc_meta_TrackID,
+    ArtworkURL = libvlc_meta_t_libvlc_meta_ArtworkURL as "artwork_url",
+    TrackID = libvlc_meta_t_libvlc_meta_TrackID as "track_id",
     TrackTotal = libvlc_meta_t_libvlc_meta_TrackTotal,
     Director = libvlc_meta_t_libvlc_meta_Director,
     Season = libvlc_meta_t_libvlc_meta_Season,
@@ -82,6 +90,16 @@ define_enum!(
     Text = libvlc_track_type_t_libvlc_track_text,
 );
 
+define_enum!(
+    MediaType, libvlc_media_type_t;
+    Unknown = libvlc_media_type_t_libvlc_media_type_unknown,
+    File = libvlc_media_type_t_libvlc_media_type_file,
+    Directory = libvlc_media_type_t_libvlc_media_type_directory,
+    Disc = libvlc_media_type_t_libvlc_media_type_disc,
+    Stream = libvlc_media_type_t_libvlc_media_type_stream,
+    Playlist = libvlc_media_type_t_libvlc_media_type_playlist,
+);
+
 define_enum!(
     Position, libvlc_position_t;
     Disable = libvlc_position_t_libvlc_position_disable,
@@ -106,15 +124,22 @@ define_enum!(
     Gamma = libvlc_video_adjust_option_t_libvlc_adjust_Gamma,
 );
 
-// libvlc 3.0
-// define_enum!(
-//     ParseFlag, libvlc_media_parse_flag_t;
-//     DoInteract = libvlc_media_parse_flag_t_libvlc_media_do_interact,
-//     FetchLocal = libvlc_media_parse_flag_t_libvlc_media_fetch_local,
-//     FetchNetwork = libvlc_media_parse_flag_t_libvlc_media_fetch_network,
-//     ParseLocal = libvlc_media_parse_flag_t_libvlc_media_parse_local,
-//     ParseNetwork = libvlc_media_parse_flag_t_libvlc_media_parse_network,
-// );
+define_enum!(
+    ParseFlag, libvlc_media_parse_flag_t;
+    DoInteract = libvlc_media_parse_flag_t_libvlc_media_do_interact,
+    FetchLocal = libvlc_media_parse_flag_t_libvlc_media_fetch_local,
+    FetchNetwork = libvlc_media_parse_flag_t_libvlc_media_fetch_network,
+    ParseLocal = libvlc_media_parse_flag_t_libvlc_media_parse_local,
+    ParseNetwork = libvlc_media_parse_flag_t_libvlc_media_parse_network,
+);
+
+define_enum!(
+    ParsedStatus, libvlc_media_parsed_status_t;
+    Skipped = libvlc_media_parsed_status_t_libvlc_media_parsed_status_skipped,
+    Failed = libvlc_media_parsed_status_t_libvlc_media_parsed_status_failed,
+    Timeout = libvlc_media_parsed_status_t_libvlc_media_parsed_status_timeout,
+    Done = libvlc_media_parsed_status_t_libvlc_media_parsed_status_done,
+);
 
 define_enum!(
     EventType, libvlc_event_e;
@@ -122,9 +147,13 @@ define_enum!(
     MediaSubItemAdded = libvlc_event_e_libvlc_MediaSubItemAdded,
     MediaDurationChanged = libvlc_event_e_libvlc_MediaDurationChanged,
     MediaParsedChanged = libvlc_event_e_libvlc_MediaParsedChanged,
+    #[cfg(not(feature = "libvlc4"))]
     MediaFreed = libvlc_event_e_libvlc_MediaFreed,
+    #[cfg(not(feature = "libvlc4"))]
     MediaStateChanged = libvlc_event_e_libvlc_MediaStateChanged,
     MediaSubItemTreeAdded = libvlc_event_e_libvlc_MediaSubItemTreeAdded,
+    #[cfg(feature = "libvlc4")]
+    MediaThumbnailGenerated = libvlc_event_e_libvlc_MediaThumbnailGenerated,
     MediaPlayerMediaChanged = libvlc_event_e_libvlc_MediaPlayerMediaChanged,
     MediaPlayerNothingSpecial = libvlc_event_e_libvlc_MediaPlayerNothingSpecial,
     MediaPlayerOpening = libvlc_event_e_libvlc_MediaPlayerOpening,
@@ -134,44 +163,75 @@ define_enum!(
     MediaPlayerStopped = libvlc_event_e_libvlc_MediaPlayerStopped,
     MediaPlayerForward = libvlc_event_e_libvlc_MediaPlayerForward,
     MediaPlayerBackward = libvlc_event_e_libvlc_MediaPlayerBackward,
+    #[cfg(feature = "libvlc4")]
+    MediaPlayerStopping = libvlc_event_e_libvlc_MediaPlayerStopping,
+    #[cfg(not(feature = "libvlc4"))]
     MediaPlayerEndReached = libvlc_event_e_libvlc_MediaPlayerEndReached,
     MediaPlayerEncounteredError = libvlc_event_e_libvlc_MediaPlayerEncounteredError,
     MediaPlayerTimeChanged = libvlc_event_e_libvlc_MediaPlayerTimeChanged,
     MediaPlayerPositionChanged = libvlc_event_e_libvlc_MediaPlayerPositionChanged,
     MediaPlayerSeekableChanged = libvlc_event_e_libvlc_MediaPlayerSeekableChanged,
     MediaPlayerPausableChanged = libvlc_event_e_libvlc_MediaPlayerPausableChanged,
+    #[cfg(not(feature = "libvlc4"))]
     MediaPlayerTitleChanged = libvlc_event_e_libvlc_MediaPlayerTitleChanged,
     MediaPlayerSnapshotTaken = libvlc_event_e_libvlc_MediaPlayerSnapshotTaken,
     MediaPlayerLengthChanged = libvlc_event_e_libvlc_MediaPlayerLengthChanged,
     MediaPlayerVout = libvlc_event_e_libvlc_MediaPlayerVout,
+    MediaPlayerAudioVolume = libvlc_event_e_libvlc_MediaPlayerAudioVolume,
+    #[cfg(not(feature = "libvlc4"))]
     MediaPlayerScrambledChanged = libvlc_event_e_libvlc_MediaPlayerScrambledChanged,
+    #[cfg(not(feature = "libvlc4"))]
     MediaPlayerTeletextChanged = libvlc_event_e_libvlc_MediaPlayerTeletextActivePageChanged,
     MediaListItemAdded = libvlc_event_e_libvlc_MediaListItemAdded,
     MediaListWillAddItem = libvlc_event_e_libvlc_MediaListWillAddItem,
     MediaListItemDeleted = libvlc_event_e_libvlc_MediaListItemDeleted,
     MediaListWillDeleteItem = libvlc_event_e_libvlc_MediaListWillDeleteItem,
+    #[cfg(not(feature = "libvlc4"))]
     MediaListViewItemAdded = libvlc_event_e_libvlc_MediaListViewItemAdded,
+    #[cfg(not(feature = "libvlc4"))]
     MediaListViewWillAddItem = libvlc_event_e_libvlc_MediaListViewWillAddItem,
+    #[cfg(not(feature = "libvlc4"))]
     MediaListViewItemDeleted = libvlc_event_e_libvlc_MediaListViewItemDeleted,
+    #[cfg(not(feature = "libvlc4"))]
     MediaListViewWillDeleteItem = libvlc_event_e_libvlc_MediaListViewWillDeleteItem,
     MediaListPlayerPlayed = libvlc_event_e_libvlc_MediaListPlayerPlayed,
     MediaListPlayerNextItemSet = libvlc_event_e_libvlc_MediaListPlayerNextItemSet,
     MediaListPlayerStopped = libvlc_event_e_libvlc_MediaListPlayerStopped,
+    #[cfg(not(feature = "libvlc4"))]
     MediaDiscovererStarted = libvlc_event_e_libvlc_MediaDiscovererStarted,
+    #[cfg(not(feature = "libvlc4"))]
     MediaDiscovererEnded = libvlc_event_e_libvlc_MediaDiscovererEnded,
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaAdded = libvlc_event_e_libvlc_VlmMediaAdded,
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaRemoved = libvlc_event_e_libvlc_VlmMediaRemoved,
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaChanged = libvlc_event_e_libvlc_VlmMediaChanged,
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaInstanceStarted = libvlc_event_e_libvlc_VlmMediaInstanceStarted,
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaInstanceStopped = libvlc_event_e_libvlc_VlmMediaInstanceStopped,
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaInstanceStatusInit = libvlc_event_e_libvlc_VlmMediaInstanceStatusInit,
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaInstanceStatusOpening = libvlc_event_e_libvlc_VlmMediaInstanceStatusOpening,
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaInstanceStatusPlaying = libvlc_event_e_libvlc_VlmMediaInstanceStatusPlaying,
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaInstanceStatusPause = libvlc_event_e_libvlc_VlmMediaInstanceStatusPause,
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaInstanceStatusEnd = libvlc_event_e_libvlc_VlmMediaInstanceStatusEnd,
+    #[cfg(not(feature = "libvlc4"))]
     VlmMediaInstanceStatusError = libvlc_event_e_libvlc_VlmMediaInstanceStatusError,
 );
 
+#[cfg(feature = "libvlc4")]
+impl EventType {
+    /// libvlc 4 has no end of media event: the player stops at the end, sending MediaPlayerStopping.
+    #[allow(non_upper_case_globals)]
+    pub const MediaPlayerEndReached: EventType = EventType::MediaPlayerStopping;
+}
+
 define_enum!(
     TeletextKey, libvlc_teletext_key_t;
     Red = libvlc_teletext_key_t_libvlc_teletext_key_red,
@@ -187,3 +247,11 @@ define_enum!(
     Loop = libvlc_playback_mode_t_libvlc_playback_mode_loop,
     Repeat = libvlc_playback_mode_t_libvlc_playback_mode_repeat,
 );
+
+#[cfg(feature = "libvlc4")]
+define_enum!(
+    PictureType, libvlc_picture_type_t;
+    Argb = libvlc_picture_type_t_libvlc_picture_Argb,
+    Png = libvlc_picture_type_t_libvlc_picture_Png,
+    Jpg = libvlc_picture_type_t_libvlc_picture_Jpg,
+);
@@ -0,0 +1,26 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::error;
+use std::fmt;
+
+/// Errors reported by vlc-rs.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// libvlc could not be loaded at runtime. Contains the loader's message.
+    LibraryNotFound(String),
+    /// libvlc failed. Contains its message.
+    Libvlc(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::LibraryNotFound(msg) | Error::Libvlc(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl error::Error for Error {}
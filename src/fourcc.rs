@@ -0,0 +1,98 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A codec or chroma identifier as used by libvlc, four bytes packed in little endian order.
+///
+/// Displayed as its characters without trailing spaces, e.g. `h264` or `mp4a`,
+/// and as hexadecimal when they are not printable.
+/// With the `serde` feature, serialized as its displayed string.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct FourCC(pub u32);
+
+impl FourCC {
+    pub const H264: FourCC = FourCC::new(*b"h264");
+    pub const HEVC: FourCC = FourCC::new(*b"hevc");
+    pub const VP9: FourCC = FourCC::new(*b"VP90");
+    pub const AV1: FourCC = FourCC::new(*b"av01");
+    pub const AAC: FourCC = FourCC::new(*b"mp4a");
+    pub const OPUS: FourCC = FourCC::new(*b"Opus");
+    /// MPEG audio layers 1 to 3, as libvlc reports MP3 streams.
+    pub const MP3: FourCC = FourCC::new(*b"mpga");
+    pub const FLAC: FourCC = FourCC::new(*b"flac");
+
+    pub const fn new(bytes: [u8; 4]) -> FourCC {
+        FourCC(u32::from_le_bytes(bytes))
+    }
+
+    pub fn to_bytes(self) -> [u8; 4] {
+        self.0.to_le_bytes()
+    }
+}
+
+impl From<u32> for FourCC {
+    fn from(v: u32) -> FourCC {
+        FourCC(v)
+    }
+}
+
+impl From<FourCC> for u32 {
+    fn from(f: FourCC) -> u32 {
+        f.0
+    }
+}
+
+impl fmt::Display for FourCC {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bytes = self.to_bytes();
+        if bytes.iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+            let s: String = bytes.iter().map(|&b| b as char).collect();
+            f.write_str(s.trim_end_matches(' '))
+        } else {
+            write!(f, "0x{:08x}", self.0)
+        }
+    }
+}
+
+impl fmt::Debug for FourCC {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FourCC({})", self)
+    }
+}
+
+/// Parse one to four ASCII characters, padded with spaces, or the hexadecimal form of [Display](fmt::Display).
+impl FromStr for FourCC {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<FourCC, ()> {
+        if let Some(hex) = s.strip_prefix("0x") {
+            if hex.len() == 8 {
+                return u32::from_str_radix(hex, 16).map(FourCC).map_err(|_| ());
+            }
+        }
+        if s.is_empty() || s.len() > 4 || !s.bytes().all(|b| b.is_ascii_graphic() || b == b' ') {
+            return Err(());
+        }
+        let mut bytes = [b' '; 4];
+        bytes[..s.len()].copy_from_slice(s.as_bytes());
+        Ok(FourCC::new(bytes))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for FourCC {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for FourCC {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<FourCC, D::Error> {
+        let s = <String as serde::Deserialize>::deserialize(d)?;
+        s.parse().map_err(|_| serde::de::Error::custom(format!("invalid FourCC {:?}", s)))
+    }
+}
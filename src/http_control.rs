@@ -0,0 +1,554 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+//! A small HTTP server to control a player with JSON requests, enabled by the `http-control` feature.
+//!
+//! It serves plain HTTP/1.1 on the address it is given, one request per connection, without authentication:
+//! bind it to localhost unless the network is trusted.
+//!
+//! | Request | Body | Response |
+//! |---|---|---|
+//! | `GET /status` | | [PlaybackStatus](crate::PlaybackStatus) with `mrl` and `tracks` |
+//! | `POST /play`, `/pause`, `/stop`, `/next`, `/previous` | | |
+//! | `POST /seek` | `{"time": ms}` or `{"position": 0.5}` | |
+//! | `POST /volume` | `{"volume": 80}`, `{"muted": true}` | |
+//! | `GET /tracks` | | audio, video and spu track lists with the selected ids |
+//! | `POST /tracks/{audio,video,spu}` | `{"id": 2}` | |
+//! | `GET /playlist` | | the items, with `index`, `mrl`, `title` and `duration` in ms |
+//! | `POST /playlist` | `{"mrl": "..."}` or `{"path": "..."}`, and `"index"` | the new item |
+//! | `GET`, `PUT`, `DELETE /playlist/{index}` | for `PUT`, as `POST /playlist` | the item |
+//! | `POST /playlist/{index}/play` | | |
+//! | `GET /events` | | a stream of [Event](crate::Event)s as server-sent events |
+//!
+//! Commands answer `{}`, and errors `{"error": "..."}` with a 4xx status.
+//! Against requests from web pages, the `Host` and any `Origin` header must name the server, as `localhost`
+//! or the address it was reached at, and a body must be sent as `application/json`.
+//! The playlist routes and `/next` and `/previous` need [Controls::with_playlist].
+//!
+//! ```no_run
+//! use vlc::{Instance, MediaList, MediaListPlayer, MediaPlayer};
+//! use vlc::http_control::{ControlServer, Controls};
+//!
+//! let instance = Instance::new().unwrap();
+//! let list = MediaList::new(&instance).unwrap();
+//! let list_player = MediaListPlayer::new(&instance).unwrap();
+//! list_player.set_media_list(&list);
+//! let player = list_player.get_media_player().unwrap();
+//!
+//! let controls = Controls::new(&instance, &player).with_playlist(&list_player, &list);
+//! let server = ControlServer::localhost(8080, controls).unwrap();
+//! println!("listening on http://{}", server.local_addr());
+//! ```
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use serde_json::{json, Map, Value};
+
+use crate::{Event, EventManagerApi, EventType, Instance, Media, MediaList, MediaListPlayer, MediaPlayer, Meta};
+use crate::{MediaPlayerAudioEx, MediaPlayerVideoEx};
+
+const MAX_BODY: usize = 64 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+// How often event streams check whether the server stopped, and send a comment to detect closed connections.
+const EVENT_POLL: Duration = Duration::from_secs(1);
+const KEEP_ALIVE_POLLS: u32 = 15;
+
+const PLAYER_EVENTS: &[EventType] = &[
+    EventType::MediaPlayerMediaChanged,
+    EventType::MediaPlayerOpening,
+    EventType::MediaPlayerBuffering,
+    EventType::MediaPlayerPlaying,
+    EventType::MediaPlayerPaused,
+    EventType::MediaPlayerStopped,
+    #[cfg(feature = "libvlc4")]
+    EventType::MediaPlayerStopping,
+    #[cfg(not(feature = "libvlc4"))]
+    EventType::MediaPlayerEndReached,
+    EventType::MediaPlayerEncounteredError,
+    EventType::MediaPlayerTimeChanged,
+    EventType::MediaPlayerPositionChanged,
+    EventType::MediaPlayerSeekableChanged,
+    EventType::MediaPlayerPausableChanged,
+    EventType::MediaPlayerLengthChanged,
+    EventType::MediaPlayerVout,
+];
+
+const LIST_EVENTS: &[EventType] = &[
+    EventType::MediaListItemAdded,
+    EventType::MediaListItemDeleted,
+];
+
+/// The objects a [ControlServer] controls.
+#[derive(Clone)]
+pub struct Controls {
+    instance: Instance,
+    player: MediaPlayer,
+    playlist: Option<(MediaListPlayer, MediaList)>,
+}
+
+impl Controls {
+    /// Control `player`. The instance creates the media added to the playlist.
+    pub fn new(instance: &Instance, player: &MediaPlayer) -> Controls {
+        Controls { instance: instance.clone(), player: player.clone(), playlist: None }
+    }
+
+    /// Also control a list player and its list, whose media player should be the controlled player.
+    pub fn with_playlist(mut self, list_player: &MediaListPlayer, list: &MediaList) -> Controls {
+        self.playlist = Some((list_player.clone(), list.clone()));
+        self
+    }
+}
+
+/// A running control server. It stops when dropped.
+pub struct ControlServer {
+    addr: SocketAddr,
+    shutdown: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ControlServer {
+    /// Serve `controls` on `addr`. Port 0 picks a free port, see [local_addr](ControlServer::local_addr).
+    pub fn start<A: ToSocketAddrs>(addr: A, controls: Controls) -> io::Result<ControlServer> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let controls = Arc::new(controls);
+
+        let thread_shutdown = shutdown.clone();
+        let thread = thread::spawn(move || {
+            for stream in listener.incoming() {
+                if thread_shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else { continue };
+                let controls = controls.clone();
+                let shutdown = thread_shutdown.clone();
+                thread::spawn(move || {
+                    let _ = serve(stream, &controls, &shutdown);
+                });
+            }
+        });
+
+        Ok(ControlServer { addr, shutdown, thread: Some(thread) })
+    }
+
+    /// Serve `controls` on the loopback interface only.
+    pub fn localhost(port: u16, controls: Controls) -> io::Result<ControlServer> {
+        ControlServer::start((Ipv4Addr::LOCALHOST, port), controls)
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Stop accepting connections. Event streams end within a second.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake up the accept loop.
+        let mut addr = self.addr;
+        match addr.ip() {
+            IpAddr::V4(ip) if ip.is_unspecified() => addr.set_ip(Ipv4Addr::LOCALHOST.into()),
+            IpAddr::V6(ip) if ip.is_unspecified() => addr.set_ip(Ipv6Addr::LOCALHOST.into()),
+            _ => (),
+        }
+        let _ = TcpStream::connect_timeout(&addr, Duration::from_secs(1));
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    host: Option<String>,
+    origin: Option<String>,
+    content_type: Option<String>,
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: u16,
+    body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Response {
+        Response { status: 200, body }
+    }
+
+    fn done() -> Response {
+        Response::ok(json!({}))
+    }
+
+    fn error(status: u16, message: &str) -> Response {
+        Response { status, body: json!({ "error": message }) }
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        _ => "Internal Server Error",
+    }
+}
+
+fn read_request(reader: &mut BufReader<TcpStream>) -> Result<Request, Response> {
+    let bad = |_| Response::error(400, "malformed request");
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(bad)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(Response::error(400, "malformed request line"));
+    };
+    let method = method.to_owned();
+    let path = target.split('?').next().unwrap_or_default().to_owned();
+
+    let mut length = 0;
+    let (mut host, mut origin, mut content_type) = (None, None, None);
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).map_err(bad)?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let (name, value) = (name.trim().to_ascii_lowercase(), value.trim());
+            match name.as_str() {
+                "content-length" => length = value.parse().map_err(|_| Response::error(400, "bad content length"))?,
+                "host" => host = Some(value.to_owned()),
+                "origin" => origin = Some(value.to_owned()),
+                "content-type" => content_type = Some(value.to_owned()),
+                _ => (),
+            }
+        }
+    }
+    if length > MAX_BODY {
+        return Err(Response::error(413, "body too large"));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(bad)?;
+    Ok(Request { method, path, host, origin, content_type, body })
+}
+
+// Whether `host`, as in a Host header, names the server reached at `local`: localhost or its address,
+// with its port if one is given.
+fn is_own_host(host: &str, local: SocketAddr) -> bool {
+    let (name, port) = match host.rsplit_once(':') {
+        Some((name, port)) if !port.ends_with(']') => match port.parse::<u16>() {
+            Ok(port) => (name, Some(port)),
+            Err(_) => return false,
+        },
+        _ => (host, None),
+    };
+    if port.is_some_and(|port| port != local.port()) {
+        return false;
+    }
+    let name = name.trim_start_matches('[').trim_end_matches(']');
+    name.eq_ignore_ascii_case("localhost") || name.parse::<IpAddr>().is_ok_and(|ip| ip == local.ip())
+}
+
+// Reject what a web page could send: a page of another site, or one on a name rebound to this address,
+// can make simple requests without a preflight, e.g. a text/plain POST, but cannot change Host or Origin.
+fn check_request(request: &Request, local: SocketAddr) -> Result<(), Response> {
+    if !request.host.as_deref().is_some_and(|host| is_own_host(host, local)) {
+        return Err(Response::error(403, "the Host header must name this server"));
+    }
+    if let Some(origin) = &request.origin {
+        let authority = origin.strip_prefix("http://").unwrap_or_default();
+        if !is_own_host(authority, local) {
+            return Err(Response::error(403, "cross-origin requests are not allowed"));
+        }
+    }
+    let json = request.content_type.as_deref()
+        .is_some_and(|t| t.split(';').next().unwrap_or_default().trim().eq_ignore_ascii_case("application/json"));
+    if !request.body.is_empty() && !json {
+        return Err(Response::error(415, "the body must be sent as application/json"));
+    }
+    Ok(())
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response) -> io::Result<()> {
+    let body = response.body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status, reason(response.status), body.len(), body,
+    )?;
+    stream.flush()
+}
+
+fn serve(stream: TcpStream, controls: &Controls, shutdown: &AtomicBool) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut stream = stream;
+    let request = match read_request(&mut reader) {
+        Ok(request) => request,
+        Err(response) => return write_response(&mut stream, &response),
+    };
+    if let Err(response) = check_request(&request, stream.local_addr()?) {
+        return write_response(&mut stream, &response);
+    }
+
+    if request.method == "GET" && request.path == "/events" {
+        return stream_events(stream, controls, shutdown);
+    }
+    let response = match route(controls, &request) {
+        Ok(response) | Err(response) => response,
+    };
+    write_response(&mut stream, &response)
+}
+
+fn parse_body(request: &Request) -> Result<Map<String, Value>, Response> {
+    if request.body.is_empty() {
+        return Ok(Map::new());
+    }
+    match serde_json::from_slice(&request.body) {
+        Ok(Value::Object(map)) => Ok(map),
+        _ => Err(Response::error(400, "the body must be a JSON object")),
+    }
+}
+
+fn checked(result: Result<(), ()>, what: &str) -> Result<Response, Response> {
+    result.map(|_| Response::done()).map_err(|_| Response::error(409, what))
+}
+
+fn route(controls: &Controls, request: &Request) -> Result<Response, Response> {
+    let segments: Vec<&str> = request.path.split('/').filter(|s| !s.is_empty()).collect();
+    let player = &controls.player;
+    let playlist = || controls.playlist.as_ref().ok_or_else(|| Response::error(404, "no playlist"));
+    let index = |s: &str| s.parse::<i32>().map_err(|_| Response::error(404, "no such item"));
+
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["status"]) => Ok(Response::ok(status(player))),
+        ("POST", ["play"]) => match &controls.playlist {
+            Some((list_player, _)) => {
+                list_player.play();
+                Ok(Response::done())
+            },
+            None => checked(player.play(), "cannot play"),
+        },
+        ("POST", ["pause"]) => {
+            player.set_pause(true);
+            Ok(Response::done())
+        },
+        ("POST", ["stop"]) => {
+            match &controls.playlist {
+                Some((list_player, _)) => list_player.stop(),
+                None => player.stop(),
+            }
+            Ok(Response::done())
+        },
+        ("POST", ["next"]) => checked(playlist()?.0.next(), "no next item"),
+        ("POST", ["previous"]) => checked(playlist()?.0.previous(), "no previous item"),
+        ("POST", ["seek"]) => {
+            let body = parse_body(request)?;
+            if let Some(time) = body.get("time").and_then(Value::as_u64) {
+                player.seek(Duration::from_millis(time));
+            } else if let Some(position) = body.get("position").and_then(Value::as_f64) {
+                player.set_position(position.clamp(0.0, 1.0) as f32);
+            } else {
+                return Err(Response::error(400, "expected time or position"));
+            }
+            Ok(Response::done())
+        },
+        ("POST", ["volume"]) => {
+            let body = parse_body(request)?;
+            if let Some(muted) = body.get("muted").and_then(Value::as_bool) {
+                player.set_mute(muted);
+            }
+            match body.get("volume") {
+                Some(volume) => {
+                    let volume = volume.as_i64().ok_or_else(|| Response::error(400, "bad volume"))?;
+                    checked(player.set_volume(volume.clamp(0, 200) as i32), "cannot set the volume")
+                },
+                None => Ok(Response::done()),
+            }
+        },
+        ("GET", ["tracks"]) => Ok(Response::ok(tracks(player))),
+        ("POST", ["tracks", kind]) => {
+            let body = parse_body(request)?;
+            let id = body.get("id").and_then(Value::as_i64).ok_or_else(|| Response::error(400, "expected id"))? as i32;
+            match *kind {
+                "audio" => player.set_audio_track(id),
+                "video" => player.set_video_track(id),
+                "spu" => player.set_spu_track(id),
+                _ => return Err(Response::error(404, "no such track type")),
+            }
+            Ok(Response::done())
+        },
+        ("GET", ["playlist"]) => {
+            let list = &playlist()?.1;
+            let items: Vec<Value> = list.iter().enumerate().map(|(i, md)| item(i as i32, &md)).collect();
+            Ok(Response::ok(json!(items)))
+        },
+        ("POST", ["playlist"]) => {
+            let list = &playlist()?.1;
+            let body = parse_body(request)?;
+            let md = new_media(controls, &body)?;
+            let guard = list.lock();
+            let index = match body.get("index").and_then(Value::as_i64) {
+                Some(index) => index.clamp(0, guard.count() as i64) as i32,
+                None => guard.count(),
+            };
+            guard.insert_media(&md, index).map_err(|_| Response::error(409, "the playlist is read-only"))?;
+            Ok(Response { status: 201, body: item(index, &md) })
+        },
+        ("GET", ["playlist", i]) => {
+            let index = index(i)?;
+            let md = playlist()?.1.item_at_index(index).ok_or_else(|| Response::error(404, "no such item"))?;
+            Ok(Response::ok(item(index, &md)))
+        },
+        ("PUT", ["playlist", i]) => {
+            let list = &playlist()?.1;
+            let index = index(i)?;
+            let md = new_media(controls, &parse_body(request)?)?;
+            let guard = list.lock();
+            if index < 0 || index >= guard.count() {
+                return Err(Response::error(404, "no such item"));
+            }
+            guard.remove_index(index)
+                .and_then(|_| guard.insert_media(&md, index))
+                .map_err(|_| Response::error(409, "the playlist is read-only"))?;
+            Ok(Response::ok(item(index, &md)))
+        },
+        ("DELETE", ["playlist", i]) => {
+            let list = &playlist()?.1;
+            let index = index(i)?;
+            let guard = list.lock();
+            let md = guard.item_at_index(index).ok_or_else(|| Response::error(404, "no such item"))?;
+            guard.remove_index(index).map_err(|_| Response::error(409, "the playlist is read-only"))?;
+            Ok(Response::ok(item(index, &md)))
+        },
+        ("POST", ["playlist", i, "play"]) => {
+            let index = index(i)?;
+            checked(playlist()?.0.play_item_at_index(index), "no such item")
+        },
+        (_, ["status"]) | (_, ["play"]) | (_, ["pause"]) | (_, ["stop"]) | (_, ["next"]) | (_, ["previous"])
+        | (_, ["seek"]) | (_, ["volume"]) | (_, ["tracks", ..]) | (_, ["playlist", ..]) | (_, ["events"]) => {
+            Err(Response::error(405, "method not allowed"))
+        },
+        _ => Err(Response::error(404, "not found")),
+    }
+}
+
+fn status(player: &MediaPlayer) -> Value {
+    let mut status = serde_json::to_value(player.status()).unwrap_or_default();
+    status["mrl"] = json!(player.get_media().and_then(|md| md.mrl()));
+    status["tracks"] = tracks(player);
+    status
+}
+
+fn tracks(player: &MediaPlayer) -> Value {
+    json!({
+        "audio": { "tracks": player.get_audio_track_description(), "selected": player.get_audio_track() },
+        "video": { "tracks": player.get_video_track_description(), "selected": player.get_video_track() },
+        "spu": { "tracks": player.get_spu_track_description(), "selected": player.get_spu_track() },
+    })
+}
+
+fn item(index: i32, md: &Media) -> Value {
+    json!({
+        "index": index,
+        "mrl": md.mrl(),
+        "title": md.get_meta(Meta::Title),
+        "duration": md.length().map(|d| d.as_millis() as u64),
+    })
+}
+
+fn new_media(controls: &Controls, body: &Map<String, Value>) -> Result<Media, Response> {
+    let md = if let Some(mrl) = body.get("mrl").and_then(Value::as_str) {
+        Media::new_location(&controls.instance, mrl)
+    } else if let Some(path) = body.get("path").and_then(Value::as_str) {
+        Media::new_path(&controls.instance, Path::new(path))
+    } else {
+        return Err(Response::error(400, "expected mrl or path"));
+    };
+    md.ok_or_else(|| Response::error(400, "invalid media"))
+}
+
+fn stream_events(mut stream: TcpStream, controls: &Controls, shutdown: &AtomicBool) -> io::Result<()> {
+    write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n")?;
+    stream.flush()?;
+
+    let (tx, rx) = channel::<Event>();
+    let player_em = controls.player.event_manager();
+    let list_em = controls.playlist.as_ref().map(|(_, list)| list.event_manager());
+    let mut handles = Vec::new();
+    for &event_type in PLAYER_EVENTS {
+        let tx = tx.clone();
+        if let Ok(handle) = EventManagerApi::attach(&player_em, event_type, move |e, _| { let _ = tx.send(e); }) {
+            handles.push((false, event_type, handle));
+        }
+    }
+    if let Some(em) = &list_em {
+        for &event_type in LIST_EVENTS {
+            let tx = tx.clone();
+            if let Ok(handle) = EventManagerApi::attach(em, event_type, move |e, _| { let _ = tx.send(e); }) {
+                handles.push((true, event_type, handle));
+            }
+        }
+    }
+    drop(tx);
+
+    let mut idle = 0;
+    let result = loop {
+        if shutdown.load(Ordering::SeqCst) {
+            break Ok(());
+        }
+        let written = match rx.recv_timeout(EVENT_POLL) {
+            Ok(event) => {
+                idle = 0;
+                let data = serde_json::to_string(&event).unwrap_or_default();
+                write!(stream, "data: {}\n\n", data).and_then(|_| stream.flush())
+            },
+            Err(RecvTimeoutError::Timeout) => {
+                idle += 1;
+                if idle < KEEP_ALIVE_POLLS {
+                    continue;
+                }
+                idle = 0;
+                stream.write_all(b": keep-alive\n\n").and_then(|_| stream.flush())
+            },
+            Err(RecvTimeoutError::Disconnected) => break Ok(()),
+        };
+        if written.is_err() {
+            break written;
+        }
+    };
+
+    for (from_list, event_type, handle) in handles {
+        match (&list_em, from_list) {
+            (Some(em), true) => EventManagerApi::detach(em, event_type, handle),
+            _ => EventManagerApi::detach(&player_em, event_type, handle),
+        }
+    }
+    result
+}
@@ -5,24 +5,64 @@
 extern crate libc;
 
 mod tools;
+mod error;
 mod core;
 mod media;
 mod media_player;
 mod media_list;
 mod media_list_player;
+mod play_queue;
+mod crossfade;
+mod stepping;
+mod sync_group;
+#[cfg(not(feature = "libvlc4"))]
 mod media_library;
 mod enums;
+mod time;
+mod fourcc;
 mod video;
 mod audio;
+#[cfg(not(feature = "libvlc4"))]
 mod vlm;
+pub mod sout;
+pub mod playlist;
+#[cfg(feature = "http-control")]
+pub mod http_control;
+#[cfg(feature = "mpris")]
+pub mod mpris;
+mod transcoder;
+mod status;
+mod api;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(all(feature = "serde", not(feature = "libvlc4")))]
+mod vlm_info;
+#[cfg(feature = "libvlc4")]
+mod picture;
 
+pub use crate::error::*;
 pub use crate::enums::*;
+pub use crate::time::*;
+pub use crate::fourcc::*;
 pub use crate::core::*;
 pub use crate::media::*;
 pub use crate::media_player::*;
 pub use crate::media_list::*;
 pub use crate::media_list_player::*;
+pub use crate::play_queue::*;
+pub use crate::crossfade::*;
+pub use crate::stepping::*;
+pub use crate::sync_group::*;
+#[cfg(not(feature = "libvlc4"))]
 pub use crate::media_library::*;
 pub use crate::video::*;
 pub use crate::audio::*;
+#[cfg(not(feature = "libvlc4"))]
 pub use crate::vlm::*;
+pub use crate::transcoder::*;
+pub use crate::status::*;
+pub use crate::api::*;
+#[cfg(all(feature = "serde", not(feature = "libvlc4")))]
+pub use crate::vlm_info::*;
+#[cfg(feature = "libvlc4")]
+pub use crate::picture::*;
@@ -2,25 +2,45 @@
 // This file is part of vlc-rs.
 // Licensed under the MIT license, see the LICENSE file.
 
-use crate::enums::{Meta, State, TrackType};
-use crate::tools::{from_cstr, path_to_cstr, to_cstr};
-use crate::{EventManager, Instance};
+use crate::enums::{MediaType, Meta, ParseFlag, ParsedStatus, State, TrackType};
+use crate::tools::{from_cstr, ms_to_duration, path_to_cstr, to_cstr};
+use crate::{EventManager, EventManagerApi, EventType, FourCC, Instance, MediaList};
+#[cfg(not(feature = "libvlc4"))]
+use libc::c_void;
+#[cfg(not(feature = "libvlc4"))]
+use std::any::Any;
 use std::path::Path;
+#[cfg(not(feature = "libvlc4"))]
+use std::ptr;
+use std::sync::mpsc::channel;
+#[cfg(not(feature = "libvlc4"))]
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use vlc_sys as sys;
 
+// How often parse_and_wait checks the parsed status.
+const PARSE_POLL: Duration = Duration::from_millis(50);
+
 pub struct Media {
     pub(crate) ptr: *mut sys::libvlc_media_t,
 }
 
 unsafe impl Send for Media {}
 
+// Media descriptors lock their own state inside libvlc.
+unsafe impl Sync for Media {}
+
 impl Media {
     /// Create a media with a certain given media resource location, for instance a valid URL.
+    /// With libvlc 4 the media does not belong to an instance and `instance` is unused.
     pub fn new_location(instance: &Instance, mrl: &str) -> Option<Media> {
         let cstr = to_cstr(mrl);
 
         unsafe {
+            #[cfg(not(feature = "libvlc4"))]
             let p = sys::libvlc_media_new_location(instance.ptr, cstr.as_ptr());
+            #[cfg(feature = "libvlc4")]
+            let p = { let _ = instance; sys::libvlc_media_new_location(cstr.as_ptr()) };
             if p.is_null() {
                 return None;
             }
@@ -39,7 +59,27 @@ impl Media {
         };
 
         unsafe {
+            #[cfg(not(feature = "libvlc4"))]
             let p = sys::libvlc_media_new_path(instance.ptr, cstr.as_ptr());
+            #[cfg(feature = "libvlc4")]
+            let p = { let _ = instance; sys::libvlc_media_new_path(cstr.as_ptr()) };
+            if p.is_null() {
+                return None;
+            }
+
+            Some(Media { ptr: p })
+        }
+    }
+
+    /// Create an empty media with a name, to be used as a node holding the media added to its [subitems](Media::subitems).
+    pub fn new_as_node(instance: &Instance, name: &str) -> Option<Media> {
+        let cstr = to_cstr(name);
+
+        unsafe {
+            #[cfg(not(feature = "libvlc4"))]
+            let p = sys::libvlc_media_new_as_node(instance.ptr, cstr.as_ptr());
+            #[cfg(feature = "libvlc4")]
+            let p = { let _ = instance; sys::libvlc_media_new_as_node(cstr.as_ptr()) };
             if p.is_null() {
                 return None;
             }
@@ -50,7 +90,10 @@ impl Media {
 
     pub fn new_fd(instance: &Instance, fd: i32) -> Option<Media> {
         unsafe {
+            #[cfg(not(feature = "libvlc4"))]
             let p = sys::libvlc_media_new_fd(instance.ptr, fd);
+            #[cfg(feature = "libvlc4")]
+            let p = { let _ = instance; sys::libvlc_media_new_fd(fd) };
             if p.is_null() {
                 return None;
             }
@@ -68,6 +111,21 @@ impl Media {
         }
     }
 
+    /// Add an option to the media, e.g. `:sout=#display` or a rendered [crate::sout::Chain].
+    /// The option determines how a media player will read the media.
+    pub fn add_option(&self, option: &str) {
+        unsafe {
+            sys::libvlc_media_add_option(self.ptr, to_cstr(option).as_ptr());
+        }
+    }
+
+    /// Add an option to the media with the given `libvlc_media_option_*` flags.
+    pub fn add_option_flag(&self, option: &str, flags: u32) {
+        unsafe {
+            sys::libvlc_media_add_option_flag(self.ptr, to_cstr(option).as_ptr(), flags);
+        }
+    }
+
     pub fn event_manager<'a>(&'a self) -> EventManager<'a> {
         unsafe {
             let p = sys::libvlc_media_event_manager(self.ptr);
@@ -112,7 +170,30 @@ impl Media {
         unsafe { sys::libvlc_media_get_state(self.ptr).into() }
     }
 
+    /// Get the type of the media, known once it has been parsed or played.
+    pub fn media_type(&self) -> MediaType {
+        unsafe { sys::libvlc_media_get_type(self.ptr).into() }
+    }
+
+    /// Create a copy of the media, with its options but without its user data.
+    pub fn duplicate(&self) -> Option<Media> {
+        unsafe {
+            let p = sys::libvlc_media_duplicate(self.ptr);
+            if p.is_null() {
+                return None;
+            }
+
+            Some(Media { ptr: p })
+        }
+    }
+
+    /// Get the duration of the media, or None if it is unknown.
+    pub fn length(&self) -> Option<Duration> {
+        ms_to_duration(unsafe { sys::libvlc_media_get_duration(self.ptr) })
+    }
+
     /// Get duration (in ms) of media descriptor object item.
+    #[deprecated(note = "use `Media::length`")]
     pub fn duration(&self) -> Option<i64> {
         let time = unsafe { sys::libvlc_media_get_duration(self.ptr) };
         if time != -1 {
@@ -123,16 +204,21 @@ impl Media {
     }
 
     /// Parse a media.
+    /// Removed from libvlc 4, use [Media::parse_request] there.
+    #[cfg(not(feature = "libvlc4"))]
     pub fn parse(&self) {
         unsafe { sys::libvlc_media_parse(self.ptr) };
     }
 
     /// Parse a media.
+    /// Removed from libvlc 4, use [Media::parse_request] there.
+    #[cfg(not(feature = "libvlc4"))]
     pub fn parse_async(&self) {
         unsafe { sys::libvlc_media_parse_async(self.ptr) };
     }
 
     /// Get Parsed status for media descriptor object.
+    #[cfg(not(feature = "libvlc4"))]
     pub fn is_parsed(&self) -> bool {
         if unsafe { sys::libvlc_media_is_parsed(self.ptr) } == 0 {
             false
@@ -141,6 +227,109 @@ impl Media {
         }
     }
 
+    /// Get Parsed status for media descriptor object.
+    /// With libvlc 4 only a successful [Media::parse_request] counts.
+    #[cfg(feature = "libvlc4")]
+    pub fn is_parsed(&self) -> bool {
+        matches!(self.parsed_status(), Some(ParsedStatus::Done))
+    }
+
+    /// Parse the media asynchronously with the given flags.
+    /// `timeout` is in ms, -1 uses the default and 0 waits forever.
+    /// MediaParsedChanged is sent when parsing finishes. libvlc 3 ignores `instance`.
+    pub fn parse_request(&self, instance: &Instance, flags: &[ParseFlag], timeout: i32) -> Result<(), ()> {
+        let flags = flags.iter().fold(0, |acc, &f| acc | f as sys::libvlc_media_parse_flag_t);
+        #[cfg(not(feature = "libvlc4"))]
+        let result = { let _ = instance; unsafe { sys::libvlc_media_parse_with_options(self.ptr, flags, timeout) } };
+        #[cfg(feature = "libvlc4")]
+        let result = unsafe { sys::libvlc_media_parse_request(instance.ptr, self.ptr, flags, timeout) };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Parsed status of the media, or None if it has not been parsed.
+    pub fn parsed_status(&self) -> Option<ParsedStatus> {
+        match unsafe { sys::libvlc_media_get_parsed_status(self.ptr) } {
+            0 => None,
+            status => Some(status.into()),
+        }
+    }
+
+    /// The items of a playlist or directory media, available once it has been parsed.
+    pub fn subitems(&self) -> Option<MediaList> {
+        unsafe {
+            let p = sys::libvlc_media_subitems(self.ptr);
+            if p.is_null() { None } else { Some(MediaList { ptr: p }) }
+        }
+    }
+
+    /// Parse the media and its subitems recursively, up to `depth` levels, and return the leaves of the tree in order.
+    /// A media without subitems, or that could not be parsed within `timeout`, is returned as is.
+    pub fn expand(&self, instance: &Instance, depth: u32, timeout: Duration) -> Vec<Media> {
+        let mut items = Vec::new();
+        self.expand_into(instance, depth, timeout, &mut items);
+        items
+    }
+
+    fn expand_into(&self, instance: &Instance, depth: u32, timeout: Duration, items: &mut Vec<Media>) {
+        self.parse_and_wait(instance, timeout);
+        let subitems = match self.subitems() {
+            Some(list) => list.iter().collect(),
+            None => Vec::new(),
+        };
+
+        if subitems.is_empty() {
+            items.push(self.clone());
+        } else if depth == 0 {
+            items.extend(subitems);
+        } else {
+            for item in subitems {
+                item.expand_into(instance, depth - 1, timeout, items);
+            }
+        }
+    }
+
+    // Request parsing, unless it was done before, and wait until it finishes or times out.
+    fn parse_and_wait(&self, instance: &Instance, timeout: Duration) {
+        if self.parsed_status().is_some() {
+            return;
+        }
+        let (tx, rx) = channel();
+        let em = self.event_manager();
+        let handle = EventManagerApi::attach(&em, EventType::MediaParsedChanged, move |_, _| {
+            let _ = tx.send(());
+        });
+
+        // Checked again now that the callback is attached, in case the parse just ended.
+        if self.parsed_status().is_none() {
+            let flags = [ParseFlag::ParseLocal, ParseFlag::ParseNetwork];
+            let timeout_ms = timeout.as_millis().clamp(1, i32::MAX as u128) as i32;
+            if self.parse_request(instance, &flags, timeout_ms).is_ok() {
+                // libvlc enforces the timeout, the margin covers the delivery of the event.
+                let deadline = Instant::now() + timeout + Duration::from_millis(500);
+                // A parse already pending makes libvlc ignore the request without a new event,
+                // so the status is polled for the end of the pending one.
+                while self.parsed_status().is_none() {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        break;
+                    }
+                    let _ = rx.recv_timeout((deadline - now).min(PARSE_POLL));
+                }
+            }
+        }
+
+        if let Ok(handle) = handle {
+            EventManagerApi::detach(&em, EventType::MediaParsedChanged, handle);
+        }
+    }
+
+    /// Get the elementary streams of the media.
+    /// The media has to be parsed first.
+    #[cfg(not(feature = "libvlc4"))]
     pub fn tracks(&self) -> Option<Vec<MediaTrack>> {
         unsafe {
             let mut p_track: *mut *mut sys::libvlc_media_track_t = ::std::ptr::null_mut();
@@ -152,47 +341,7 @@ impl Media {
             let mut track = Vec::new();
 
             for i in 0..n {
-                let p = p_track.offset(i as isize);
-                let i_type: TrackType = (**p).i_type.into();
-                let type_specific_data = match i_type {
-                    TrackType::Audio => {
-                        let audio = (**p).__bindgen_anon_1.audio;
-                        MediaTrackUnion::Audio(AudioTrack {
-                            channels: (*audio).i_channels,
-                            rate: (*audio).i_rate,
-                        })
-                    }
-                    TrackType::Video => {
-                        let video = (**p).__bindgen_anon_1.video;
-                        MediaTrackUnion::Video(VideoTrack {
-                            height: (*video).i_height,
-                            width: (*video).i_width,
-                            sar_num: (*video).i_sar_num,
-                            sar_den: (*video).i_sar_den,
-                            frame_rate_num: (*video).i_frame_rate_num,
-                            frame_rate_den: (*video).i_frame_rate_den,
-                        })
-                    }
-                    TrackType::Text => {
-                        let subtitle = (**p).__bindgen_anon_1.subtitle;
-                        MediaTrackUnion::Subtitle(SubtitleTrack {
-                            encoding: from_cstr((*subtitle).psz_encoding),
-                        })
-                    }
-                    TrackType::Unknown => MediaTrackUnion::None,
-                };
-                track.push(MediaTrack {
-                    codec: (**p).i_codec,
-                    original_fourcc: (**p).i_original_fourcc,
-                    id: (**p).i_id,
-                    track_type: (**p).i_type.into(),
-                    profile: (**p).i_profile,
-                    level: (**p).i_level,
-                    bitrate: (**p).i_bitrate,
-                    language: from_cstr((**p).psz_language),
-                    description: from_cstr((**p).psz_description),
-                    type_specific_data,
-                });
+                track.push(MediaTrack::from_raw(*p_track.offset(i as isize)));
             }
 
             sys::libvlc_media_tracks_release(p_track, n);
@@ -200,26 +349,27 @@ impl Media {
         }
     }
 
+    /// Get the elementary streams of the media.
+    /// The media has to be parsed first.
+    #[cfg(feature = "libvlc4")]
+    pub fn tracks(&self) -> Option<Vec<MediaTrack>> {
+        let mut track = Vec::new();
+        for track_type in [TrackType::Audio, TrackType::Video, TrackType::Text] {
+            unsafe {
+                let list = sys::libvlc_media_get_tracklist(self.ptr, track_type as sys::libvlc_track_type_t);
+                if !list.is_null() {
+                    track.extend(MediaTrack::from_tracklist(list));
+                }
+            }
+        }
+
+        if track.is_empty() { None } else { Some(track) }
+    }
+
     /// Returns statistics of current media playback
     pub fn stats(&self) -> Option<MediaStats> {
         unsafe {
-            let mut p_stats: sys::libvlc_media_stats_t = sys::libvlc_media_stats_t {
-                i_read_bytes: 0,
-                f_input_bitrate: 0.0,
-                i_demux_read_bytes: 0,
-                f_demux_bitrate: 0.0,
-                i_demux_corrupted: 0,
-                i_demux_discontinuity: 0,
-                i_decoded_video: 0,
-                i_decoded_audio: 0,
-                i_displayed_pictures: 0,
-                i_lost_pictures: 0,
-                i_played_abuffers: 0,
-                i_lost_abuffers: 0,
-                i_sent_packets: 0,
-                i_sent_bytes: 0,
-                f_send_bitrate: 0.0,
-            };
+            let mut p_stats: sys::libvlc_media_stats_t = ::std::mem::zeroed();
 
             let n = sys::libvlc_media_get_stats(self.ptr, &mut p_stats);
             if n == 0 {
@@ -236,29 +386,118 @@ impl Media {
                 decoded_video: p_stats.i_decoded_video,
                 decoded_audio: p_stats.i_decoded_audio,
                 displayed_pictures: p_stats.i_displayed_pictures,
+                #[cfg(feature = "libvlc4")]
+                late_pictures: p_stats.i_late_pictures,
                 lost_pictures: p_stats.i_lost_pictures,
                 played_abuffers: p_stats.i_played_abuffers,
                 lost_abuffers: p_stats.i_lost_abuffers,
+                #[cfg(not(feature = "libvlc4"))]
                 sent_packets: p_stats.i_sent_packets,
+                #[cfg(not(feature = "libvlc4"))]
                 sent_bytes: p_stats.i_sent_bytes,
+                #[cfg(not(feature = "libvlc4"))]
                 send_bitrate: p_stats.f_send_bitrate,
             })
         }
     }
 
+    /// Attach a value to the media, replacing the previous one. It is shared by every handle on the media,
+    /// including the ones returned by media lists and events, and dropped when the media is freed.
+    /// Not available with libvlc 4, which does not report when a media is freed.
+    #[cfg(not(feature = "libvlc4"))]
+    pub fn set_user_data<T: Any + Send>(&self, data: T) {
+        let data: Box<Box<dyn Any + Send>> = Box::new(Box::new(data));
+        let guard = USER_DATA_LOCK.lock().unwrap();
+        let old = unsafe {
+            let old = sys::libvlc_media_get_user_data(self.ptr) as *mut Box<dyn Any + Send>;
+            sys::libvlc_media_set_user_data(self.ptr, Box::into_raw(data) as *mut c_void);
+            if old.is_null() {
+                let em = sys::libvlc_media_event_manager(self.ptr);
+                sys::libvlc_event_attach(em, EventType::MediaFreed as i32, Some(free_user_data), ptr::null_mut());
+                None
+            } else {
+                Some(Box::from_raw(old))
+            }
+        };
+        // Drop the previous value unlocked, it may use the user data of other media.
+        drop(guard);
+        drop(old);
+    }
+
+    /// A copy of the value attached with [set_user_data](Media::set_user_data), or None if there is none or it is not a `T`.
+    #[cfg(not(feature = "libvlc4"))]
+    pub fn user_data<T: Any + Clone>(&self) -> Option<T> {
+        let _guard = USER_DATA_LOCK.lock().unwrap();
+        unsafe {
+            let p = sys::libvlc_media_get_user_data(self.ptr) as *const Box<dyn Any + Send>;
+            p.as_ref()?.downcast_ref::<T>().cloned()
+        }
+    }
+
+    /// Detach and return the value attached with [set_user_data](Media::set_user_data), if it is a `T`.
+    #[cfg(not(feature = "libvlc4"))]
+    pub fn take_user_data<T: Any>(&self) -> Option<T> {
+        let _guard = USER_DATA_LOCK.lock().unwrap();
+        unsafe {
+            let p = sys::libvlc_media_get_user_data(self.ptr) as *mut Box<dyn Any + Send>;
+            if !p.as_ref()?.is::<T>() {
+                return None;
+            }
+            sys::libvlc_media_set_user_data(self.ptr, ptr::null_mut());
+            let em = sys::libvlc_media_event_manager(self.ptr);
+            sys::libvlc_event_detach(em, EventType::MediaFreed as i32, Some(free_user_data), ptr::null_mut());
+            Box::from_raw(p).downcast::<T>().ok().map(|data| *data)
+        }
+    }
+
+    // Wrap a media borrowed from libvlc, taking a reference.
+    pub(crate) unsafe fn retain(ptr: *mut sys::libvlc_media_t) -> Option<Media> {
+        if ptr.is_null() {
+            return None;
+        }
+        sys::libvlc_media_retain(ptr);
+        Some(Media { ptr })
+    }
+
     /// Returns raw pointer
     pub fn raw(&self) -> *mut sys::libvlc_media_t {
         self.ptr
     }
 }
 
+impl std::fmt::Debug for Media {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Media")
+            .field("mrl", &self.mrl())
+            .finish()
+    }
+}
+
+impl Clone for Media {
+    /// Retain the media. The underlying media is released when the last clone is dropped.
+    fn clone(&self) -> Media {
+        unsafe { sys::libvlc_media_retain(self.ptr) };
+        Media { ptr: self.ptr }
+    }
+}
+
 impl Drop for Media {
     fn drop(&mut self) {
         unsafe { sys::libvlc_media_release(self.ptr) };
     }
 }
 
+impl PartialEq for Media {
+    /// Whether both are the same libvlc media, as the clones of a media are.
+    fn eq(&self, other: &Media) -> bool {
+        self.ptr == other.ptr
+    }
+}
+
+impl Eq for Media {}
+
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MediaTrack {
     pub codec: u32,
     pub original_fourcc: u32,
@@ -270,9 +509,140 @@ pub struct MediaTrack {
     pub language: Option<String>,
     pub description: Option<String>,
     pub type_specific_data: MediaTrackUnion,
+    /// String identifier of the track, used to select it. Only available with libvlc 4.
+    #[cfg(feature = "libvlc4")]
+    pub str_id: Option<String>,
+    /// Name of the track. Only available with libvlc 4.
+    #[cfg(feature = "libvlc4")]
+    pub name: Option<String>,
+    /// Whether the track is selected. Only meaningful for tracks of a media player.
+    #[cfg(feature = "libvlc4")]
+    pub selected: bool,
 }
 
+impl MediaTrack {
+    pub(crate) unsafe fn from_raw(p: *const sys::libvlc_media_track_t) -> MediaTrack {
+        let i_type: TrackType = (*p).i_type.into();
+        let type_specific_data = match i_type {
+            TrackType::Audio => {
+                let audio = (*p).__bindgen_anon_1.audio;
+                MediaTrackUnion::Audio(AudioTrack {
+                    channels: (*audio).i_channels,
+                    rate: (*audio).i_rate,
+                })
+            }
+            TrackType::Video => {
+                let video = (*p).__bindgen_anon_1.video;
+                MediaTrackUnion::Video(VideoTrack {
+                    height: (*video).i_height,
+                    width: (*video).i_width,
+                    sar_num: (*video).i_sar_num,
+                    sar_den: (*video).i_sar_den,
+                    frame_rate_num: (*video).i_frame_rate_num,
+                    frame_rate_den: (*video).i_frame_rate_den,
+                })
+            }
+            TrackType::Text => {
+                let subtitle = (*p).__bindgen_anon_1.subtitle;
+                MediaTrackUnion::Subtitle(SubtitleTrack {
+                    encoding: from_cstr((*subtitle).psz_encoding),
+                })
+            }
+            TrackType::Unknown => MediaTrackUnion::None,
+        };
+        MediaTrack {
+            codec: (*p).i_codec,
+            original_fourcc: (*p).i_original_fourcc,
+            id: (*p).i_id,
+            track_type: i_type,
+            profile: (*p).i_profile,
+            level: (*p).i_level,
+            bitrate: (*p).i_bitrate,
+            language: from_cstr((*p).psz_language),
+            description: from_cstr((*p).psz_description),
+            type_specific_data,
+            #[cfg(feature = "libvlc4")]
+            str_id: from_cstr((*p).psz_id),
+            #[cfg(feature = "libvlc4")]
+            name: from_cstr((*p).psz_name),
+            #[cfg(feature = "libvlc4")]
+            selected: (*p).selected,
+        }
+    }
+
+    /// The codec as a [FourCC], e.g. to print it.
+    pub fn codec_fourcc(&self) -> FourCC {
+        FourCC(self.codec)
+    }
+
+    /// Human readable name of the codec, e.g. "H264 - MPEG-4 AVC (part 10)". Since libvlc 3.0.
+    #[cfg(vlc_3_0)]
+    pub fn codec_description(&self) -> Option<String> {
+        codec_description(self.track_type, self.codec_fourcc())
+    }
+
+    // Convert and delete a track list.
+    #[cfg(feature = "libvlc4")]
+    pub(crate) unsafe fn from_tracklist(list: *mut sys::libvlc_media_tracklist_t) -> Vec<MediaTrack> {
+        let tracks = (0..sys::libvlc_media_tracklist_count(list))
+            .map(|i| MediaTrack::from_raw(sys::libvlc_media_tracklist_at(list, i)))
+            .collect();
+        sys::libvlc_media_tracklist_delete(list);
+        tracks
+    }
+}
+
+// Serializes the accesses to the user data of every media.
+#[cfg(not(feature = "libvlc4"))]
+static USER_DATA_LOCK: Mutex<()> = Mutex::new(());
+
+// Drop the user data of a media being freed.
+#[cfg(not(feature = "libvlc4"))]
+unsafe extern "C" fn free_user_data(pe: *const sys::libvlc_event_t, _: *mut c_void) {
+    let md = (*pe).u.media_freed.md;
+    let guard = USER_DATA_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let p = sys::libvlc_media_get_user_data(md) as *mut Box<dyn Any + Send>;
+    sys::libvlc_media_set_user_data(md, ptr::null_mut());
+    drop(guard);
+    if !p.is_null() {
+        drop(Box::from_raw(p));
+    }
+}
+
+// Serialize a media as its MRL. It cannot be deserialized back and becomes None.
+#[cfg(feature = "serde")]
+pub(crate) mod media_mrl {
+    use serde::de::IgnoredAny;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::Media;
+
+    pub fn serialize<S: Serializer>(md: &Option<Media>, s: S) -> Result<S::Ok, S::Error> {
+        match md.as_ref().and_then(Media::mrl) {
+            Some(mrl) => s.serialize_str(&mrl),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Media>, D::Error> {
+        IgnoredAny::deserialize(d)?;
+        Ok(None)
+    }
+}
+
+/// Human readable name of a codec of the given track type, or None if libvlc does not know it.
+/// Since libvlc 3.0.
+#[cfg(vlc_3_0)]
+pub fn codec_description(track_type: TrackType, codec: FourCC) -> Option<String> {
+    let p = unsafe { sys::libvlc_media_get_codec_description(track_type as sys::libvlc_track_type_t, codec.0) };
+    unsafe { from_cstr(p) }.filter(|s| !s.is_empty())
+}
+
+/// With the `serde` feature, serialized with a `type` field next to the fields of the track,
+/// e.g. `{"type": "audio", "channels": 2, "rate": 44100}`.
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
 pub enum MediaTrackUnion {
     Audio(AudioTrack),
     Video(VideoTrack),
@@ -281,12 +651,14 @@ pub enum MediaTrackUnion {
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AudioTrack {
     pub channels: u32,
     pub rate: u32,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VideoTrack {
     pub height: u32,
     pub width: u32,
@@ -297,11 +669,14 @@ pub struct VideoTrack {
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SubtitleTrack {
     pub encoding: Option<String>,
 }
 
+#[cfg(not(feature = "libvlc4"))]
 #[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MediaStats {
     pub read_bytes: i32,
     pub input_bitrate: f32,
@@ -319,3 +694,23 @@ pub struct MediaStats {
     pub sent_bytes: i32,
     pub send_bitrate: f32,
 }
+
+/// libvlc 4 counts with 64 bit integers and no longer reports stream output statistics.
+#[cfg(feature = "libvlc4")]
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MediaStats {
+    pub read_bytes: u64,
+    pub input_bitrate: f32,
+    pub demux_read_bytes: u64,
+    pub demux_bitrate: f32,
+    pub demux_corrupted: u64,
+    pub demux_discontinuity: u64,
+    pub decoded_video: u64,
+    pub decoded_audio: u64,
+    pub displayed_pictures: u64,
+    pub late_pictures: u64,
+    pub lost_pictures: u64,
+    pub played_abuffers: u64,
+    pub lost_abuffers: u64,
+}
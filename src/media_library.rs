@@ -9,6 +9,8 @@ pub struct MediaLibrary {
     pub(crate) ptr: *mut sys::libvlc_media_library_t,
 }
 
+// libvlc_media_library_load() is not synchronized. Since clones share the same library,
+// MediaLibrary is neither Send nor Sync.
 impl MediaLibrary {
     /// Create an new Media Library object.
     pub fn new(instance: &Instance) -> Option<MediaLibrary> {
@@ -39,6 +41,14 @@ impl MediaLibrary {
     }
 }
 
+impl Clone for MediaLibrary {
+    /// Retain the media library. The underlying library is released when the last clone is dropped.
+    fn clone(&self) -> MediaLibrary {
+        unsafe{ sys::libvlc_media_library_retain(self.ptr) };
+        MediaLibrary{ptr: self.ptr}
+    }
+}
+
 impl Drop for MediaLibrary {
     fn drop(&mut self) {
         unsafe{ sys::libvlc_media_library_release(self.ptr) };
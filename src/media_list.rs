@@ -2,7 +2,11 @@
 // This file is part of vlc-rs.
 // Licensed under the MIT license, see the LICENSE file.
 
+use std::marker::PhantomData;
+use std::vec;
 use vlc_sys as sys;
+#[cfg(not(feature = "libvlc4"))]
+use crate::tools::to_cstr;
 use crate::{Instance, Media, EventManager};
 
 pub struct MediaList {
@@ -11,11 +15,18 @@ pub struct MediaList {
 
 unsafe impl Send for MediaList{}
 
+// Every accessor below holds the media list lock while touching the items.
+unsafe impl Sync for MediaList{}
+
 impl MediaList {
     /// Create an empty media list.
+    /// With libvlc 4 the list does not belong to an instance and `instance` is unused.
     pub fn new(instance: &Instance) -> Option<MediaList> {
         unsafe{
+            #[cfg(not(feature = "libvlc4"))]
             let p = sys::libvlc_media_list_new(instance.ptr);
+            #[cfg(feature = "libvlc4")]
+            let p = { let _ = instance; sys::libvlc_media_list_new() };
             if p.is_null() { None }else{ Some(MediaList{ptr: p}) }
         }
     }
@@ -28,78 +39,78 @@ impl MediaList {
 
     /// Get media instance from this media list instance.
     pub fn media(&self) -> Option<Media> {
-        self.lock();
-        let result = unsafe{
+        let _guard = self.lock();
+        unsafe{
             let p = sys::libvlc_media_list_media(self.ptr);
             if p.is_null() { None }else{ Some(Media{ptr: p}) }
-        };
-        self.unlock();
-
-        result
+        }
     }
 
     /// Add media instance to media list.
     pub fn add_media(&self, md: &Media) -> Result<(), ()> {
-        self.lock();
-        let result = unsafe{
-            if sys::libvlc_media_list_add_media(self.ptr, md.ptr) == 0 { Ok(()) }else{ Err(()) }
-        };
-        self.unlock();
-
-        result
+        self.lock().add_media(md)
     }
 
     /// Insert media instance in media list on a position.
     pub fn insert_media(&self, md: &Media, pos: i32) -> Result<(), ()> {
-        self.lock();
-        let result = unsafe{
-            if sys::libvlc_media_list_insert_media(self.ptr, md.ptr, pos) == 0 { Ok(()) }else{ Err(()) }
-        };
-        self.unlock();
-
-        result
+        self.lock().insert_media(md, pos)
     }
 
     /// Remove media instance from media list on a position.
     pub fn remove_index(&self, pos: i32) -> Result<(), ()> {
-        self.lock();
-        let result = unsafe{
-            if sys::libvlc_media_list_remove_index(self.ptr, pos) == 0 { Ok(()) }else{ Err(()) }
-        };
-        self.unlock();
-
-        result
-
+        self.lock().remove_index(pos)
     }
 
     /// Get count on media list items.
     pub fn count(&self) -> i32 {
-        self.lock();
-        let result = unsafe{ sys::libvlc_media_list_count(self.ptr) };
-        self.unlock();
-
-        result
-
+        self.lock().count()
     }
 
     /// List media instance in media list at a position.
     pub fn item_at_index(&self, pos: i32) -> Option<Media> {
-        self.lock();
-        let result = unsafe{
-            let p = sys::libvlc_media_list_item_at_index(self.ptr, pos);
-            if p.is_null() { None }else{ Some(Media{ptr: p}) }
-        };
-        self.unlock();
-
-        result
-
+        self.lock().item_at_index(pos)
     }
 
     /// Find index position of List media instance in media list.
     pub fn index_of_item(&self, md: &Media) -> Option<i32> {
+        self.lock().index_of_item(md)
+    }
+
+    /// The items of the list, as they are when called.
+    pub fn iter(&self) -> MediaListIter {
+        self.lock().iter()
+    }
+
+    /// Remove every item.
+    pub fn clear(&self) -> Result<(), ()> {
+        self.lock().clear()
+    }
+
+    /// Move the item at `from` so that it ends up at `to`, shifting the items in between.
+    pub fn move_item(&self, from: i32, to: i32) -> Result<(), ()> {
+        self.lock().move_item(from, to)
+    }
+
+    /// Swap the items at `a` and `b`.
+    pub fn swap(&self, a: i32, b: i32) -> Result<(), ()> {
+        self.lock().swap(a, b)
+    }
+
+    /// Keep only the items for which `f` returns true.
+    pub fn retain<F: FnMut(&Media) -> bool>(&self, f: F) -> Result<(), ()> {
+        self.lock().retain(f)
+    }
+
+    /// Add a media for `uri` and read it, blocking until it is done.
+    /// The list stays locked while reading, so the other accessors wait for it.
+    /// Not available on [MediaListGuard], nor with libvlc 4 which removed it.
+    #[cfg(not(feature = "libvlc4"))]
+    pub fn add_file_content(&self, uri: &str) -> Result<(), ()> {
+        let cstr = to_cstr(uri);
+        // libvlc adds the media with libvlc_media_list_add_media, which needs the lock.
+        let _guard = self.lock();
         unsafe{
-            let i = sys::libvlc_media_list_index_of_item(self.ptr, md.ptr);
-            if i == -1 { None }else{ Some(i) }
+            if sys::libvlc_media_list_add_file_content(self.ptr, cstr.as_ptr()) == 0 { Ok(()) }else{ Err(()) }
         }
     }
 
@@ -108,15 +119,14 @@ impl MediaList {
         unsafe{ if sys::libvlc_media_list_is_readonly(self.ptr) == 0 { false }else{ true } }
     }
 
-    /// Get lock on media list items
-    fn lock(&self) {
+    /// Lock the list, to make several changes at once.
+    ///
+    /// The lock is not recursive: calling the methods of the list, e.g. `list.count()`, from the thread
+    /// holding the guard deadlocks, so go through the guard until it is dropped. The list events are sent
+    /// with the lock held, so their callbacks must not call the list either.
+    pub fn lock(&self) -> MediaListGuard<'_> {
         unsafe{ sys::libvlc_media_list_lock(self.ptr); }
-    }
-
-    /// Release lock on media list items
-    /// The libvlc_media_list_lock should be held upon entering this function.
-    fn unlock(&self) {
-        unsafe{ sys::libvlc_media_list_unlock(self.ptr); }
+        MediaListGuard{list: self, _not_send: PhantomData}
     }
 
     /// Get EventManager from this media list instance.
@@ -134,8 +144,198 @@ impl MediaList {
     }
 }
 
+impl Clone for MediaList {
+    /// Retain the media list. The underlying list is released when the last clone is dropped.
+    fn clone(&self) -> MediaList {
+        unsafe{ sys::libvlc_media_list_retain(self.ptr) };
+        MediaList{ptr: self.ptr}
+    }
+}
+
 impl Drop for MediaList {
     fn drop(&mut self) {
         unsafe{ sys::libvlc_media_list_release(self.ptr) };
     }
 }
+
+impl IntoIterator for &MediaList {
+    type Item = Media;
+    type IntoIter = MediaListIter;
+
+    fn into_iter(self) -> MediaListIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for MediaList {
+    type Item = Media;
+    type IntoIter = MediaListIter;
+
+    fn into_iter(self) -> MediaListIter {
+        self.iter()
+    }
+}
+
+/// Adds the media under one lock, stopping at the first one that cannot be added.
+impl Extend<Media> for MediaList {
+    fn extend<T: IntoIterator<Item = Media>>(&mut self, iter: T) {
+        let _ = self.lock().extend(iter);
+    }
+}
+
+/// Adds the media under one lock, stopping at the first one that cannot be added.
+impl<'a> Extend<&'a Media> for MediaList {
+    fn extend<T: IntoIterator<Item = &'a Media>>(&mut self, iter: T) {
+        let _ = self.lock().extend(iter);
+    }
+}
+
+/// The media list lock, released when dropped. See [MediaList::lock] for the methods that must not be
+/// called while it is held.
+pub struct MediaListGuard<'a> {
+    list: &'a MediaList,
+    // The lock has to be released by the thread holding it.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl MediaListGuard<'_> {
+    /// Add media instance to media list.
+    pub fn add_media(&self, md: &Media) -> Result<(), ()> {
+        unsafe{
+            if sys::libvlc_media_list_add_media(self.list.ptr, md.ptr) == 0 { Ok(()) }else{ Err(()) }
+        }
+    }
+
+    /// Insert media instance in media list on a position.
+    pub fn insert_media(&self, md: &Media, pos: i32) -> Result<(), ()> {
+        unsafe{
+            if sys::libvlc_media_list_insert_media(self.list.ptr, md.ptr, pos) == 0 { Ok(()) }else{ Err(()) }
+        }
+    }
+
+    /// Remove media instance from media list on a position.
+    pub fn remove_index(&self, pos: i32) -> Result<(), ()> {
+        unsafe{
+            if sys::libvlc_media_list_remove_index(self.list.ptr, pos) == 0 { Ok(()) }else{ Err(()) }
+        }
+    }
+
+    /// Get count on media list items.
+    pub fn count(&self) -> i32 {
+        unsafe{ sys::libvlc_media_list_count(self.list.ptr) }
+    }
+
+    /// List media instance in media list at a position.
+    pub fn item_at_index(&self, pos: i32) -> Option<Media> {
+        unsafe{
+            let p = sys::libvlc_media_list_item_at_index(self.list.ptr, pos);
+            if p.is_null() { None }else{ Some(Media{ptr: p}) }
+        }
+    }
+
+    /// Find index position of List media instance in media list.
+    pub fn index_of_item(&self, md: &Media) -> Option<i32> {
+        unsafe{
+            let i = sys::libvlc_media_list_index_of_item(self.list.ptr, md.ptr);
+            if i == -1 { None }else{ Some(i) }
+        }
+    }
+
+    /// The items of the list.
+    pub fn iter(&self) -> MediaListIter {
+        let items: Vec<Media> = (0..self.count()).filter_map(|i| self.item_at_index(i)).collect();
+        MediaListIter{inner: items.into_iter()}
+    }
+
+    /// Add media at the end of the list, stopping at the first one that cannot be added.
+    pub fn extend<I>(&self, iter: I) -> Result<(), ()>
+        where I: IntoIterator, I::Item: std::borrow::Borrow<Media>
+    {
+        for md in iter {
+            self.add_media(std::borrow::Borrow::borrow(&md))?;
+        }
+        Ok(())
+    }
+
+    /// Remove every item.
+    pub fn clear(&self) -> Result<(), ()> {
+        for i in (0..self.count()).rev() {
+            self.remove_index(i)?;
+        }
+        Ok(())
+    }
+
+    /// Move the item at `from` so that it ends up at `to`, shifting the items in between.
+    pub fn move_item(&self, from: i32, to: i32) -> Result<(), ()> {
+        let count = self.count();
+        if from < 0 || from >= count || to < 0 || to >= count {
+            return Err(());
+        }
+        if from == to {
+            return Ok(());
+        }
+        let md = self.item_at_index(from).ok_or(())?;
+        self.remove_index(from)?;
+        self.insert_media(&md, to)
+    }
+
+    /// Swap the items at `a` and `b`.
+    pub fn swap(&self, a: i32, b: i32) -> Result<(), ()> {
+        let (low, high) = if a < b { (a, b) }else{ (b, a) };
+        if low < 0 || high >= self.count() {
+            return Err(());
+        }
+        if low == high {
+            return Ok(());
+        }
+        let low_md = self.item_at_index(low).ok_or(())?;
+        let high_md = self.item_at_index(high).ok_or(())?;
+        // Replace the higher one first, so that the lower index stays valid.
+        self.remove_index(high)?;
+        self.insert_media(&low_md, high)?;
+        self.remove_index(low)?;
+        self.insert_media(&high_md, low)
+    }
+
+    /// Keep only the items for which `f` returns true.
+    pub fn retain<F: FnMut(&Media) -> bool>(&self, mut f: F) -> Result<(), ()> {
+        let items: Vec<Media> = self.iter().collect();
+        for (i, md) in items.iter().enumerate().rev() {
+            if !f(md) {
+                self.remove_index(i as i32)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MediaListGuard<'_> {
+    fn drop(&mut self) {
+        unsafe{ sys::libvlc_media_list_unlock(self.list.ptr); }
+    }
+}
+
+/// The items of a [MediaList], taken when the iterator is created.
+pub struct MediaListIter {
+    inner: vec::IntoIter<Media>,
+}
+
+impl Iterator for MediaListIter {
+    type Item = Media;
+
+    fn next(&mut self) -> Option<Media> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for MediaListIter {
+    fn next_back(&mut self) -> Option<Media> {
+        self.inner.next_back()
+    }
+}
+
+impl ExactSizeIterator for MediaListIter {}
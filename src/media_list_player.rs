@@ -8,6 +8,9 @@ pub struct MediaListPlayer {
 
 unsafe impl Send for MediaListPlayer {}
 
+// The list player serializes its operations with an internal lock.
+unsafe impl Sync for MediaListPlayer {}
+
 impl MediaListPlayer {
     /// Create a new MediaListPlayer instance
     pub fn new(instance: &Instance) -> Option<MediaListPlayer> {
@@ -70,9 +73,24 @@ impl MediaListPlayer {
         }
     }
 
-    /// Stop media playback
+    /// Stop media playback, returning once the player has stopped.
+    /// Must not be called from the player's event callbacks, see [MediaListPlayer::stop_async].
     pub fn stop(&self) {
+        #[cfg(not(feature = "libvlc4"))]
+        self.stop_async();
+        #[cfg(feature = "libvlc4")]
+        match self.get_media_player() {
+            Some(player) => player.wait_stopped(|| { self.stop_async(); Ok(()) }),
+            None => self.stop_async(),
+        }
+    }
+
+    /// Request the list player to stop. libvlc 3 stops synchronously.
+    pub fn stop_async(&self) {
+        #[cfg(not(feature = "libvlc4"))]
         unsafe { sys::libvlc_media_list_player_stop(self.ptr) }
+        #[cfg(feature = "libvlc4")]
+        unsafe { sys::libvlc_media_list_player_stop_async(self.ptr) }
     }
 
     /// Skip to next media in list
@@ -101,6 +119,14 @@ impl MediaListPlayer {
     }
 }
 
+impl Clone for MediaListPlayer {
+    /// Retain the list player. The underlying player is released when the last clone is dropped.
+    fn clone(&self) -> MediaListPlayer {
+        unsafe { sys::libvlc_media_list_player_retain(self.ptr) };
+        MediaListPlayer { ptr: self.ptr }
+    }
+}
+
 impl Drop for MediaListPlayer {
     fn drop(&mut self) {
         unsafe { sys::libvlc_media_list_player_release(self.ptr) }
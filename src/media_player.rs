@@ -8,7 +8,24 @@ use crate::Media;
 use crate::EventManager;
 use libc::{c_void, c_uint};
 use crate::enums::{State, Position};
+#[cfg(feature = "libvlc4")]
+use crate::enums::TrackType;
+#[cfg(feature = "libvlc4")]
+use crate::MediaTrack;
+#[cfg(feature = "libvlc4")]
+use crate::tools::{from_cstr, to_cstr};
 use std::mem::transmute;
+use std::time::Duration;
+use crate::MTime;
+use crate::tools::{duration_to_ms, ms_to_duration};
+#[cfg(feature = "libvlc4")]
+use crate::{EventManagerApi, EventType};
+#[cfg(feature = "libvlc4")]
+use std::sync::mpsc::channel;
+
+// How often a blocking stop checks the player state.
+#[cfg(feature = "libvlc4")]
+const STOP_POLL: Duration = Duration::from_millis(50);
 
 /// A LibVLC media player plays one media (usually in a custom drawable).
 pub struct MediaPlayer {
@@ -17,6 +34,9 @@ pub struct MediaPlayer {
 
 unsafe impl Send for MediaPlayer {}
 
+// All libvlc_media_player_* calls take the player's object lock.
+unsafe impl Sync for MediaPlayer {}
+
 impl MediaPlayer {
     /// Create an empty Media Player object
     pub fn new(instance: &Instance) -> Option<MediaPlayer> {
@@ -82,19 +102,61 @@ impl MediaPlayer {
         unsafe{ sys::libvlc_media_player_pause(self.ptr) };
     }
 
-    /// Stop (no effect if there is no media)
+    /// Stop (no effect if there is no media), returning once the player has stopped.
+    /// With libvlc 4 this waits for MediaPlayerStopped, so it must not be called from the
+    /// player's event callbacks: use [MediaPlayer::stop_async] there.
     pub fn stop(&self) {
+        #[cfg(not(feature = "libvlc4"))]
+        let _ = self.stop_async();
+        #[cfg(feature = "libvlc4")]
+        self.wait_stopped(|| self.stop_async());
+    }
+
+    // Send a stop request and wait until the player has stopped.
+    #[cfg(feature = "libvlc4")]
+    pub(crate) fn wait_stopped<F: FnOnce() -> Result<(), ()>>(&self, request: F) {
+        let (tx, rx) = channel();
+        let em = self.event_manager();
+        let handle = EventManagerApi::attach(&em, EventType::MediaPlayerStopped, move |_, _| {
+            let _ = tx.send(());
+        });
+
+        if request().is_ok() {
+            // The state is polled too, as no event is sent when the player was already stopped.
+            while !matches!(self.state(), State::NothingSpecial | State::Stopped | State::Error) {
+                let _ = rx.recv_timeout(STOP_POLL);
+            }
+        }
+
+        if let Ok(handle) = handle {
+            EventManagerApi::detach(&em, EventType::MediaPlayerStopped, handle);
+        }
+    }
+
+    /// Request the player to stop. MediaPlayerStopped is sent once it has stopped.
+    /// libvlc 3 stops synchronously.
+    pub fn stop_async(&self) -> Result<(), ()> {
+        #[cfg(not(feature = "libvlc4"))]
         unsafe{ sys::libvlc_media_player_stop(self.ptr) };
+        #[cfg(feature = "libvlc4")]
+        {
+            if unsafe{ sys::libvlc_media_player_stop_async(self.ptr) } != 0 {
+                return Err(());
+            }
+        }
+        Ok(())
     }
 
-    pub fn set_callbacks<F>(
+    /// Set callbacks receiving the decoded audio instead of an audio output.
+    /// `play` gets the samples, their count and their pts; the others get the pts of the event.
+    pub fn set_audio_callbacks<F>(
         &self,
         play: F,
-        pause: Option<Box<dyn Fn(i64) + Send + 'static>>,
-        resume: Option<Box<dyn Fn(i64) + Send + 'static>>,
-        flush: Option<Box<dyn Fn(i64) + Send + 'static>>,
+        pause: Option<Box<dyn Fn(MTime) + Send + 'static>>,
+        resume: Option<Box<dyn Fn(MTime) + Send + 'static>>,
+        flush: Option<Box<dyn Fn(MTime) + Send + 'static>>,
         drain: Option<Box<dyn Fn() + Send + 'static>>)
-        where F: Fn(*const c_void, u32, i64) + Send + 'static,
+        where F: Fn(*const c_void, u32, MTime) + Send + 'static,
     {
         let flag_pause = pause.is_some();
         let flag_resume = resume.is_some();
@@ -119,6 +181,26 @@ impl MediaPlayer {
         }
     }
 
+    /// Set audio callbacks receiving pts as microseconds.
+    #[deprecated(note = "use `MediaPlayer::set_audio_callbacks`")]
+    pub fn set_callbacks<F>(
+        &self,
+        play: F,
+        pause: Option<Box<dyn Fn(i64) + Send + 'static>>,
+        resume: Option<Box<dyn Fn(i64) + Send + 'static>>,
+        flush: Option<Box<dyn Fn(i64) + Send + 'static>>,
+        drain: Option<Box<dyn Fn() + Send + 'static>>)
+        where F: Fn(*const c_void, u32, i64) + Send + 'static,
+    {
+        fn micros(f: Option<Box<dyn Fn(i64) + Send + 'static>>) -> Option<Box<dyn Fn(MTime) + Send + 'static>> {
+            f.map(|f| Box::new(move |pts: MTime| f(pts.as_micros())) as Box<dyn Fn(MTime) + Send + 'static>)
+        }
+
+        self.set_audio_callbacks(
+            move |samples, count, pts| play(samples, count, pts.as_micros()),
+            micros(pause), micros(resume), micros(flush), drain);
+    }
+
     /// Set the NSView handler where the media player should render its video output.
     pub fn set_nsobject(&self, drawable: *mut c_void) {
         unsafe{ sys::libvlc_media_player_set_nsobject(self.ptr, drawable) };
@@ -153,7 +235,23 @@ impl MediaPlayer {
         if hwnd.is_null() { None }else{ Some(hwnd) }
     }
 
+    /// Get the current movie time.
+    pub fn time(&self) -> Option<Duration> {
+        ms_to_duration(unsafe{ sys::libvlc_media_player_get_time(self.ptr) })
+    }
+
+    /// Set the movie time.
+    /// This has no effect if no media is being played. Not all formats and protocols support this.
+    pub fn seek(&self, time: Duration) {
+        let time = duration_to_ms(time);
+        #[cfg(not(feature = "libvlc4"))]
+        unsafe{ sys::libvlc_media_player_set_time(self.ptr, time); }
+        #[cfg(feature = "libvlc4")]
+        unsafe{ sys::libvlc_media_player_set_time(self.ptr, time, false); }
+    }
+
     /// Get the current movie time (in ms).
+    #[deprecated(note = "use `MediaPlayer::time`")]
     pub fn get_time(&self) -> Option<i64> {
         unsafe{
             let t = sys::libvlc_media_player_get_time(self.ptr);
@@ -162,15 +260,26 @@ impl MediaPlayer {
     }
 
     /// Set the movie time (in ms).
-    /// This has no effect if no media is being played. Not all formats and protocols support this.
+    #[deprecated(note = "use `MediaPlayer::seek`")]
     pub fn set_time(&self, time: i64) {
-        unsafe{ sys::libvlc_media_player_set_time(self.ptr, time); }
+        if time >= 0 {
+            self.seek(Duration::from_millis(time as u64));
+        }
+    }
+
+    /// Get the length of the current media, or None if there is none.
+    pub fn length(&self) -> Option<Duration> {
+        ms_to_duration(unsafe{ sys::libvlc_media_player_get_length(self.ptr) })
     }
 
     /// Get movie position as percentage between 0.0 and 1.0.
     pub fn get_position(&self) -> Option<f32> {
         unsafe{
+            #[cfg(not(feature = "libvlc4"))]
             let pos = sys::libvlc_media_player_get_position(self.ptr);
+            // libvlc 4 returns a double.
+            #[cfg(feature = "libvlc4")]
+            let pos = sys::libvlc_media_player_get_position(self.ptr) as f32;
             if pos == -1f32 { None }else{ Some(pos) }
         }
     }
@@ -178,7 +287,10 @@ impl MediaPlayer {
     /// Set movie position as percentage between 0.0 and 1.0.
     /// This has no effect if playback is not enabled. This might not work depending on the underlying input format and protocol.
     pub fn set_position(&self, pos: f32) {
+        #[cfg(not(feature = "libvlc4"))]
         unsafe{ sys::libvlc_media_player_set_position(self.ptr, pos); }
+        #[cfg(feature = "libvlc4")]
+        unsafe{ sys::libvlc_media_player_set_position(self.ptr, pos as f64, false); }
     }
 
     /// Set movie chapter (if applicable).
@@ -203,6 +315,7 @@ impl MediaPlayer {
     }
 
     /// Is the player able to play.
+    #[cfg(not(feature = "libvlc4"))]
     pub fn will_play(&self) -> bool {
         unsafe{
             let b = sys::libvlc_media_player_will_play(self.ptr);
@@ -235,7 +348,7 @@ impl MediaPlayer {
     pub fn title_count(&self) -> Option<i32> {
         unsafe{
             let t = sys::libvlc_media_player_get_title_count(self.ptr);
-            if t == -1 { Some(t) } else { None }
+            if t == -1 { None }else{ Some(t) }
         }
     }
 
@@ -292,6 +405,7 @@ impl MediaPlayer {
     }
 
     /// Check if the current program is scrambled.
+    #[cfg(not(feature = "libvlc4"))]
     pub fn program_scrambled(&self) -> bool {
         unsafe{
             let b = sys::libvlc_media_player_program_scrambled(self.ptr);
@@ -299,6 +413,130 @@ impl MediaPlayer {
         }
     }
 
+    /// Check if the current program is scrambled.
+    #[cfg(feature = "libvlc4")]
+    pub fn program_scrambled(&self) -> bool {
+        self.selected_program().is_some_and(|p| p.scrambled)
+    }
+
+    /// Get the programs of the current media. Only available with libvlc 4.
+    #[cfg(feature = "libvlc4")]
+    pub fn programs(&self) -> Vec<Program> {
+        unsafe{
+            let list = sys::libvlc_media_player_get_programlist(self.ptr);
+            if list.is_null() {
+                return Vec::new();
+            }
+            let programs = (0..sys::libvlc_player_programlist_count(list))
+                .map(|i| Program::from_raw(sys::libvlc_player_programlist_at(list, i)))
+                .collect();
+            sys::libvlc_player_programlist_delete(list);
+            programs
+        }
+    }
+
+    /// Get the selected program. Only available with libvlc 4.
+    #[cfg(feature = "libvlc4")]
+    pub fn selected_program(&self) -> Option<Program> {
+        unsafe{
+            let p = sys::libvlc_media_player_get_selected_program(self.ptr);
+            if p.is_null() {
+                return None;
+            }
+            let program = Program::from_raw(p);
+            sys::libvlc_player_program_delete(p);
+            Some(program)
+        }
+    }
+
+    /// Select the program with the given group id. Only available with libvlc 4.
+    #[cfg(feature = "libvlc4")]
+    pub fn select_program(&self, group_id: i32) {
+        unsafe{ sys::libvlc_media_player_select_program_id(self.ptr, group_id) };
+    }
+
+    /// Get the tracks of the given type, or only the selected ones. Only available with libvlc 4.
+    #[cfg(feature = "libvlc4")]
+    pub fn tracklist(&self, track_type: TrackType, selected: bool) -> Vec<MediaTrack> {
+        unsafe{
+            let list = sys::libvlc_media_player_get_tracklist(self.ptr, track_type as sys::libvlc_track_type_t, selected);
+            if list.is_null() { Vec::new() }else{ MediaTrack::from_tracklist(list) }
+        }
+    }
+
+    /// Get the selected track of the given type. Only available with libvlc 4.
+    #[cfg(feature = "libvlc4")]
+    pub fn selected_track(&self, track_type: TrackType) -> Option<MediaTrack> {
+        unsafe{
+            let p = sys::libvlc_media_player_get_selected_track(self.ptr, track_type as sys::libvlc_track_type_t);
+            if p.is_null() {
+                return None;
+            }
+            let track = MediaTrack::from_raw(p);
+            sys::libvlc_media_track_release(p);
+            Some(track)
+        }
+    }
+
+    /// Select a track by its [MediaTrack::str_id]. Only available with libvlc 4.
+    #[cfg(feature = "libvlc4")]
+    pub fn select_track(&self, str_id: &str) -> Result<(), ()> {
+        unsafe{
+            let p = sys::libvlc_media_player_get_track_from_id(self.ptr, to_cstr(str_id).as_ptr());
+            if p.is_null() {
+                return Err(());
+            }
+            sys::libvlc_media_player_select_track(self.ptr, p);
+            sys::libvlc_media_track_release(p);
+            Ok(())
+        }
+    }
+
+    /// Unselect all tracks of the given type. Only available with libvlc 4.
+    #[cfg(feature = "libvlc4")]
+    pub fn unselect_track_type(&self, track_type: TrackType) {
+        unsafe{ sys::libvlc_media_player_unselect_track_type(self.ptr, track_type as sys::libvlc_track_type_t) };
+    }
+
+    // Ids and names of the tracks of a type, as libvlc 3 track descriptions.
+    #[cfg(feature = "libvlc4")]
+    pub(crate) fn track_descriptions(&self, track_type: TrackType) -> Option<Vec<TrackDescription>> {
+        let tracks = self.tracklist(track_type, false);
+        if tracks.is_empty() {
+            return None;
+        }
+        Some(tracks.into_iter().map(|t| TrackDescription{ id: t.id, name: t.name }).collect())
+    }
+
+    // Numeric id of the selected track of a type.
+    #[cfg(feature = "libvlc4")]
+    pub(crate) fn selected_track_id(&self, track_type: TrackType) -> Option<i32> {
+        self.selected_track(track_type).map(|t| t.id)
+    }
+
+    // Select a track by its numeric id, or unselect the type if `id` is -1.
+    // Backs the libvlc 3 style track setters.
+    #[cfg(feature = "libvlc4")]
+    pub(crate) fn select_track_by_es_id(&self, track_type: TrackType, id: i32) {
+        if id == -1 {
+            return self.unselect_track_type(track_type);
+        }
+        unsafe{
+            let list = sys::libvlc_media_player_get_tracklist(self.ptr, track_type as sys::libvlc_track_type_t, false);
+            if list.is_null() {
+                return;
+            }
+            for i in 0..sys::libvlc_media_tracklist_count(list) {
+                let p = sys::libvlc_media_tracklist_at(list, i);
+                if (*p).i_id == id {
+                    sys::libvlc_media_player_select_track(self.ptr, p);
+                    break;
+                }
+            }
+            sys::libvlc_media_tracklist_delete(list);
+        }
+    }
+
     /// Display the next frame (if supported)
     pub fn next_frame(&self) {
         unsafe{ sys::libvlc_media_player_next_frame(self.ptr); }
@@ -320,6 +558,14 @@ impl MediaPlayer {
     }
 }
 
+impl Clone for MediaPlayer {
+    /// Retain the media player. The underlying player is released when the last clone is dropped.
+    fn clone(&self) -> MediaPlayer {
+        unsafe{ sys::libvlc_media_player_retain(self.ptr) };
+        MediaPlayer{ptr: self.ptr}
+    }
+}
+
 impl Drop for MediaPlayer {
     fn drop(&mut self) {
         unsafe{ sys::libvlc_media_player_release(self.ptr) };
@@ -328,33 +574,33 @@ impl Drop for MediaPlayer {
 
 // For audio_set_callbacks
 struct AudioCallbacksData {
-    play: Box<dyn Fn(*const c_void, u32, i64) + Send + 'static>,
-    pause: Option<Box<dyn Fn(i64) + Send + 'static>>,
-    resume: Option<Box<dyn Fn(i64) + Send + 'static>>,
-    flush: Option<Box<dyn Fn(i64) + Send + 'static>>,
+    play: Box<dyn Fn(*const c_void, u32, MTime) + Send + 'static>,
+    pause: Option<Box<dyn Fn(MTime) + Send + 'static>>,
+    resume: Option<Box<dyn Fn(MTime) + Send + 'static>>,
+    flush: Option<Box<dyn Fn(MTime) + Send + 'static>>,
     drain: Option<Box<dyn Fn() + Send + 'static>>,
 }
 
 unsafe extern "C" fn audio_cb_play(
     data: *mut c_void, samples: *const c_void, count: c_uint, pts: i64) {
     let data: &AudioCallbacksData = transmute(data as *mut AudioCallbacksData);
-    (data.play)(samples, count, pts);
+    (data.play)(samples, count, MTime::from_micros(pts));
 
 }
 
 unsafe extern "C" fn audio_cb_pause(data: *mut c_void, pts: i64) {
     let data: &AudioCallbacksData = transmute(data as *mut AudioCallbacksData);
-    (data.pause.as_ref().unwrap())(pts);
+    (data.pause.as_ref().unwrap())(MTime::from_micros(pts));
 }
 
 unsafe extern "C" fn audio_cb_resume(data: *mut c_void, pts: i64) {
     let data: &AudioCallbacksData = transmute(data as *mut AudioCallbacksData);
-    (data.resume.as_ref().unwrap())(pts);
+    (data.resume.as_ref().unwrap())(MTime::from_micros(pts));
 }
 
 unsafe extern "C" fn audio_cb_flush(data: *mut c_void, pts: i64) {
     let data: &AudioCallbacksData = transmute(data as *mut AudioCallbacksData);
-    (data.flush.as_ref().unwrap())(pts);
+    (data.flush.as_ref().unwrap())(MTime::from_micros(pts));
 }
 
 unsafe extern "C" fn audio_cb_drain(data: *mut c_void) {
@@ -363,8 +609,32 @@ unsafe extern "C" fn audio_cb_drain(data: *mut c_void) {
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TrackDescription {
     pub id: i32,
     pub name: Option<String>,
 }
 
+/// A program (e.g. a TV channel) of the current media. Only available with libvlc 4.
+#[cfg(feature = "libvlc4")]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Program {
+    pub group_id: i32,
+    pub name: Option<String>,
+    pub selected: bool,
+    pub scrambled: bool,
+}
+
+#[cfg(feature = "libvlc4")]
+impl Program {
+    unsafe fn from_raw(p: *const sys::libvlc_player_program_t) -> Program {
+        Program {
+            group_id: (*p).i_group_id,
+            name: from_cstr((*p).psz_name),
+            selected: (*p).b_selected,
+            scrambled: (*p).b_scrambled,
+        }
+    }
+}
+
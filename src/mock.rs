@@ -0,0 +1,788 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+//! In-process fakes implementing the traits of this crate, to test code which drives
+//! players and playlists without libvlc or media files.
+//!
+//! Nothing happens on its own: the clock only moves on [MockMediaPlayer::advance] or
+//! [MockMediaListPlayer::advance], and events are delivered on the calling thread.
+//! `play` puts a player in `Opening`, the next `advance` makes it `Playing`, and it
+//! ends once its time reaches the media duration.
+//!
+//! ```
+//! use std::time::Duration;
+//! use vlc::mock::{MockMedia, MockMediaPlayer};
+//! use vlc::{MediaPlayerApi, State};
+//!
+//! let player = MockMediaPlayer::new();
+//! player.set_media(&MockMedia::new("file:///song.ogg", Some(Duration::from_secs(3))));
+//! player.play().unwrap();
+//! player.advance(Duration::from_secs(1));
+//! assert_eq!(player.state(), State::Playing);
+//! assert_eq!(player.time(), Some(Duration::from_secs(1)));
+//! player.seek(Duration::from_millis(2500));
+//! player.advance(Duration::from_secs(1));
+//! assert!(player.time().is_none());
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::mem;
+use std::ptr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::{Event, EventType, Meta, PlaybackMode, State, VLCObject};
+use crate::{EventManagerApi, MediaApi, MediaListApi, MediaListPlayerApi, MediaPlayerApi};
+use crate::{MediaPlayerAudioEx, TrackDescription};
+use crate::tools::{duration_to_ms, ms_to_duration};
+
+type Callback = Arc<Mutex<Box<dyn Fn(Event, VLCObject) + Send + 'static>>>;
+
+// Events to deliver once the state lock is released, as callbacks may call back into the mock.
+type Pending = Vec<(MockEventManager, Event)>;
+
+// A panicking test callback must not take the other tests' mocks down with it.
+fn lock<T>(m: &Mutex<T>) -> MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn emit_all(pending: Pending) {
+    for (em, event) in pending {
+        em.emit(event);
+    }
+}
+
+#[derive(Default)]
+struct Dispatcher {
+    next_id: usize,
+    callbacks: Vec<(usize, EventType, Callback)>,
+    queue: VecDeque<Event>,
+    dispatching: bool,
+    emitted: Vec<Event>,
+}
+
+/// Delivers events synchronously, in the order they are emitted.
+/// Events emitted by a callback are delivered after it returns.
+#[derive(Clone, Default)]
+pub struct MockEventManager {
+    inner: Arc<Mutex<Dispatcher>>,
+}
+
+impl MockEventManager {
+    pub fn new() -> MockEventManager {
+        Default::default()
+    }
+
+    /// Deliver `event` to the callbacks attached for its type.
+    pub fn emit(&self, event: Event) {
+        {
+            let mut d = lock(&self.inner);
+            d.emitted.push(event.clone());
+            d.queue.push_back(event);
+            if d.dispatching {
+                return;
+            }
+            d.dispatching = true;
+        }
+
+        loop {
+            let (event, callbacks) = {
+                let mut d = lock(&self.inner);
+                match d.queue.pop_front() {
+                    Some(event) => {
+                        let event_type = event.event_type();
+                        let callbacks: Vec<Callback> = d.callbacks.iter()
+                            .filter(|c| c.1 == event_type)
+                            .map(|c| c.2.clone())
+                            .collect();
+                        (event, callbacks)
+                    },
+                    None => {
+                        d.dispatching = false;
+                        return;
+                    },
+                }
+            };
+            for callback in callbacks {
+                (lock(&callback))(event.clone(), VLCObject{ ptr: ptr::null_mut() });
+            }
+        }
+    }
+
+    /// Events emitted since the last call, whether or not a callback was attached.
+    pub fn take_emitted(&self) -> Vec<Event> {
+        mem::take(&mut lock(&self.inner).emitted)
+    }
+}
+
+impl EventManagerApi for MockEventManager {
+    type Handle = usize;
+
+    fn attach<F>(&self, event_type: EventType, callback: F) -> Result<usize, ()>
+        where F: Fn(Event, VLCObject) + Send + 'static
+    {
+        let mut d = lock(&self.inner);
+        d.next_id += 1;
+        let id = d.next_id;
+        d.callbacks.push((id, event_type, Arc::new(Mutex::new(Box::new(callback)))));
+        Ok(id)
+    }
+
+    fn detach(&self, event_type: EventType, handle: usize) {
+        lock(&self.inner).callbacks.retain(|c| c.0 != handle || c.1 != event_type);
+    }
+}
+
+struct MediaData {
+    mrl: String,
+    duration: Option<i64>,
+    meta: HashMap<Meta, String>,
+    options: Vec<String>,
+    state: State,
+    fails: bool,
+}
+
+/// A fake media. Clones share their state, and compare equal like the libvlc object would.
+#[derive(Clone)]
+pub struct MockMedia {
+    data: Arc<Mutex<MediaData>>,
+    events: MockEventManager,
+}
+
+impl MockMedia {
+    /// A media at `mrl` lasting `duration`, or a stream which never ends if `None`.
+    pub fn new(mrl: &str, duration: Option<Duration>) -> MockMedia {
+        MockMedia {
+            data: Arc::new(Mutex::new(MediaData {
+                mrl: mrl.to_owned(),
+                duration: duration.map(duration_to_ms),
+                meta: HashMap::new(),
+                options: Vec::new(),
+                state: State::NothingSpecial,
+                fails: false,
+            })),
+            events: MockEventManager::new(),
+        }
+    }
+
+    /// A media which fails to open: players go to `Error` instead of `Playing`.
+    pub fn failing(mrl: &str) -> MockMedia {
+        let md = MockMedia::new(mrl, None);
+        lock(&md.data).fails = true;
+        md
+    }
+
+    /// Options given to `add_option`, in order.
+    pub fn options(&self) -> Vec<String> {
+        lock(&self.data).options.clone()
+    }
+}
+
+impl PartialEq for MockMedia {
+    fn eq(&self, other: &MockMedia) -> bool {
+        Arc::ptr_eq(&self.data, &other.data)
+    }
+}
+
+impl MediaApi for MockMedia {
+    type EventManager<'a> = MockEventManager;
+
+    fn mrl(&self) -> Option<String> {
+        Some(lock(&self.data).mrl.clone())
+    }
+
+    fn get_meta(&self, meta: Meta) -> Option<String> {
+        lock(&self.data).meta.get(&meta).cloned()
+    }
+
+    fn set_meta(&self, meta: Meta, value: &str) {
+        lock(&self.data).meta.insert(meta, value.to_owned());
+        self.events.emit(Event::MediaMetaChanged(meta));
+    }
+
+    fn add_option(&self, option: &str) {
+        lock(&self.data).options.push(option.to_owned());
+    }
+
+    fn state(&self) -> State {
+        lock(&self.data).state
+    }
+
+    fn length(&self) -> Option<Duration> {
+        lock(&self.data).duration.and_then(ms_to_duration)
+    }
+
+    fn event_manager(&self) -> MockEventManager {
+        self.events.clone()
+    }
+}
+
+struct PlayerData {
+    events: MockEventManager,
+    media: Option<MockMedia>,
+    state: State,
+    time: i64,
+    rate: f32,
+    seekable: bool,
+    pausable: bool,
+    reached_end: bool,
+    volume: i32,
+    muted: bool,
+}
+
+impl PlayerData {
+    fn emit(&self, pending: &mut Pending, event: Event) {
+        pending.push((self.events.clone(), event));
+    }
+
+    fn set_state(&mut self, state: State, pending: &mut Pending) {
+        self.state = state;
+        if let Some(ref md) = self.media {
+            lock(&md.data).state = state;
+            #[cfg(not(feature = "libvlc4"))]
+            pending.push((md.events.clone(), Event::MediaStateChanged(state)));
+        }
+        let event = match state {
+            State::NothingSpecial => Event::MediaPlayerNothingSpecial,
+            State::Opening => Event::MediaPlayerOpening,
+            State::Playing => Event::MediaPlayerPlaying,
+            State::Paused => Event::MediaPlayerPaused,
+            State::Stopped => Event::MediaPlayerStopped,
+            State::Error => Event::MediaPlayerEncounteredError,
+            _ => return,
+        };
+        self.emit(pending, event);
+    }
+
+    // Whether there is an input with a current time.
+    fn is_active(&self) -> bool {
+        self.state == State::Playing || self.state == State::Paused
+    }
+
+    fn length(&self) -> Option<i64> {
+        self.media.as_ref().and_then(|md| lock(&md.data).duration)
+    }
+
+    fn position(&self) -> Option<f32> {
+        self.length().filter(|&len| len > 0).map(|len| self.time as f32 / len as f32)
+    }
+
+    // Move to `time`, clamped to the media, and end the playback if it was reached.
+    fn move_to(&mut self, time: i64, pending: &mut Pending) {
+        let length = self.length();
+        self.time = match length {
+            Some(len) => time.max(0).min(len),
+            None => time.max(0),
+        };
+        self.emit(pending, Event::MediaPlayerTimeChanged(self.time));
+        if let Some(pos) = self.position() {
+            self.emit(pending, Event::MediaPlayerPositionChanged(pos));
+        }
+        if length.is_some_and(|len| self.time >= len) {
+            self.finish(pending);
+        }
+    }
+
+    fn finish(&mut self, pending: &mut Pending) {
+        self.reached_end = true;
+        #[cfg(not(feature = "libvlc4"))]
+        {
+            self.set_state(State::Ended, pending);
+            self.emit(pending, Event::MediaPlayerEndReached);
+        }
+        #[cfg(feature = "libvlc4")]
+        {
+            self.emit(pending, Event::MediaPlayerStopping);
+            self.set_state(State::Stopped, pending);
+        }
+    }
+}
+
+/// A fake media player. Clones share their state.
+#[derive(Clone)]
+pub struct MockMediaPlayer {
+    data: Arc<Mutex<PlayerData>>,
+    events: MockEventManager,
+}
+
+impl MockMediaPlayer {
+    pub fn new() -> MockMediaPlayer {
+        let events = MockEventManager::new();
+        MockMediaPlayer {
+            data: Arc::new(Mutex::new(PlayerData {
+                events: events.clone(),
+                media: None,
+                state: State::NothingSpecial,
+                time: 0,
+                rate: 1.0,
+                seekable: true,
+                pausable: true,
+                reached_end: false,
+                volume: 100,
+                muted: false,
+            })),
+            events,
+        }
+    }
+
+    fn update<R, F: FnOnce(&mut PlayerData, &mut Pending) -> R>(&self, f: F) -> R {
+        let mut pending = Vec::new();
+        let r = f(&mut lock(&self.data), &mut pending);
+        emit_all(pending);
+        r
+    }
+
+    /// Let `elapsed` pass. Playback time moves by `elapsed` times the rate.
+    /// An opening player first starts playing, or fails if its media is [MockMedia::failing].
+    pub fn advance(&self, elapsed: Duration) {
+        let ms = duration_to_ms(elapsed);
+        self.update(|d, pending| {
+            if d.state == State::Opening {
+                if d.media.as_ref().is_some_and(|md| lock(&md.data).fails) {
+                    d.set_state(State::Error, pending);
+                    return;
+                }
+                d.set_state(State::Playing, pending);
+                if d.length().is_some() {
+                    d.emit(pending, Event::MediaPlayerLengthChanged);
+                }
+            }
+            if d.state == State::Playing && ms > 0 {
+                let time = d.time + (ms as f64 * d.rate as f64) as i64;
+                d.move_to(time, pending);
+            }
+        })
+    }
+
+    /// Make the current media seekable or not, as a live stream would be.
+    pub fn set_seekable(&self, seekable: bool) {
+        self.update(|d, pending| {
+            d.seekable = seekable;
+            d.emit(pending, Event::MediaPlayerSeekableChanged);
+        })
+    }
+
+    /// Make the current media pausable or not.
+    pub fn set_pausable(&self, pausable: bool) {
+        self.update(|d, pending| {
+            d.pausable = pausable;
+            d.emit(pending, Event::MediaPlayerPausableChanged);
+        })
+    }
+
+    // Whether playback reached the end of the media since the last call.
+    fn take_reached_end(&self) -> bool {
+        mem::replace(&mut lock(&self.data).reached_end, false)
+    }
+}
+
+impl Default for MockMediaPlayer {
+    fn default() -> MockMediaPlayer {
+        MockMediaPlayer::new()
+    }
+}
+
+impl MediaPlayerApi for MockMediaPlayer {
+    type Media = MockMedia;
+    type EventManager<'a> = MockEventManager;
+
+    fn set_media(&self, md: &MockMedia) {
+        self.update(|d, pending| {
+            if d.is_active() || d.state == State::Opening {
+                // libvlc 4 stops the previous media as stop() does.
+                #[cfg(feature = "libvlc4")]
+                d.emit(pending, Event::MediaPlayerStopping);
+                d.set_state(State::Stopped, pending);
+            }
+            d.media = Some(md.clone());
+            d.state = State::NothingSpecial;
+            d.time = 0;
+            d.reached_end = false;
+            d.emit(pending, Event::MediaPlayerMediaChanged);
+        })
+    }
+
+    fn get_media(&self) -> Option<MockMedia> {
+        lock(&self.data).media.clone()
+    }
+
+    fn event_manager(&self) -> MockEventManager {
+        self.events.clone()
+    }
+
+    fn is_playing(&self) -> bool {
+        lock(&self.data).state == State::Playing
+    }
+
+    fn play(&self) -> Result<(), ()> {
+        self.update(|d, pending| {
+            if d.media.is_none() {
+                return Err(());
+            }
+            match d.state {
+                State::Opening | State::Playing => (),
+                State::Paused => d.set_state(State::Playing, pending),
+                _ => {
+                    d.time = 0;
+                    d.reached_end = false;
+                    d.set_state(State::Opening, pending);
+                },
+            }
+            Ok(())
+        })
+    }
+
+    fn set_pause(&self, do_pause: bool) {
+        self.update(|d, pending| {
+            if do_pause && d.state == State::Playing && d.pausable {
+                d.set_state(State::Paused, pending);
+            } else if !do_pause && d.state == State::Paused {
+                d.set_state(State::Playing, pending);
+            }
+        })
+    }
+
+    fn pause(&self) {
+        let playing = self.is_playing();
+        self.set_pause(playing);
+    }
+
+    fn stop(&self) {
+        self.update(|d, pending| {
+            match d.state {
+                State::NothingSpecial | State::Stopped => (),
+                _ => {
+                    #[cfg(feature = "libvlc4")]
+                    d.emit(pending, Event::MediaPlayerStopping);
+                    d.time = 0;
+                    d.set_state(State::Stopped, pending);
+                },
+            }
+        })
+    }
+
+    fn time(&self) -> Option<Duration> {
+        let d = lock(&self.data);
+        if d.is_active() { ms_to_duration(d.time) } else { None }
+    }
+
+    fn seek(&self, time: Duration) {
+        self.update(|d, pending| {
+            if d.is_active() && d.seekable {
+                d.move_to(duration_to_ms(time), pending);
+            }
+        })
+    }
+
+    fn get_position(&self) -> Option<f32> {
+        let d = lock(&self.data);
+        if d.is_active() { d.position() } else { None }
+    }
+
+    fn set_position(&self, pos: f32) {
+        self.update(|d, pending| {
+            if let Some(len) = d.length() {
+                if d.is_active() && d.seekable {
+                    d.move_to((pos as f64 * len as f64) as i64, pending);
+                }
+            }
+        })
+    }
+
+    fn get_rate(&self) -> f32 {
+        lock(&self.data).rate
+    }
+
+    fn set_rate(&self, rate: f32) -> Result<(), ()> {
+        if rate > 0.0 {
+            lock(&self.data).rate = rate;
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    fn state(&self) -> State {
+        lock(&self.data).state
+    }
+
+    fn is_seekable(&self) -> bool {
+        let d = lock(&self.data);
+        d.is_active() && d.seekable
+    }
+
+    fn can_pause(&self) -> bool {
+        let d = lock(&self.data);
+        d.is_active() && d.pausable
+    }
+}
+
+/// Volume and mute are only recorded, setting the volume emits MediaPlayerAudioVolume. The mock has no audio tracks.
+impl MediaPlayerAudioEx for MockMediaPlayer {
+    fn get_mute(&self) -> Option<bool> {
+        Some(lock(&self.data).muted)
+    }
+
+    fn set_mute(&self, muted: bool) {
+        lock(&self.data).muted = muted;
+    }
+
+    fn get_volume(&self) -> i32 {
+        lock(&self.data).volume
+    }
+
+    fn set_volume(&self, volume: i32) -> Result<(), ()> {
+        if !(0..=200).contains(&volume) {
+            return Err(());
+        }
+        self.update(|d, pending| {
+            d.volume = volume;
+            d.emit(pending, Event::MediaPlayerAudioVolume(volume as f32 / 100.0));
+        });
+        Ok(())
+    }
+
+    fn get_audio_track_description(&self) -> Option<Vec<TrackDescription>> {
+        None
+    }
+
+    fn get_audio_track(&self) -> Option<i32> {
+        None
+    }
+
+    fn set_audio_track(&self, _track: i32) {}
+}
+
+#[derive(Default)]
+struct ListData {
+    items: Vec<MockMedia>,
+    readonly: bool,
+}
+
+/// A fake media list. Clones share their items.
+#[derive(Clone, Default)]
+pub struct MockMediaList {
+    data: Arc<Mutex<ListData>>,
+    events: MockEventManager,
+}
+
+impl MockMediaList {
+    pub fn new() -> MockMediaList {
+        Default::default()
+    }
+
+    /// Make the list refuse additions and removals.
+    pub fn set_readonly(&self, readonly: bool) {
+        lock(&self.data).readonly = readonly;
+    }
+}
+
+impl MediaListApi for MockMediaList {
+    type Media = MockMedia;
+    type EventManager<'a> = MockEventManager;
+
+    fn add_media(&self, md: &MockMedia) -> Result<(), ()> {
+        let count = self.count();
+        self.insert_media(md, count)
+    }
+
+    fn insert_media(&self, md: &MockMedia, pos: i32) -> Result<(), ()> {
+        {
+            let d = lock(&self.data);
+            if d.readonly || pos < 0 || pos as usize > d.items.len() {
+                return Err(());
+            }
+        }
+        self.events.emit(Event::MediaListWillAddItem);
+        lock(&self.data).items.insert(pos as usize, md.clone());
+        self.events.emit(Event::MediaListItemAdded);
+        Ok(())
+    }
+
+    fn remove_index(&self, pos: i32) -> Result<(), ()> {
+        {
+            let d = lock(&self.data);
+            if d.readonly || pos < 0 || pos as usize >= d.items.len() {
+                return Err(());
+            }
+        }
+        self.events.emit(Event::MediaListWillDeleteItem);
+        lock(&self.data).items.remove(pos as usize);
+        self.events.emit(Event::MediaListItemDeleted);
+        Ok(())
+    }
+
+    fn count(&self) -> i32 {
+        lock(&self.data).items.len() as i32
+    }
+
+    fn item_at_index(&self, pos: i32) -> Option<MockMedia> {
+        if pos < 0 {
+            return None;
+        }
+        lock(&self.data).items.get(pos as usize).cloned()
+    }
+
+    fn index_of_item(&self, md: &MockMedia) -> Option<i32> {
+        lock(&self.data).items.iter().position(|item| item == md).map(|i| i as i32)
+    }
+
+    fn is_readonly(&self) -> bool {
+        lock(&self.data).readonly
+    }
+
+    fn event_manager(&self) -> MockEventManager {
+        self.events.clone()
+    }
+}
+
+struct ListPlayerData {
+    list: Option<MockMediaList>,
+    index: Option<i32>,
+    mode: PlaybackMode,
+}
+
+impl ListPlayerData {
+    // The item `offset` away from the current one, wrapping around in loop mode.
+    fn relative_index(&self, offset: i32) -> Option<i32> {
+        let count = self.list.as_ref().map_or(0, |list| list.count());
+        if count == 0 {
+            return None;
+        }
+        let index = match self.index {
+            Some(index) => index + offset,
+            None => 0,
+        };
+        if (0..count).contains(&index) {
+            Some(index)
+        } else if self.mode == PlaybackMode::Loop {
+            Some(index.rem_euclid(count))
+        } else {
+            None
+        }
+    }
+}
+
+/// A fake media list player, driving its own [MockMediaPlayer].
+#[derive(Clone)]
+pub struct MockMediaListPlayer {
+    data: Arc<Mutex<ListPlayerData>>,
+    player: MockMediaPlayer,
+    events: MockEventManager,
+}
+
+impl MockMediaListPlayer {
+    pub fn new() -> MockMediaListPlayer {
+        MockMediaListPlayer {
+            data: Arc::new(Mutex::new(ListPlayerData {
+                list: None,
+                index: None,
+                mode: PlaybackMode::Default,
+            })),
+            player: MockMediaPlayer::new(),
+            events: MockEventManager::new(),
+        }
+    }
+
+    /// The event manager for the `MediaListPlayer*` events.
+    pub fn event_manager(&self) -> MockEventManager {
+        self.events.clone()
+    }
+
+    /// Index of the current item in the list.
+    pub fn current_index(&self) -> Option<i32> {
+        lock(&self.data).index
+    }
+
+    /// Let `elapsed` pass on the player, see [MockMediaPlayer::advance].
+    /// When the current item ends, the next one is started according to the playback mode.
+    pub fn advance(&self, elapsed: Duration) {
+        self.player.advance(elapsed);
+        if !self.player.take_reached_end() {
+            return;
+        }
+
+        let next = {
+            let d = lock(&self.data);
+            match d.mode {
+                PlaybackMode::Repeat => d.index,
+                _ => d.relative_index(1),
+            }
+        };
+        match next {
+            Some(index) => { let _ = self.play_item_at_index(index); },
+            None => self.events.emit(Event::MediaListPlayerPlayed),
+        }
+    }
+
+}
+
+impl Default for MockMediaListPlayer {
+    fn default() -> MockMediaListPlayer {
+        MockMediaListPlayer::new()
+    }
+}
+
+impl MediaListPlayerApi for MockMediaListPlayer {
+    type MediaPlayer = MockMediaPlayer;
+    type MediaList = MockMediaList;
+
+    fn get_media_player(&self) -> Option<MockMediaPlayer> {
+        Some(self.player.clone())
+    }
+
+    fn set_media_list(&self, media_list: &MockMediaList) {
+        let mut d = lock(&self.data);
+        d.list = Some(media_list.clone());
+        d.index = None;
+    }
+
+    fn play(&self) {
+        if self.player.state() == State::Paused {
+            let _ = self.player.play();
+            return;
+        }
+        let index = lock(&self.data).index.unwrap_or(0);
+        let _ = self.play_item_at_index(index);
+    }
+
+    fn set_pause(&self, pause: bool) {
+        self.player.set_pause(pause);
+    }
+
+    fn is_playing(&self) -> bool {
+        self.player.is_playing()
+    }
+
+    fn play_item_at_index(&self, index: i32) -> Result<(), ()> {
+        let md = {
+            let mut d = lock(&self.data);
+            let md = d.list.as_ref().and_then(|list| list.item_at_index(index)).ok_or(())?;
+            d.index = Some(index);
+            md
+        };
+        self.player.set_media(&md);
+        self.events.emit(Event::MediaListPlayerNextItemSet);
+        self.player.play()
+    }
+
+    fn stop(&self) {
+        self.player.stop();
+        self.events.emit(Event::MediaListPlayerStopped);
+    }
+
+    fn next(&self) -> Result<(), ()> {
+        let index = lock(&self.data).relative_index(1);
+        self.play_item_at_index(index.ok_or(())?)
+    }
+
+    fn previous(&self) -> Result<(), ()> {
+        let index = lock(&self.data).relative_index(-1);
+        self.play_item_at_index(index.ok_or(())?)
+    }
+
+    fn set_playback_mode(&self, mode: PlaybackMode) {
+        lock(&self.data).mode = mode;
+    }
+}
@@ -0,0 +1,843 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+//! Remote control over the [MPRIS](https://specifications.freedesktop.org/mpris-spec/latest/) D-Bus interface,
+//! so that desktop media keys, applets and `playerctl` drive a [MediaPlayer].
+//!
+//! ```no_run
+//! use vlc::{Instance, Media, MediaList, MediaListPlayer, MediaListPlayerApi};
+//! use vlc::mpris::Mpris;
+//!
+//! let instance = Instance::new().unwrap();
+//! let list = MediaList::new(&instance).unwrap();
+//! let list_player = MediaListPlayer::new(&instance).unwrap();
+//! list_player.set_media_list(&list);
+//! let player = list_player.get_media_player().unwrap();
+//! let _server = Mpris::new("my_player", &player)
+//!     .with_identity("My Player")
+//!     .with_track_list(&list_player, &list)
+//!     .with_opener(move |uri| Media::new_location(&instance, uri))
+//!     .start()
+//!     .unwrap();
+//! ```
+
+use std::collections::HashMap;
+use std::convert::{TryFrom, TryInto};
+use std::mem;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use zbus::blocking::connection::Builder;
+use zbus::blocking::object_server::InterfaceRef;
+use zbus::blocking::Connection;
+use zbus::fdo;
+use zbus::interface;
+use zbus::object_server::SignalEmitter;
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
+
+use crate::{EventManagerApi, EventType, MediaApi, MediaListApi, MediaListPlayerApi, MediaPlayer, MediaPlayerApi};
+use crate::{MediaPlayerAudioEx, Meta, PlaybackMode, State};
+
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+const TRACK_PREFIX: &str = "/org/videolan/vlc_rs/track/";
+const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+const MIN_RATE: f64 = 0.25;
+const MAX_RATE: f64 = 4.0;
+
+type Metadata = HashMap<String, OwnedValue>;
+type Opener<M> = Arc<dyn Fn(&str) -> Option<M> + Send + Sync>;
+
+enum Message {
+    Status,
+    Track,
+    Tracks,
+    Volume,
+    Quit,
+}
+
+// The list side, behind a trait object so that the interfaces are only generic over the player.
+trait TrackSource<M>: Send + Sync {
+    /// The items of the list with their track ids, in list order.
+    fn entries(&self) -> Vec<(M, u64)>;
+    /// The id of the first entry of `md`.
+    fn id_of(&self, md: &M) -> Option<u64>;
+    fn insert(&self, md: &M, index: i32) -> Result<(), ()>;
+    fn remove(&self, index: i32) -> Result<(), ()>;
+    fn is_readonly(&self) -> bool;
+    fn play(&self);
+    fn stop(&self);
+    fn play_index(&self, index: i32) -> Result<(), ()>;
+    fn next(&self) -> Result<(), ()>;
+    fn previous(&self) -> Result<(), ()>;
+    fn set_mode(&self, mode: PlaybackMode);
+    /// Forward the changes of the list to `tx` while `run` runs.
+    fn watch(&self, tx: &Sender<Message>, run: &mut dyn FnMut());
+}
+
+// The track ids of the list entries, which they keep while other entries are added or removed.
+struct TrackIds<M> {
+    entries: Vec<(M, u64)>,
+    next: u64,
+}
+
+impl<M: Clone + PartialEq> TrackIds<M> {
+    // Match `items` with the entries as they were: an item keeps the id of the next entry of the
+    // same media, the entries skipped over were removed, and items without an entry are new.
+    fn update(&mut self, items: Vec<M>) -> Vec<(M, u64)> {
+        let old = mem::take(&mut self.entries);
+        let mut from = 0;
+        for md in items {
+            let id = match old[from..].iter().position(|(m, _)| *m == md) {
+                Some(i) => {
+                    from += i + 1;
+                    old[from - 1].1
+                },
+                None => {
+                    self.next += 1;
+                    self.next - 1
+                },
+            };
+            self.entries.push((md, id));
+        }
+        self.entries.clone()
+    }
+}
+
+struct ListSource<LP: MediaListPlayerApi> {
+    player: LP,
+    list: LP::MediaList,
+    ids: Mutex<TrackIds<<LP::MediaList as MediaListApi>::Media>>,
+}
+
+impl<LP, L> TrackSource<L::Media> for ListSource<LP>
+    where LP: MediaListPlayerApi<MediaList = L> + Send + Sync, L: MediaListApi + Send + Sync, L::Media: PartialEq + Send
+{
+    fn entries(&self) -> Vec<(L::Media, u64)> {
+        let items = (0..self.list.count()).filter_map(|i| self.list.item_at_index(i)).collect();
+        self.ids.lock().unwrap_or_else(|e| e.into_inner()).update(items)
+    }
+
+    fn id_of(&self, md: &L::Media) -> Option<u64> {
+        self.entries().into_iter().find(|(m, _)| m == md).map(|(_, id)| id)
+    }
+
+    fn insert(&self, md: &L::Media, index: i32) -> Result<(), ()> {
+        self.list.insert_media(md, index)
+    }
+
+    fn remove(&self, index: i32) -> Result<(), ()> {
+        self.list.remove_index(index)
+    }
+
+    fn is_readonly(&self) -> bool {
+        self.list.is_readonly()
+    }
+
+    fn play(&self) {
+        self.player.play();
+    }
+
+    fn stop(&self) {
+        self.player.stop();
+    }
+
+    fn play_index(&self, index: i32) -> Result<(), ()> {
+        self.player.play_item_at_index(index)
+    }
+
+    fn next(&self) -> Result<(), ()> {
+        self.player.next()
+    }
+
+    fn previous(&self) -> Result<(), ()> {
+        self.player.previous()
+    }
+
+    fn set_mode(&self, mode: PlaybackMode) {
+        self.player.set_playback_mode(mode);
+    }
+
+    fn watch(&self, tx: &Sender<Message>, run: &mut dyn FnMut()) {
+        let em = self.list.event_manager();
+        let mut attached = Vec::new();
+        for &event_type in &[EventType::MediaListItemAdded, EventType::MediaListItemDeleted] {
+            let tx = tx.clone();
+            let handle = em.attach(event_type, move |_, _| {
+                let _ = tx.send(Message::Tracks);
+            });
+            attached.push((event_type, handle));
+        }
+        run();
+        for (event_type, handle) in attached {
+            if let Ok(handle) = handle {
+                em.detach(event_type, handle);
+            }
+        }
+    }
+}
+
+struct Shared<P: MediaPlayerApi> {
+    player: P,
+    tracks: Option<Arc<dyn TrackSource<P::Media>>>,
+    opener: Option<Opener<P::Media>>,
+    // libvlc has no getter for the playback mode of a list player.
+    mode: Mutex<PlaybackMode>,
+}
+
+impl<P: MediaPlayerApi> Shared<P> {
+    fn track_id(&self, md: &P::Media) -> OwnedObjectPath {
+        match self.tracks.as_ref().and_then(|tracks| tracks.id_of(md)) {
+            Some(id) => track_path(id),
+            None => object_path(&format!("{}current", TRACK_PREFIX)),
+        }
+    }
+
+    fn current_id(&self) -> OwnedObjectPath {
+        match self.player.get_media() {
+            Some(md) => self.track_id(&md),
+            None => object_path(NO_TRACK),
+        }
+    }
+
+    fn metadata(&self, md: &P::Media) -> Metadata {
+        let mut metadata = Metadata::new();
+        insert(&mut metadata, "mpris:trackid", Value::from(ObjectPath::from(self.track_id(md))));
+        if let Some(length) = md.length() {
+            insert(&mut metadata, "mpris:length", Value::from(length.as_micros() as i64));
+        }
+        if let Some(title) = md.get_meta(Meta::Title) {
+            insert(&mut metadata, "xesam:title", Value::from(title));
+        }
+        if let Some(artist) = md.get_meta(Meta::Artist) {
+            insert(&mut metadata, "xesam:artist", Value::from(vec![artist]));
+        }
+        if let Some(album) = md.get_meta(Meta::Album) {
+            insert(&mut metadata, "xesam:album", Value::from(album));
+        }
+        if let Some(art) = md.get_meta(Meta::ArtworkURL) {
+            insert(&mut metadata, "mpris:artUrl", Value::from(art));
+        }
+        if let Some(mrl) = md.mrl() {
+            insert(&mut metadata, "xesam:url", Value::from(mrl));
+        }
+        metadata
+    }
+
+    fn current_metadata(&self) -> Metadata {
+        match self.player.get_media() {
+            Some(md) => self.metadata(&md),
+            None => {
+                let mut metadata = Metadata::new();
+                insert(&mut metadata, "mpris:trackid", Value::from(ObjectPath::from(object_path(NO_TRACK))));
+                metadata
+            },
+        }
+    }
+
+    // The index in the list of the track `path` names.
+    fn track_index(&self, path: &ObjectPath<'_>) -> Option<i32> {
+        let id: u64 = path.as_str().strip_prefix(TRACK_PREFIX)?.parse().ok()?;
+        let entries = self.tracks.as_ref()?.entries();
+        entries.iter().position(|&(_, entry)| entry == id).map(|i| i as i32)
+    }
+
+    fn length(&self) -> Option<Duration> {
+        self.player.get_media().and_then(|md| md.length())
+    }
+}
+
+fn object_path(path: &str) -> OwnedObjectPath {
+    OwnedObjectPath::try_from(path).expect("valid object path")
+}
+
+fn track_path(id: u64) -> OwnedObjectPath {
+    object_path(&format!("{}{}", TRACK_PREFIX, id))
+}
+
+fn insert(metadata: &mut Metadata, key: &str, value: Value<'_>) {
+    if let Ok(value) = value.try_into() {
+        metadata.insert(key.to_owned(), value);
+    }
+}
+
+fn playback_status(state: State) -> &'static str {
+    match state {
+        State::Opening | State::Buffering | State::Playing => "Playing",
+        State::Paused => "Paused",
+        _ => "Stopped",
+    }
+}
+
+struct RootIface {
+    identity: String,
+    has_track_list: bool,
+    can_open: bool,
+}
+
+#[interface(name = "org.mpris.MediaPlayer2")]
+impl RootIface {
+    fn raise(&self) {}
+
+    fn quit(&self) {}
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn can_quit(&self) -> bool {
+        false
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn can_raise(&self) -> bool {
+        false
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn has_track_list(&self) -> bool {
+        self.has_track_list
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn identity(&self) -> String {
+        self.identity.clone()
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn supported_uri_schemes(&self) -> Vec<String> {
+        if !self.can_open {
+            return Vec::new();
+        }
+        ["file", "http", "https", "rtsp", "rtp", "udp", "mms", "ftp", "smb", "sftp", "dvd", "cdda"]
+            .iter().map(|s| s.to_string()).collect()
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn supported_mime_types(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+struct PlayerIface<P: MediaPlayerApi> {
+    shared: Arc<Shared<P>>,
+}
+
+#[interface(name = "org.mpris.MediaPlayer2.Player")]
+impl<P> PlayerIface<P>
+    where P: MediaPlayerApi + MediaPlayerAudioEx + Send + Sync + 'static
+{
+    fn next(&self) {
+        if let Some(tracks) = &self.shared.tracks {
+            let _ = tracks.next();
+        }
+    }
+
+    fn previous(&self) {
+        if let Some(tracks) = &self.shared.tracks {
+            let _ = tracks.previous();
+        }
+    }
+
+    fn pause(&self) {
+        if self.shared.player.state() == State::Playing {
+            self.shared.player.set_pause(true);
+        }
+    }
+
+    fn play_pause(&self) {
+        match self.shared.player.state() {
+            State::Playing => self.shared.player.set_pause(true),
+            _ => self.play(),
+        }
+    }
+
+    fn stop(&self) {
+        match &self.shared.tracks {
+            Some(tracks) => tracks.stop(),
+            None => self.shared.player.stop(),
+        }
+    }
+
+    fn play(&self) {
+        match &self.shared.tracks {
+            Some(tracks) if self.shared.player.state() != State::Paused => tracks.play(),
+            _ => { let _ = self.shared.player.play(); },
+        }
+    }
+
+    /// Seek by `offset` microseconds, or go to the next track past the end.
+    async fn seek(&self, offset: i64, #[zbus(signal_emitter)] emitter: SignalEmitter<'_>) -> fdo::Result<()> {
+        let Some(time) = self.shared.player.time() else { return Ok(()) };
+        let target = (time.as_micros() as i64).saturating_add(offset).max(0);
+        if self.shared.length().is_some_and(|length| target > length.as_micros() as i64) {
+            self.next();
+            return Ok(());
+        }
+        self.shared.player.seek(Duration::from_micros(target as u64));
+        PlayerIface::<P>::seeked(&emitter, target).await?;
+        Ok(())
+    }
+
+    /// Seek to `position` microseconds, if `track_id` is still the current track.
+    async fn set_position(
+        &self,
+        track_id: ObjectPath<'_>,
+        position: i64,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        let current = self.shared.current_id();
+        let in_range = position >= 0 && self.shared.length().map_or(true, |length| position <= length.as_micros() as i64);
+        if track_id != *current || !in_range {
+            return Ok(());
+        }
+        self.shared.player.seek(Duration::from_micros(position as u64));
+        PlayerIface::<P>::seeked(&emitter, position).await?;
+        Ok(())
+    }
+
+    fn open_uri(&self, uri: &str) -> fdo::Result<()> {
+        let opener = self.shared.opener.as_ref().ok_or_else(|| fdo::Error::NotSupported("OpenUri".into()))?;
+        let md = opener(uri).ok_or_else(|| fdo::Error::InvalidArgs(format!("cannot open {}", uri)))?;
+        self.shared.player.set_media(&md);
+        self.shared.player.play().map_err(|_| fdo::Error::Failed(format!("cannot play {}", uri)))
+    }
+
+    #[zbus(signal)]
+    async fn seeked(emitter: &SignalEmitter<'_>, position: i64) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn playback_status(&self) -> String {
+        playback_status(self.shared.player.state()).to_owned()
+    }
+
+    #[zbus(property)]
+    fn loop_status(&self) -> String {
+        match *self.shared.mode.lock().unwrap_or_else(|e| e.into_inner()) {
+            PlaybackMode::Repeat => "Track",
+            PlaybackMode::Loop => "Playlist",
+            _ => "None",
+        }.to_owned()
+    }
+
+    #[zbus(property)]
+    fn set_loop_status(&self, status: String) -> zbus::Result<()> {
+        let tracks = self.shared.tracks.as_ref()
+            .ok_or_else(|| fdo::Error::NotSupported("LoopStatus without a track list".into()))?;
+        let mode = match status.as_str() {
+            "None" => PlaybackMode::Default,
+            "Track" => PlaybackMode::Repeat,
+            "Playlist" => PlaybackMode::Loop,
+            _ => return Err(fdo::Error::InvalidArgs(format!("unknown loop status {}", status)).into()),
+        };
+        tracks.set_mode(mode);
+        *self.shared.mode.lock().unwrap_or_else(|e| e.into_inner()) = mode;
+        Ok(())
+    }
+
+    /// libvlc does not report rate changes.
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn rate(&self) -> f64 {
+        self.shared.player.get_rate() as f64
+    }
+
+    /// A rate of 0 pauses, as MPRIS asks.
+    #[zbus(property)]
+    fn set_rate(&self, rate: f64) -> zbus::Result<()> {
+        if rate <= 0.0 {
+            self.pause();
+            return Ok(());
+        }
+        self.shared.player.set_rate(rate.clamp(MIN_RATE, MAX_RATE) as f32)
+            .map_err(|_| fdo::Error::Failed("cannot set the rate".into()).into())
+    }
+
+    #[zbus(property)]
+    fn metadata(&self) -> Metadata {
+        self.shared.current_metadata()
+    }
+
+    /// 1.0 is libvlc's volume 100.
+    #[zbus(property)]
+    fn volume(&self) -> f64 {
+        self.shared.player.get_volume().max(0) as f64 / 100.0
+    }
+
+    #[zbus(property)]
+    fn set_volume(&self, volume: f64) -> zbus::Result<()> {
+        self.shared.player.set_volume((volume.clamp(0.0, 2.0) * 100.0).round() as i32)
+            .map_err(|_| fdo::Error::Failed("cannot set the volume".into()).into())
+    }
+
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn position(&self) -> i64 {
+        self.shared.player.time().map_or(0, |t| t.as_micros() as i64)
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn minimum_rate(&self) -> f64 {
+        MIN_RATE
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn maximum_rate(&self) -> f64 {
+        MAX_RATE
+    }
+
+    #[zbus(property)]
+    fn can_go_next(&self) -> bool {
+        self.shared.tracks.is_some()
+    }
+
+    #[zbus(property)]
+    fn can_go_previous(&self) -> bool {
+        self.shared.tracks.is_some()
+    }
+
+    #[zbus(property)]
+    fn can_play(&self) -> bool {
+        self.shared.tracks.is_some() || self.shared.player.get_media().is_some()
+    }
+
+    #[zbus(property)]
+    fn can_pause(&self) -> bool {
+        self.shared.player.can_pause()
+    }
+
+    #[zbus(property)]
+    fn can_seek(&self) -> bool {
+        self.shared.player.is_seekable()
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn can_control(&self) -> bool {
+        true
+    }
+}
+
+struct TrackListIface<P: MediaPlayerApi> {
+    shared: Arc<Shared<P>>,
+}
+
+impl<P: MediaPlayerApi> TrackListIface<P> {
+    fn source(&self) -> &dyn TrackSource<P::Media> {
+        self.shared.tracks.as_deref().expect("the track list is only served with a list")
+    }
+
+    fn ids(&self) -> Vec<OwnedObjectPath> {
+        self.source().entries().into_iter().map(|(_, id)| track_path(id)).collect()
+    }
+
+    fn index(&self, id: &ObjectPath<'_>) -> fdo::Result<i32> {
+        self.shared.track_index(id).ok_or_else(|| fdo::Error::InvalidArgs(format!("unknown track {}", id)))
+    }
+}
+
+/// Each entry of the list keeps its track id until it is removed, and every change of the list
+/// replaces the track list.
+#[interface(name = "org.mpris.MediaPlayer2.TrackList")]
+impl<P> TrackListIface<P>
+    where P: MediaPlayerApi + Send + Sync + 'static
+{
+    fn get_tracks_metadata(&self, track_ids: Vec<ObjectPath<'_>>) -> Vec<Metadata> {
+        let entries = self.source().entries();
+        track_ids.iter()
+            .filter_map(|path| {
+                let id: u64 = path.as_str().strip_prefix(TRACK_PREFIX)?.parse().ok()?;
+                entries.iter().find(|&&(_, entry)| entry == id)
+            })
+            .map(|(md, _)| self.shared.metadata(md))
+            .collect()
+    }
+
+    /// Add `uri` after `after_track`, or first for the NoTrack id.
+    fn add_track(&self, uri: &str, after_track: ObjectPath<'_>, set_as_current: bool) -> fdo::Result<()> {
+        if !self.can_edit_tracks() {
+            return Err(fdo::Error::NotSupported("the track list is read only".into()));
+        }
+        let index = if after_track.as_str() == NO_TRACK { 0 } else { self.index(&after_track)? + 1 };
+        let opener = self.shared.opener.as_ref().expect("can_edit_tracks checks for an opener");
+        let md = opener(uri).ok_or_else(|| fdo::Error::InvalidArgs(format!("cannot open {}", uri)))?;
+        self.source().insert(&md, index).map_err(|_| fdo::Error::Failed(format!("cannot add {}", uri)))?;
+        if set_as_current {
+            self.source().play_index(index).map_err(|_| fdo::Error::Failed(format!("cannot play {}", uri)))?;
+        }
+        Ok(())
+    }
+
+    fn remove_track(&self, track_id: ObjectPath<'_>) -> fdo::Result<()> {
+        if !self.can_edit_tracks() {
+            return Err(fdo::Error::NotSupported("the track list is read only".into()));
+        }
+        let index = self.index(&track_id)?;
+        self.source().remove(index).map_err(|_| fdo::Error::Failed(format!("cannot remove {}", track_id)))
+    }
+
+    fn go_to(&self, track_id: ObjectPath<'_>) -> fdo::Result<()> {
+        let index = self.index(&track_id)?;
+        self.source().play_index(index).map_err(|_| fdo::Error::Failed(format!("cannot play {}", track_id)))
+    }
+
+    #[zbus(signal)]
+    async fn track_list_replaced(
+        emitter: &SignalEmitter<'_>,
+        tracks: Vec<OwnedObjectPath>,
+        current_track: OwnedObjectPath,
+    ) -> zbus::Result<()>;
+
+    #[zbus(property(emits_changed_signal = "invalidates"))]
+    fn tracks(&self) -> Vec<OwnedObjectPath> {
+        self.ids()
+    }
+
+    #[zbus(property)]
+    fn can_edit_tracks(&self) -> bool {
+        self.shared.opener.is_some() && !self.source().is_readonly()
+    }
+}
+
+/// Exports a player over MPRIS, see the [module](self) documentation.
+///
+/// The player is served as `org.mpris.MediaPlayer2.<name>`. With [with_track_list](Mpris::with_track_list),
+/// Next and Previous drive the list player and the list is exported as the TrackList interface.
+/// Opening URIs needs [with_opener](Mpris::with_opener).
+pub struct Mpris<P: MediaPlayerApi = MediaPlayer> {
+    name: String,
+    identity: String,
+    player: P,
+    tracks: Option<Arc<dyn TrackSource<P::Media>>>,
+    opener: Option<Opener<P::Media>>,
+}
+
+impl<P> Mpris<P>
+    where P: MediaPlayerApi + MediaPlayerAudioEx + Clone + Send + Sync + 'static
+{
+    /// Export `player` as `org.mpris.MediaPlayer2.<name>`. `name` may only contain letters, digits and `_`.
+    pub fn new(name: &str, player: &P) -> Mpris<P> {
+        Mpris {
+            name: name.to_owned(),
+            identity: name.to_owned(),
+            player: player.clone(),
+            tracks: None,
+            opener: None,
+        }
+    }
+
+    /// The name shown to the user, `name` by default.
+    pub fn with_identity(mut self, identity: &str) -> Mpris<P> {
+        self.identity = identity.to_owned();
+        self
+    }
+
+    /// Drive `list_player`, whose media player must be the exported one, and export `list`.
+    pub fn with_track_list<LP, L>(mut self, list_player: &LP, list: &L) -> Mpris<P>
+        where LP: MediaListPlayerApi<MediaList = L> + Clone + Send + Sync + 'static,
+              L: MediaListApi<Media = P::Media> + Clone + Send + Sync + 'static,
+              P::Media: PartialEq + Send + 'static
+    {
+        list_player.set_media_list(list);
+        let ids = Mutex::new(TrackIds { entries: Vec::new(), next: 0 });
+        self.tracks = Some(Arc::new(ListSource { player: list_player.clone(), list: list.clone(), ids }));
+        self
+    }
+
+    /// Make media for the OpenUri and AddTrack calls, e.g. with `Media::new_location`.
+    pub fn with_opener<F>(mut self, opener: F) -> Mpris<P>
+        where F: Fn(&str) -> Option<P::Media> + Send + Sync + 'static
+    {
+        self.opener = Some(Arc::new(opener));
+        self
+    }
+
+    /// Serve on the session bus.
+    pub fn start(self) -> zbus::Result<MprisServer> {
+        let builder = Builder::session()?;
+        self.serve(builder)
+    }
+
+    /// Serve on the bus at `address`, e.g. a private `dbus-daemon`.
+    pub fn start_at(self, address: &str) -> zbus::Result<MprisServer> {
+        let builder = Builder::address(address)?;
+        self.serve(builder)
+    }
+
+    fn serve(self, builder: Builder<'_>) -> zbus::Result<MprisServer> {
+        let bus_name = format!("org.mpris.MediaPlayer2.{}", self.name);
+        let shared = Arc::new(Shared {
+            player: self.player,
+            tracks: self.tracks,
+            opener: self.opener,
+            mode: Mutex::new(PlaybackMode::Default),
+        });
+        let root = RootIface {
+            identity: self.identity,
+            has_track_list: shared.tracks.is_some(),
+            can_open: shared.opener.is_some(),
+        };
+
+        let mut builder = builder
+            .serve_at(OBJECT_PATH, root)?
+            .serve_at(OBJECT_PATH, PlayerIface { shared: shared.clone() })?;
+        if shared.tracks.is_some() {
+            builder = builder.serve_at(OBJECT_PATH, TrackListIface { shared: shared.clone() })?;
+        }
+        let connection = builder.name(bus_name.clone())?.build()?;
+
+        let player_iface = connection.object_server().interface::<_, PlayerIface<P>>(OBJECT_PATH)?;
+        let track_list_iface = match shared.tracks {
+            Some(_) => Some(connection.object_server().interface::<_, TrackListIface<P>>(OBJECT_PATH)?),
+            None => None,
+        };
+
+        let (tx, rx) = channel();
+        let (ready_tx, ready_rx) = channel();
+        let event_tx = tx.clone();
+        let worker_shared = shared.clone();
+        // The properties call into libvlc, which must not be done from its callbacks.
+        // The callbacks are attached and detached on the worker.
+        let worker = thread::spawn(move || {
+            let mut status = String::new();
+            let mut metadata = Metadata::new();
+            let mut volume = player_iface.get().volume();
+            let mut serve = || {
+                let _ = ready_tx.send(());
+                loop {
+                    match rx.recv() {
+                        Ok(Message::Status) => {
+                            let now = player_iface.get().playback_status();
+                            if now != status {
+                                status = now;
+                                emit(&player_iface, |iface, emitter| zbus::block_on(iface.playback_status_changed(emitter)));
+                                emit(&player_iface, |iface, emitter| zbus::block_on(iface.can_seek_changed(emitter)));
+                                emit(&player_iface, |iface, emitter| zbus::block_on(iface.can_pause_changed(emitter)));
+                            }
+                        },
+                        Ok(Message::Track) => {
+                            let now = player_iface.get().metadata();
+                            if now != metadata {
+                                metadata = now;
+                                emit(&player_iface, |iface, emitter| zbus::block_on(iface.metadata_changed(emitter)));
+                                emit(&player_iface, |iface, emitter| zbus::block_on(iface.can_play_changed(emitter)));
+                            }
+                        },
+                        Ok(Message::Tracks) => {
+                            if let Some(iface) = &track_list_iface {
+                                let (tracks, current) = {
+                                    let track_list = iface.get();
+                                    (track_list.ids(), track_list.shared.current_id())
+                                };
+                                let emitter = iface.signal_emitter();
+                                let _ = zbus::block_on(TrackListIface::<P>::track_list_replaced(emitter, tracks, current));
+                                let _ = zbus::block_on(iface.get().tracks_invalidate(emitter));
+                            }
+                            // The position of the current track may have changed with its id.
+                            let now = player_iface.get().metadata();
+                            if now != metadata {
+                                metadata = now;
+                                emit(&player_iface, |iface, emitter| zbus::block_on(iface.metadata_changed(emitter)));
+                            }
+                        },
+                        Ok(Message::Volume) => {
+                            let now = player_iface.get().volume();
+                            if now != volume {
+                                volume = now;
+                                emit(&player_iface, |iface, emitter| zbus::block_on(iface.volume_changed(emitter)));
+                            }
+                        },
+                        _ => break,
+                    }
+                }
+            };
+            watch(&worker_shared.player, &event_tx, || match &worker_shared.tracks {
+                Some(tracks) => tracks.watch(&event_tx, &mut serve),
+                None => serve(),
+            });
+        });
+        let _ = ready_rx.recv();
+
+        Ok(MprisServer { connection, bus_name, tx, worker: Some(worker) })
+    }
+}
+
+fn emit<P, F>(iface: &InterfaceRef<PlayerIface<P>>, f: F)
+    where P: MediaPlayerApi + MediaPlayerAudioEx + Send + Sync + 'static,
+          F: FnOnce(&PlayerIface<P>, &SignalEmitter<'static>) -> zbus::Result<()>
+{
+    let _ = f(&iface.get(), iface.signal_emitter());
+}
+
+// Forward the player events to the worker while `run` runs.
+fn watch<P: MediaPlayerApi, F: FnOnce()>(player: &P, tx: &Sender<Message>, run: F) {
+    let em = player.event_manager();
+    let status = [
+        EventType::MediaPlayerOpening,
+        EventType::MediaPlayerPlaying,
+        EventType::MediaPlayerPaused,
+        EventType::MediaPlayerStopped,
+        #[cfg(not(feature = "libvlc4"))]
+        EventType::MediaPlayerEndReached,
+        #[cfg(feature = "libvlc4")]
+        EventType::MediaPlayerStopping,
+        EventType::MediaPlayerEncounteredError,
+    ];
+    let mut attached = Vec::new();
+    for &event_type in &status {
+        let tx = tx.clone();
+        let handle = em.attach(event_type, move |_, _| {
+            let _ = tx.send(Message::Status);
+            // Meta data is often only known once the media is parsed for playback.
+            let _ = tx.send(Message::Track);
+        });
+        attached.push((event_type, handle));
+    }
+    for &event_type in &[EventType::MediaPlayerMediaChanged, EventType::MediaPlayerLengthChanged] {
+        let tx = tx.clone();
+        let handle = em.attach(event_type, move |_, _| {
+            let _ = tx.send(Message::Track);
+        });
+        attached.push((event_type, handle));
+    }
+    let volume_tx = tx.clone();
+    let handle = em.attach(EventType::MediaPlayerAudioVolume, move |_, _| {
+        let _ = volume_tx.send(Message::Volume);
+    });
+    attached.push((EventType::MediaPlayerAudioVolume, handle));
+
+    run();
+    for (event_type, handle) in attached {
+        if let Ok(handle) = handle {
+            em.detach(event_type, handle);
+        }
+    }
+}
+
+/// A player exported over MPRIS, started with [Mpris]. The bus name is released when dropped.
+pub struct MprisServer {
+    connection: Connection,
+    bus_name: String,
+    tx: Sender<Message>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl MprisServer {
+    /// The well-known name, `org.mpris.MediaPlayer2.<name>`.
+    pub fn bus_name(&self) -> &str {
+        &self.bus_name
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Stop serving and release the bus name.
+    pub fn stop(self) {}
+}
+
+impl Drop for MprisServer {
+    fn drop(&mut self) {
+        let _ = self.tx.send(Message::Quit);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        let _ = self.connection.release_name(self.bus_name.as_str());
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::path::Path;
+use std::slice;
+
+use vlc_sys as sys;
+use crate::enums::PictureType;
+use crate::tools::path_to_cstr;
+use crate::{Instance, Media};
+
+/// A picture generated by libvlc, e.g. a thumbnail.
+pub struct Picture {
+    ptr: *mut sys::libvlc_picture_t,
+}
+
+// Pictures are immutable once generated.
+unsafe impl Send for Picture {}
+unsafe impl Sync for Picture {}
+
+impl Picture {
+    // Take a reference on a picture owned by libvlc.
+    pub(crate) unsafe fn retain(ptr: *mut sys::libvlc_picture_t) -> Option<Picture> {
+        if ptr.is_null() {
+            return None;
+        }
+        sys::libvlc_picture_retain(ptr);
+        Some(Picture{ptr})
+    }
+
+    /// Format of the picture data.
+    pub fn picture_type(&self) -> PictureType {
+        unsafe{ sys::libvlc_picture_type(self.ptr) }.into()
+    }
+
+    pub fn width(&self) -> u32 {
+        unsafe{ sys::libvlc_picture_get_width(self.ptr) }
+    }
+
+    pub fn height(&self) -> u32 {
+        unsafe{ sys::libvlc_picture_get_height(self.ptr) }
+    }
+
+    /// Line size in bytes. Only meaningful for [PictureType::Argb].
+    pub fn stride(&self) -> u32 {
+        unsafe{ sys::libvlc_picture_get_stride(self.ptr) }
+    }
+
+    /// Time in the media of the picture (in ms).
+    pub fn time(&self) -> i64 {
+        unsafe{ sys::libvlc_picture_get_time(self.ptr) }
+    }
+
+    /// Encoded picture data, or raw pixels for [PictureType::Argb].
+    pub fn buffer(&self) -> &[u8] {
+        unsafe{
+            let mut size = 0;
+            let p = sys::libvlc_picture_get_buffer(self.ptr, &mut size);
+            if p.is_null() { &[] }else{ slice::from_raw_parts(p, size) }
+        }
+    }
+
+    /// Save the picture to a file, in its own format.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ()> {
+        let cstr = path_to_cstr(path.as_ref()).map_err(|_| ())?;
+        if unsafe{ sys::libvlc_picture_save(self.ptr, cstr.as_ptr()) } == 0 { Ok(()) }else{ Err(()) }
+    }
+
+    /// Returns raw pointer
+    pub fn raw(&self) -> *mut sys::libvlc_picture_t {
+        self.ptr
+    }
+}
+
+impl Clone for Picture {
+    /// Retain the picture. The underlying picture is released when the last clone is dropped.
+    fn clone(&self) -> Picture {
+        unsafe{ sys::libvlc_picture_retain(self.ptr) };
+        Picture{ptr: self.ptr}
+    }
+}
+
+impl Drop for Picture {
+    fn drop(&mut self) {
+        unsafe{ sys::libvlc_picture_release(self.ptr) };
+    }
+}
+
+impl std::fmt::Debug for Picture {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Picture")
+            .field("picture_type", &self.picture_type())
+            .field("width", &self.width())
+            .field("height", &self.height())
+            .field("time", &self.time())
+            .finish()
+    }
+}
+
+/// Parameters of a thumbnail request.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ThumbnailOptions {
+    /// Width of the thumbnail, 0 to derive it from the height.
+    pub width: u32,
+    /// Height of the thumbnail, 0 to derive it from the width.
+    pub height: u32,
+    /// Crop the picture to the requested size instead of fitting it.
+    pub crop: bool,
+    pub picture_type: PictureType,
+    /// Seek to the nearest keyframe instead of the exact time.
+    pub fast_seek: bool,
+    /// Timeout in ms, 0 for none.
+    pub timeout: i64,
+}
+
+impl Default for ThumbnailOptions {
+    fn default() -> ThumbnailOptions {
+        ThumbnailOptions {
+            width: 0,
+            height: 0,
+            crop: false,
+            picture_type: PictureType::Png,
+            fast_seek: true,
+            timeout: 0,
+        }
+    }
+}
+
+impl ThumbnailOptions {
+    fn seek_speed(&self) -> sys::libvlc_thumbnailer_seek_speed_t {
+        if self.fast_seek {
+            sys::libvlc_thumbnailer_seek_speed_t_libvlc_media_thumbnail_seek_fast
+        } else {
+            sys::libvlc_thumbnailer_seek_speed_t_libvlc_media_thumbnail_seek_precise
+        }
+    }
+}
+
+/// A pending thumbnail request. Dropping it cancels the request if it has not completed.
+///
+/// The result is sent as Event::MediaThumbnailGenerated on the media's event manager,
+/// with None if the thumbnail could not be generated.
+pub struct ThumbnailRequest {
+    ptr: *mut sys::libvlc_media_thumbnail_request_t,
+    _media: Media,
+}
+
+unsafe impl Send for ThumbnailRequest {}
+
+impl ThumbnailRequest {
+    /// Returns raw pointer
+    pub fn raw(&self) -> *mut sys::libvlc_media_thumbnail_request_t {
+        self.ptr
+    }
+}
+
+impl Drop for ThumbnailRequest {
+    fn drop(&mut self) {
+        unsafe{ sys::libvlc_media_thumbnail_request_destroy(self.ptr) };
+    }
+}
+
+impl Media {
+    /// Request a thumbnail at the given time (in ms). Only available with libvlc 4.
+    pub fn thumbnail_request_by_time(&self, instance: &Instance, time: i64, options: &ThumbnailOptions) -> Option<ThumbnailRequest> {
+        unsafe{
+            let p = sys::libvlc_media_thumbnail_request_by_time(
+                instance.ptr, self.ptr, time, options.seek_speed(), options.width, options.height,
+                options.crop, options.picture_type as sys::libvlc_picture_type_t, options.timeout);
+            if p.is_null() { None }else{ Some(ThumbnailRequest{ptr: p, _media: self.clone()}) }
+        }
+    }
+
+    /// Request a thumbnail at the given position between 0.0 and 1.0. Only available with libvlc 4.
+    pub fn thumbnail_request_by_pos(&self, instance: &Instance, pos: f32, options: &ThumbnailOptions) -> Option<ThumbnailRequest> {
+        unsafe{
+            let p = sys::libvlc_media_thumbnail_request_by_pos(
+                instance.ptr, self.ptr, pos as f64, options.seek_speed(), options.width, options.height,
+                options.crop, options.picture_type as sys::libvlc_picture_type_t, options.timeout);
+            if p.is_null() { None }else{ Some(ThumbnailRequest{ptr: p, _media: self.clone()}) }
+        }
+    }
+}
+
+// Pictures are not serialized: an event carrying one is written with null data.
+#[cfg(feature = "serde")]
+pub(crate) mod no_picture {
+    use serde::de::IgnoredAny;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::Picture;
+
+    pub fn serialize<S: Serializer>(_: &Option<Picture>, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_none()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Picture>, D::Error> {
+        IgnoredAny::deserialize(d)?;
+        Ok(None)
+    }
+}
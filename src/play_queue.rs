@@ -0,0 +1,546 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+
+use crate::{EventManagerApi, EventType, MediaPlayerApi};
+#[cfg(feature = "libvlc4")]
+use crate::State;
+
+/// What the queue does when an item ends.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RepeatMode {
+    /// Stop after the last item.
+    #[default]
+    Off,
+    /// Play the current item again.
+    One,
+    /// Start over after the last item, with a new order when shuffling.
+    All,
+}
+
+/// Events of a [PlayQueue].
+#[derive(Clone, Debug)]
+pub enum QueueEvent<M> {
+    /// An item started, with its index in the queue.
+    NowPlaying { index: usize, media: M },
+    /// The last item ended and the queue does not repeat.
+    Finished,
+}
+
+/// The position of a [PlayQueue], to save and restore it along with its items.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QueueCursor {
+    /// Indices of the items in play order.
+    pub order: Vec<usize>,
+    /// Position in `order` of the current item.
+    pub position: Option<usize>,
+    /// The current item was removed, and `position` is the one before it.
+    pub current_removed: bool,
+    pub shuffle: bool,
+    pub repeat: RepeatMode,
+    pub rng_state: u64,
+}
+
+// splitmix64, good enough to shuffle and reproducible from a seed.
+fn next_random(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn random_below(state: &mut u64, n: usize) -> usize {
+    (next_random(state) % n as u64) as usize
+}
+
+fn shuffle_slice(state: &mut u64, slice: &mut [usize]) {
+    for i in (1..slice.len()).rev() {
+        slice.swap(i, random_below(state, i + 1));
+    }
+}
+
+struct QueueState<M> {
+    items: Vec<M>,
+    order: Vec<usize>,
+    pos: Option<usize>,
+    current_removed: bool,
+    shuffle: bool,
+    repeat: RepeatMode,
+    rng: u64,
+    // Whether the player holds the current item.
+    loaded: bool,
+}
+
+impl<M> QueueState<M> {
+    // Position in `order` of the item to play after the current one.
+    fn next_pos(&self) -> usize {
+        self.pos.map_or(0, |p| p + 1)
+    }
+
+    fn current(&self) -> Option<usize> {
+        if self.current_removed { None } else { self.pos.map(|p| self.order[p]) }
+    }
+
+    // Start a new round over every item, not beginning with the one just played.
+    fn new_round(&mut self) {
+        if self.shuffle {
+            let last = self.current();
+            shuffle_slice(&mut self.rng, &mut self.order);
+            if self.order.len() > 1 && self.order.first().copied() == last {
+                let end = self.order.len() - 1;
+                self.order.swap(0, end);
+            }
+        }
+        self.pos = None;
+        self.current_removed = false;
+    }
+}
+
+type Listener<M> = Arc<dyn Fn(QueueEvent<M>) + Send + Sync>;
+
+struct Shared<P: MediaPlayerApi> {
+    player: P,
+    state: Mutex<QueueState<P::Media>>,
+    listeners: Mutex<Vec<Listener<P::Media>>>,
+    // Bumped whenever an item is started, so that the end of a replaced item is ignored.
+    generation: Arc<AtomicU64>,
+    // Stops of an interrupted item, reported like the end of an item by libvlc 4.
+    #[cfg_attr(not(feature = "libvlc4"), allow(dead_code))]
+    interrupted: AtomicUsize,
+}
+
+enum Message {
+    Ended(u64),
+    Quit,
+}
+
+impl<P: MediaPlayerApi> Shared<P> where P::Media: Clone {
+    fn lock(&self) -> MutexGuard<'_, QueueState<P::Media>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn notify(&self, events: Vec<QueueEvent<P::Media>>) {
+        if events.is_empty() {
+            return;
+        }
+        let listeners = self.listeners.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        for event in events {
+            for listener in &listeners {
+                listener(event.clone());
+            }
+        }
+    }
+
+    // Count the stop libvlc 4 reports when the player is interrupted.
+    fn before_interrupt(&self) {
+        #[cfg(feature = "libvlc4")]
+        {
+            if matches!(self.player.state(), State::Opening | State::Buffering | State::Playing | State::Paused) {
+                self.interrupted.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    fn start(&self, st: &mut QueueState<P::Media>, pos: usize) -> Result<QueueEvent<P::Media>, ()> {
+        let index = *st.order.get(pos).ok_or(())?;
+        let md = st.items[index].clone();
+        st.pos = Some(pos);
+        st.current_removed = false;
+        st.loaded = true;
+
+        self.before_interrupt();
+        self.player.set_media(&md);
+        // After set_media, the end of the previous item is stale.
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.player.play()?;
+        Ok(QueueEvent::NowPlaying { index, media: md })
+    }
+
+    fn next(&self, st: &mut QueueState<P::Media>) -> Result<QueueEvent<P::Media>, ()> {
+        let next = st.next_pos();
+        if next < st.order.len() {
+            self.start(st, next)
+        } else if st.repeat == RepeatMode::All && !st.order.is_empty() {
+            st.new_round();
+            self.start(st, 0)
+        } else {
+            Err(())
+        }
+    }
+
+    fn on_end(&self, generation: u64) {
+        #[cfg(feature = "libvlc4")]
+        {
+            let skipped = self.interrupted.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1));
+            if skipped.is_ok() {
+                return;
+            }
+        }
+
+        let mut st = self.lock();
+        if generation != self.generation.load(Ordering::SeqCst) || !st.loaded {
+            return;
+        }
+        let result = match (st.repeat, st.pos) {
+            (RepeatMode::One, Some(pos)) if !st.current_removed => self.start(&mut st, pos),
+            _ => self.next(&mut st),
+        };
+        let event = match result {
+            Ok(event) => event,
+            Err(()) => {
+                st.pos = None;
+                st.current_removed = false;
+                st.loaded = false;
+                QueueEvent::Finished
+            },
+        };
+        drop(st);
+        self.notify(vec![event]);
+    }
+}
+
+/// A play queue driving a media player, with shuffle, repeat and history.
+///
+/// The queue holds its items and their play order. Shuffling picks a new order of the items,
+/// reproducible from its seed, and plays every item once before repeating any. [previous](PlayQueue::previous)
+/// and [next](PlayQueue::next) move back and forth in that order, which is the history of what was played.
+///
+/// When an item ends, the queue starts the next one from a background thread.
+/// [QueueEvent]s are sent to the attached callbacks from the thread that caused them.
+/// With libvlc 4, an item is considered ended when the player stops, so the player should be
+/// stopped with [PlayQueue::stop] rather than directly.
+///
+/// ```no_run
+/// use vlc::{Instance, Media, MediaPlayer, PlayQueue, QueueEvent, RepeatMode};
+///
+/// let instance = Instance::new().unwrap();
+/// let player = MediaPlayer::new(&instance).unwrap();
+/// let queue = PlayQueue::new(&player);
+/// for path in &["a.ogg", "b.ogg", "c.ogg"] {
+///     queue.enqueue(Media::new_path(&instance, path).unwrap());
+/// }
+/// queue.set_shuffle(Some(42));
+/// queue.set_repeat(RepeatMode::All);
+/// queue.attach(|e| if let QueueEvent::NowPlaying { index, .. } = e {
+///     println!("now playing item {}", index);
+/// });
+/// queue.play().unwrap();
+/// ```
+pub struct PlayQueue<P: MediaPlayerApi> {
+    shared: Arc<Shared<P>>,
+    tx: Sender<Message>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl<P> PlayQueue<P>
+    where P: MediaPlayerApi + Clone + Send + Sync + 'static, P::Media: Send + 'static
+{
+    /// Create an empty queue driving `player`.
+    /// With a [MediaListPlayer](crate::MediaListPlayer), use its media player and leave the list player stopped.
+    pub fn new(player: &P) -> PlayQueue<P> {
+        let shared = Arc::new(Shared {
+            player: player.clone(),
+            state: Mutex::new(QueueState {
+                items: Vec::new(),
+                order: Vec::new(),
+                pos: None,
+                current_removed: false,
+                shuffle: false,
+                repeat: RepeatMode::Off,
+                rng: 0,
+                loaded: false,
+            }),
+            listeners: Mutex::new(Vec::new()),
+            generation: Arc::new(AtomicU64::new(0)),
+            interrupted: AtomicUsize::new(0),
+        });
+
+        let (tx, rx) = channel();
+        let (ready_tx, ready_rx) = channel();
+        let event_tx = tx.clone();
+        let worker_shared = shared.clone();
+        // Stopping with libvlc 4, which also ends items on interruption.
+        let end_event = EventType::MediaPlayerEndReached;
+        // The callback is attached and detached on the worker, which owns its handle.
+        let worker = thread::spawn(move || {
+            let generation = worker_shared.generation.clone();
+            let em = worker_shared.player.event_manager();
+            let handle = em.attach(end_event, move |_, _| {
+                let _ = event_tx.send(Message::Ended(generation.load(Ordering::SeqCst)));
+            });
+            let _ = ready_tx.send(());
+
+            while let Ok(Message::Ended(generation)) = rx.recv() {
+                worker_shared.on_end(generation);
+            }
+            if let Ok(handle) = handle {
+                em.detach(end_event, handle);
+            }
+        });
+        let _ = ready_rx.recv();
+
+        PlayQueue { shared, tx, worker: Some(worker) }
+    }
+
+    /// Register a callback for the events of the queue.
+    pub fn attach<F>(&self, callback: F)
+        where F: Fn(QueueEvent<P::Media>) + Send + Sync + 'static
+    {
+        self.shared.listeners.lock().unwrap_or_else(|e| e.into_inner()).push(Arc::new(callback));
+    }
+
+    fn with_state<R, F>(&self, f: F) -> R
+        where F: FnOnce(&Shared<P>, &mut QueueState<P::Media>) -> R
+    {
+        f(&self.shared, &mut self.shared.lock())
+    }
+
+    // Run an operation which may start an item, and report it once unlocked.
+    fn control<F>(&self, f: F) -> Result<(), ()>
+        where F: FnOnce(&Shared<P>, &mut QueueState<P::Media>) -> Result<Option<QueueEvent<P::Media>>, ()>
+    {
+        let event = self.with_state(f)?;
+        self.shared.notify(event.into_iter().collect());
+        Ok(())
+    }
+
+    /// Add a media at the end of the queue. When shuffling, it is played at a random point among the upcoming items.
+    pub fn enqueue(&self, md: P::Media) {
+        self.with_state(|_, st| {
+            let index = st.items.len();
+            st.items.push(md);
+            let pos = if st.shuffle {
+                let next = st.next_pos();
+                next + random_below(&mut st.rng, st.order.len() - next + 1)
+            } else {
+                st.order.len()
+            };
+            st.order.insert(pos, index);
+        })
+    }
+
+    /// Add a media to be played right after the current one.
+    pub fn play_next(&self, md: P::Media) {
+        self.with_state(|_, st| {
+            let next = st.next_pos();
+            st.items.push(md);
+            st.order.insert(next, st.items.len() - 1);
+        })
+    }
+
+    /// Remove the item at `index` in the queue. If it is playing, it goes on and the queue continues after it.
+    pub fn remove(&self, index: usize) -> Result<P::Media, ()> {
+        self.with_state(|_, st| {
+            if index >= st.items.len() {
+                return Err(());
+            }
+            let md = st.items.remove(index);
+            let q = st.order.iter().position(|&i| i == index).ok_or(())?;
+            st.order.remove(q);
+            for i in st.order.iter_mut() {
+                if *i > index {
+                    *i -= 1;
+                }
+            }
+            if let Some(p) = st.pos {
+                if q < p || (q == p && st.current_removed) {
+                    st.pos = p.checked_sub(1);
+                } else if q == p {
+                    st.pos = p.checked_sub(1);
+                    st.current_removed = true;
+                }
+            }
+            Ok(md)
+        })
+    }
+
+    /// Remove every item. The player is left as it is.
+    pub fn clear(&self) {
+        self.with_state(|_, st| {
+            st.items.clear();
+            st.order.clear();
+            st.pos = None;
+            st.current_removed = false;
+            st.loaded = false;
+        })
+    }
+
+    /// The items, in the order they were added.
+    pub fn items(&self) -> Vec<P::Media> {
+        self.shared.lock().items.clone()
+    }
+
+    /// Indices of the items in play order.
+    pub fn order(&self) -> Vec<usize> {
+        self.shared.lock().order.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.shared.lock().items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The current item and its index in the queue.
+    pub fn current(&self) -> Option<(usize, P::Media)> {
+        let st = self.shared.lock();
+        st.current().map(|i| (i, st.items[i].clone()))
+    }
+
+    /// Shuffle the items not played yet with a seed, or go back to the queue order with None.
+    pub fn set_shuffle(&self, seed: Option<u64>) {
+        self.with_state(|_, st| {
+            match seed {
+                // Not shuffling, the order is kept with the items queued by play_next.
+                None if !st.shuffle => (),
+                Some(seed) => {
+                    st.shuffle = true;
+                    st.rng = seed;
+                    let next = st.next_pos();
+                    shuffle_slice(&mut st.rng, &mut st.order[next..]);
+                },
+                None => {
+                    let next = st.order.get(st.next_pos()).copied();
+                    let current = st.current();
+                    st.shuffle = false;
+                    st.order = (0..st.items.len()).collect();
+                    st.pos = match (current, next) {
+                        (Some(i), _) => Some(i),
+                        (None, Some(n)) => n.checked_sub(1),
+                        (None, None) => st.order.len().checked_sub(1),
+                    };
+                    st.current_removed = current.is_none() && st.pos.is_some();
+                },
+            }
+        })
+    }
+
+    pub fn shuffle(&self) -> bool {
+        self.shared.lock().shuffle
+    }
+
+    pub fn set_repeat(&self, repeat: RepeatMode) {
+        self.shared.lock().repeat = repeat;
+    }
+
+    pub fn repeat(&self) -> RepeatMode {
+        self.shared.lock().repeat
+    }
+
+    /// Resume the current item, or start the next one.
+    pub fn play(&self) -> Result<(), ()> {
+        self.control(|sh, st| match st.pos {
+            Some(pos) if !st.current_removed && !st.loaded => sh.start(st, pos).map(Some),
+            Some(_) if !st.current_removed => sh.player.play().map(|_| None),
+            _ => sh.next(st).map(Some),
+        })
+    }
+
+    /// Stop the player, keeping the current item.
+    pub fn stop(&self) {
+        self.with_state(|sh, _| {
+            sh.before_interrupt();
+            sh.player.stop();
+        })
+    }
+
+    /// Play the next item in play order. Fails at the end of the queue, unless repeating all.
+    pub fn next(&self) -> Result<(), ()> {
+        self.control(|sh, st| sh.next(st).map(Some))
+    }
+
+    /// Play the item before the current one in play order.
+    pub fn previous(&self) -> Result<(), ()> {
+        self.control(|sh, st| {
+            let target = match st.pos {
+                Some(p) if st.current_removed => p,
+                Some(p) if p > 0 => p - 1,
+                _ => return Err(()),
+            };
+            sh.start(st, target).map(Some)
+        })
+    }
+
+    /// Play the item at `index` in the queue. When shuffling, it is moved after the current one in play order,
+    /// so that [previous](PlayQueue::previous) goes back to the current one.
+    pub fn play_index(&self, index: usize) -> Result<(), ()> {
+        self.control(|sh, st| {
+            if index >= st.items.len() {
+                return Err(());
+            }
+            if st.current() == Some(index) || !st.shuffle {
+                return sh.start(st, index_pos(st, index)).map(Some);
+            }
+            let q = index_pos(st, index);
+            let mut next = st.next_pos();
+            st.order.remove(q);
+            if q < next {
+                next -= 1;
+                st.pos = st.pos.and_then(|p| p.checked_sub(1));
+            }
+            st.order.insert(next, index);
+            sh.start(st, next).map(Some)
+        })
+    }
+
+    /// The current position, to restore it later with the same items.
+    pub fn cursor(&self) -> QueueCursor {
+        let st = self.shared.lock();
+        QueueCursor {
+            order: st.order.clone(),
+            position: st.pos,
+            current_removed: st.current_removed,
+            shuffle: st.shuffle,
+            repeat: st.repeat,
+            rng_state: st.rng,
+        }
+    }
+
+    /// Restore a position saved with [cursor](PlayQueue::cursor). The items must be the same as when it was saved.
+    /// Playback does not start until [play](PlayQueue::play).
+    pub fn restore(&self, cursor: &QueueCursor) -> Result<(), ()> {
+        self.with_state(|_, st| {
+            let mut seen = vec![false; st.items.len()];
+            if cursor.order.len() != seen.len() || cursor.position.is_some_and(|p| p >= seen.len()) {
+                return Err(());
+            }
+            for &i in &cursor.order {
+                if i >= seen.len() || std::mem::replace(&mut seen[i], true) {
+                    return Err(());
+                }
+            }
+            st.order = cursor.order.clone();
+            st.pos = cursor.position;
+            st.current_removed = cursor.current_removed;
+            st.shuffle = cursor.shuffle;
+            st.repeat = cursor.repeat;
+            st.rng = cursor.rng_state;
+            st.loaded = false;
+            Ok(())
+        })
+    }
+}
+
+fn index_pos<M>(st: &QueueState<M>, index: usize) -> usize {
+    st.order.iter().position(|&i| i == index).unwrap_or(index)
+}
+
+impl<P: MediaPlayerApi> Drop for PlayQueue<P> {
+    fn drop(&mut self) {
+        let _ = self.tx.send(Message::Quit);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
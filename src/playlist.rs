@@ -0,0 +1,517 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+//! Playlist files: M3U (and extended M3U), PLS and XSPF.
+//!
+//! Parsing and writing are done in Rust, so that playlists can be exported and imported
+//! regardless of the modules of the installed VLC. [MediaList::load_playlist](crate::MediaList::load_playlist)
+//! and [MediaList::save_playlist](crate::MediaList::save_playlist) convert them to media lists.
+//!
+//! ```
+//! use std::path::Path;
+//! use std::time::Duration;
+//! use vlc::playlist::{self, Entry, Format, Location};
+//!
+//! let text = "#EXTM3U\n#EXTINF:215,Artist - Title\nmusic/song.mp3\nhttp://example.com/radio\n";
+//! let entries = playlist::parse(Format::M3u, text, Path::new("/home/user")).unwrap();
+//! assert_eq!(entries[0], Entry {
+//!     location: Location::Path("/home/user/music/song.mp3".into()),
+//!     title: Some("Title".to_owned()),
+//!     artist: Some("Artist".to_owned()),
+//!     length: Some(Duration::from_secs(215)),
+//! });
+//! assert_eq!(entries[1].location, Location::Url("http://example.com/radio".to_owned()));
+//! assert_eq!(playlist::write(Format::M3u, &entries, Path::new("/home/user")), text);
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use crate::{Instance, Media, MediaList, Meta};
+
+/// A playlist file format.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Format {
+    /// M3U, written as UTF-8 extended M3U. Also used for `.m3u8`.
+    M3u,
+    Pls,
+    Xspf,
+}
+
+impl Format {
+    /// The format matching the extension of a file, if any.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Format> {
+        let ext = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "m3u" | "m3u8" => Some(Format::M3u),
+            "pls" => Some(Format::Pls),
+            "xspf" => Some(Format::Xspf),
+            _ => None,
+        }
+    }
+
+    // Guess the format from the beginning of a playlist.
+    fn detect(text: &str) -> Option<Format> {
+        let start = text.trim_start_matches('\u{feff}').trim_start();
+        if start.starts_with("#EXTM3U") {
+            Some(Format::M3u)
+        } else if start.get(..10).is_some_and(|s| s.eq_ignore_ascii_case("[playlist]")) {
+            Some(Format::Pls)
+        } else if start.starts_with("<?xml") || start.starts_with("<playlist") {
+            Some(Format::Xspf)
+        } else {
+            None
+        }
+    }
+}
+
+/// Where a playlist entry points to.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Location {
+    /// A local file.
+    Path(PathBuf),
+    /// Any other MRL, such as `http://...` or `dvd://...`.
+    Url(String),
+}
+
+impl Location {
+    /// The location of a media, from its MRL. `file://` MRLs become paths.
+    pub fn from_mrl(mrl: &str) -> Location {
+        match mrl_to_path(mrl) {
+            Some(path) => Location::Path(path),
+            None => Location::Url(mrl.to_owned()),
+        }
+    }
+
+    // Parse a location as written in M3U and PLS files, resolving relative paths against `base`.
+    fn from_entry(s: &str, base: &Path) -> Location {
+        if s.starts_with("file://") {
+            Location::from_mrl(s)
+        } else if has_scheme(s) {
+            Location::Url(s.to_owned())
+        } else {
+            Location::Path(base.join(s))
+        }
+    }
+
+    // Write a location for M3U and PLS files: paths below `base` are relative.
+    fn to_entry(&self, base: &Path) -> String {
+        match self {
+            Location::Path(path) => match path.strip_prefix(base) {
+                Ok(rel) if is_plain(rel) => rel.to_string_lossy().into_owned(),
+                _ => path.to_string_lossy().into_owned(),
+            },
+            Location::Url(url) => url.clone(),
+        }
+    }
+}
+
+/// An item of a playlist.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Entry {
+    pub location: Location,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub length: Option<Duration>,
+}
+
+impl Entry {
+    pub fn new(location: Location) -> Entry {
+        Entry { location, title: None, artist: None, length: None }
+    }
+}
+
+/// Parse a playlist. Relative locations are resolved against `base`, the directory of the playlist.
+/// Returns None if the text is not a PLS or XSPF playlist; any text is read as M3U.
+pub fn parse(format: Format, text: &str, base: &Path) -> Option<Vec<Entry>> {
+    let text = text.trim_start_matches('\u{feff}');
+    match format {
+        Format::M3u => Some(parse_m3u(text, base)),
+        Format::Pls => parse_pls(text, base),
+        Format::Xspf => parse_xspf(text, base),
+    }
+}
+
+/// Write a playlist. Paths below `base`, the directory of the playlist, are written relative to it.
+pub fn write(format: Format, entries: &[Entry], base: &Path) -> String {
+    match format {
+        Format::M3u => write_m3u(entries, base),
+        Format::Pls => write_pls(entries, base),
+        Format::Xspf => write_xspf(entries, base),
+    }
+}
+
+// The fields of an entry read before its location.
+#[derive(Default)]
+struct Info {
+    title: Option<String>,
+    artist: Option<String>,
+    length: Option<Duration>,
+}
+
+impl Info {
+    fn into_entry(self, location: Location) -> Entry {
+        Entry { location, title: self.title, artist: self.artist, length: self.length }
+    }
+}
+
+fn parse_m3u(text: &str, base: &Path) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut info = Info::default();
+
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(extinf) = line.strip_prefix("#EXTINF:") {
+            // #EXTINF:<seconds>[ attributes],[<artist> - ]<title>
+            let (head, name) = extinf.split_once(',').unwrap_or((extinf, ""));
+            let length = head.split_whitespace().next().and_then(secs_to_duration);
+            let (artist, title) = match name.trim().split_once(" - ") {
+                Some((artist, title)) => (non_empty(artist), non_empty(title)),
+                None => (None, non_empty(name)),
+            };
+            info = Info { title, artist, length };
+        } else if !line.starts_with('#') {
+            entries.push(std::mem::take(&mut info).into_entry(Location::from_entry(line, base)));
+        }
+    }
+
+    entries
+}
+
+fn write_m3u(entries: &[Entry], base: &Path) -> String {
+    let mut out = String::from("#EXTM3U\n");
+    for entry in entries {
+        if entry.title.is_some() || entry.artist.is_some() || entry.length.is_some() {
+            let secs = entry.length.map_or(-1, |d| d.as_secs() as i64);
+            let _ = match (&entry.artist, &entry.title) {
+                (Some(artist), Some(title)) => writeln!(out, "#EXTINF:{},{} - {}", secs, artist, title),
+                (Some(name), None) | (None, Some(name)) => writeln!(out, "#EXTINF:{},{}", secs, name),
+                (None, None) => writeln!(out, "#EXTINF:{},", secs),
+            };
+        }
+        out.push_str(&entry.location.to_entry(base));
+        out.push('\n');
+    }
+    out
+}
+
+fn parse_pls(text: &str, base: &Path) -> Option<Vec<Entry>> {
+    Format::detect(text).filter(|&f| f == Format::Pls)?;
+
+    // Entries are numbered from 1, in any order.
+    let mut items: BTreeMap<u32, (Option<String>, Info)> = BTreeMap::new();
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let key = key.trim().to_ascii_lowercase();
+        let value = value.trim();
+        let field = ["file", "title", "length"].iter()
+            .find_map(|name| Some((*name, key.strip_prefix(name)?.parse::<u32>().ok()?)));
+        let Some((name, n)) = field else { continue };
+        let (file, info) = items.entry(n).or_default();
+        match name {
+            "file" => *file = non_empty(value),
+            "title" => info.title = non_empty(value),
+            _ => info.length = secs_to_duration(value),
+        }
+    }
+
+    Some(items.into_values()
+        .filter_map(|(file, info)| Some(info.into_entry(Location::from_entry(&file?, base))))
+        .collect())
+}
+
+fn write_pls(entries: &[Entry], base: &Path) -> String {
+    let mut out = String::from("[playlist]\n");
+    for (i, entry) in entries.iter().enumerate() {
+        let n = i + 1;
+        let _ = writeln!(out, "File{}={}", n, entry.location.to_entry(base));
+        if let Some(title) = &entry.title {
+            let _ = writeln!(out, "Title{}={}", n, title);
+        }
+        let _ = writeln!(out, "Length{}={}", n, entry.length.map_or(-1, |d| d.as_secs() as i64));
+    }
+    let _ = write!(out, "NumberOfEntries={}\nVersion=2\n", entries.len());
+    out
+}
+
+fn parse_xspf(text: &str, base: &Path) -> Option<Vec<Entry>> {
+    text.find("<playlist")?;
+
+    let mut entries = Vec::new();
+    let mut rest = text;
+    while let Some(track) = next_element(&mut rest, "track") {
+        let Some(location) = element_text(track, "location") else { continue };
+        let location = if has_scheme(&location) {
+            Location::from_mrl(&location)
+        } else {
+            Location::Path(base.join(percent_decode(&location)))
+        };
+        entries.push(Entry {
+            location,
+            title: element_text(track, "title"),
+            artist: element_text(track, "creator"),
+            length: element_text(track, "duration")
+                .and_then(|ms| ms.parse().ok())
+                .map(Duration::from_millis),
+        });
+    }
+
+    Some(entries)
+}
+
+fn write_xspf(entries: &[Entry], base: &Path) -> String {
+    let mut out = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">\n",
+        "  <trackList>\n"));
+    for entry in entries {
+        let location = match &entry.location {
+            Location::Path(path) => match path.strip_prefix(base) {
+                Ok(rel) if is_plain(rel) => percent_encode(&rel.to_string_lossy().replace('\\', "/")),
+                _ => path_to_mrl(path),
+            },
+            Location::Url(url) => url.clone(),
+        };
+        out.push_str("    <track>\n");
+        let _ = writeln!(out, "      <location>{}</location>", xml_escape(&location));
+        if let Some(title) = &entry.title {
+            let _ = writeln!(out, "      <title>{}</title>", xml_escape(title));
+        }
+        if let Some(artist) = &entry.artist {
+            let _ = writeln!(out, "      <creator>{}</creator>", xml_escape(artist));
+        }
+        if let Some(length) = entry.length {
+            let _ = writeln!(out, "      <duration>{}</duration>", length.as_millis());
+        }
+        out.push_str("    </track>\n");
+    }
+    out.push_str("  </trackList>\n</playlist>\n");
+    out
+}
+
+impl MediaList {
+    /// Create a media list from a playlist file, in the format given by its extension or its content.
+    /// Titles and artists are set as meta data of the media; libvlc has no way to set their duration.
+    pub fn load_playlist<P: AsRef<Path>>(instance: &Instance, path: P) -> io::Result<MediaList> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)?;
+        // Extended M3U and the other formats are UTF-8, old M3U files are often Latin-1.
+        let text = match String::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(e) => e.into_bytes().iter().map(|&b| b as char).collect(),
+        };
+
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "not a playlist");
+        let format = Format::from_path(path).or_else(|| Format::detect(&text)).ok_or_else(invalid)?;
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+        let entries = parse(format, &text, base).ok_or_else(invalid)?;
+
+        let list = MediaList::new(instance)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "failed to create a media list"))?;
+        for entry in entries {
+            let md = match &entry.location {
+                Location::Path(path) => Media::new_path(instance, path),
+                Location::Url(url) => Media::new_location(instance, url),
+            };
+            let Some(md) = md else { continue };
+            if let Some(title) = &entry.title {
+                md.set_meta(Meta::Title, title);
+            }
+            if let Some(artist) = &entry.artist {
+                md.set_meta(Meta::Artist, artist);
+            }
+            let _ = list.add_media(&md);
+        }
+
+        Ok(list)
+    }
+
+    /// Write the media of the list to a playlist file, with their title, artist and length.
+    pub fn save_playlist<P: AsRef<Path>>(&self, path: P, format: Format) -> io::Result<()> {
+        let path = path.as_ref();
+        let entries: Vec<Entry> = self.iter()
+            .filter_map(|md| Some(Entry {
+                location: Location::from_mrl(&md.mrl()?),
+                title: md.get_meta(Meta::Title),
+                artist: md.get_meta(Meta::Artist),
+                length: md.length(),
+            }))
+            .collect();
+
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+        fs::write(path, write(format, &entries, base))
+    }
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    let s = s.trim();
+    if s.is_empty() { None } else { Some(s.to_owned()) }
+}
+
+// Playlists use -1 for an unknown length.
+fn secs_to_duration(s: &str) -> Option<Duration> {
+    s.trim().parse::<f64>().ok()
+        .filter(|secs| *secs > 0.0 && secs.is_finite())
+        .map(Duration::from_secs_f64)
+}
+
+// Whether `s` starts with a URL scheme such as `http:`. Windows drive letters are not schemes.
+fn has_scheme(s: &str) -> bool {
+    match s.find(':') {
+        Some(i) if i > 1 => {
+            let scheme = &s[..i];
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+        },
+        _ => false,
+    }
+}
+
+// Whether a relative path stays below its base.
+fn is_plain(path: &Path) -> bool {
+    path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+fn mrl_to_path(mrl: &str) -> Option<PathBuf> {
+    let rest = mrl.strip_prefix("file://")?;
+    let rest = rest.strip_prefix("localhost").unwrap_or(rest);
+    let path = percent_decode(rest);
+    // file:///C:/dir on Windows.
+    if cfg!(windows) && path.len() > 2 && path.as_bytes()[0] == b'/' && path.as_bytes()[2] == b':' {
+        return Some(PathBuf::from(&path[1..]));
+    }
+    Some(PathBuf::from(path))
+}
+
+fn path_to_mrl(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    if path.starts_with('/') {
+        format!("file://{}", percent_encode(&path))
+    } else {
+        format!("file:///{}", percent_encode(&path))
+    }
+}
+
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~/:".contains(&b) {
+            out.push(b as char);
+        } else {
+            let _ = write!(out, "%{:02X}", b);
+        }
+    }
+    out
+}
+
+fn percent_decode(s: &str) -> String {
+    let b = s.as_bytes();
+    let mut out = Vec::with_capacity(b.len());
+    let mut i = 0;
+    while i < b.len() {
+        let hex = b.get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (b[i], hex) {
+            (b'%', Some(v)) => {
+                out.push(v);
+                i += 3;
+            },
+            (c, _) => {
+                out.push(c);
+                i += 1;
+            },
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn xml_unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let entity = rest.find(';').map(|end| (&rest[1..end], end));
+        let c = entity.and_then(|(name, _)| match name {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => {
+                let code = match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => name.strip_prefix('#')?.parse().ok(),
+                };
+                code.and_then(char::from_u32)
+            },
+        });
+        match (c, entity) {
+            (Some(c), Some((_, end))) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            },
+            _ => {
+                out.push('&');
+                rest = &rest[1..];
+            },
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// The content of the next `<name>` element in `rest`, advancing past it.
+// Good enough for XSPF: elements of the same name are not nested.
+fn next_element<'a>(rest: &mut &'a str, name: &str) -> Option<&'a str> {
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+    loop {
+        let start = rest.find(&open)?;
+        let after = &rest[start + open.len()..];
+        // Skip longer names sharing the prefix, such as <trackList> for <track>.
+        if !after.starts_with(|c: char| c == '>' || c == '/' || c.is_whitespace()) {
+            *rest = after;
+            continue;
+        }
+        let tag_end = after.find('>')?;
+        if after[..tag_end].ends_with('/') {
+            *rest = &after[tag_end + 1..];
+            return Some("");
+        }
+        let content = &after[tag_end + 1..];
+        let end = content.find(&close)?;
+        *rest = &content[end + close.len()..];
+        return Some(&content[..end]);
+    }
+}
+
+fn element_text(parent: &str, name: &str) -> Option<String> {
+    let mut rest = parent;
+    let content = next_element(&mut rest, name)?;
+    let text = match content.trim().strip_prefix("<![CDATA[").and_then(|s| s.strip_suffix("]]>")) {
+        Some(cdata) => cdata.to_owned(),
+        None => xml_unescape(content),
+    };
+    non_empty(&text)
+}
@@ -0,0 +1,897 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+//! Stream output (sout) chains.
+//!
+//! A chain such as `#transcode{vcodec=h264,vb=800}:std{access=file,mux=ts,dst=out.ts}` can be
+//! built from typed modules and rendered with `to_string()`, which is accepted as a VLM output,
+//! or with [Chain::to_option], which is accepted by `Media::add_option`.
+//!
+//! ```
+//! use vlc::sout::{Chain, Std, Transcode};
+//!
+//! let chain = Chain::new()
+//!     .then(Transcode::new().vcodec("h264").vb(800).acodec("mp4a").ab(128))
+//!     .then(Std::file("out.ts").mux("ts"));
+//! assert_eq!(
+//!     chain.to_string(),
+//!     "#transcode{vcodec=h264,vb=800,acodec=mp4a,ab=128}:std{access=file,mux=ts,dst=out.ts}"
+//! );
+//! assert_eq!(chain.to_string().parse::<Chain>().unwrap(), chain);
+//! ```
+
+use std::error;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A stream output chain: modules separated by `:`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Chain {
+    modules: Vec<Module>,
+}
+
+impl Chain {
+    /// Create an empty chain.
+    pub fn new() -> Chain {
+        Chain::default()
+    }
+
+    /// Append a module to the chain.
+    pub fn then<M: Into<Module>>(mut self, module: M) -> Chain {
+        self.modules.push(module.into());
+        self
+    }
+
+    /// Append a module to the chain.
+    pub fn push<M: Into<Module>>(&mut self, module: M) {
+        self.modules.push(module.into());
+    }
+
+    pub fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    pub fn modules_mut(&mut self) -> &mut Vec<Module> {
+        &mut self.modules
+    }
+
+    /// Find the first module with the given name.
+    pub fn module(&self, name: &str) -> Option<&Module> {
+        self.modules.iter().find(|m| m.name == name)
+    }
+
+    /// Find the first module with the given name.
+    pub fn module_mut(&mut self, name: &str) -> Option<&mut Module> {
+        self.modules.iter_mut().find(|m| m.name == name)
+    }
+
+    /// Render the chain as a media option, `:sout=#...`.
+    pub fn to_option(&self) -> String {
+        format!(":sout={}", self)
+    }
+
+    /// Parse a chain. A leading `:sout=` or `#` is accepted.
+    pub fn parse(s: &str) -> Result<Chain, ParseError> {
+        let s = s.trim();
+        let (s, offset) = match s.strip_prefix(":sout=") {
+            Some(rest) => (rest, 6),
+            None => (s, 0),
+        };
+        let (s, offset) = match s.strip_prefix('#') {
+            Some(rest) => (rest, offset + 1),
+            None => (s, offset),
+        };
+
+        let mut parser = Parser { b: s.as_bytes(), pos: 0 };
+        let mut modules = Vec::new();
+        if !s.is_empty() {
+            loop {
+                modules.push(parser.module().map_err(|e| ParseError { position: e.position + offset, ..e })?);
+                match parser.peek() {
+                    None => break,
+                    Some(b':') => parser.pos += 1,
+                    Some(_) => return Err(parser.error(offset, "expected ':' between modules")),
+                }
+            }
+        }
+
+        Ok(Chain { modules })
+    }
+
+    // Render without the leading '#', as used for nested chains.
+    fn write_modules(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, module) in self.modules.iter().enumerate() {
+            if i > 0 {
+                f.write_str(":")?;
+            }
+            write!(f, "{}", module)?;
+        }
+        Ok(())
+    }
+
+    fn to_nested_string(&self) -> String {
+        struct Nested<'a>(&'a Chain);
+
+        impl<'a> fmt::Display for Nested<'a> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.write_modules(f)
+            }
+        }
+
+        Nested(self).to_string()
+    }
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("#")?;
+        self.write_modules(f)
+    }
+}
+
+impl FromStr for Chain {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Chain, ParseError> {
+        Chain::parse(s)
+    }
+}
+
+/// With the `serde` feature, a chain is serialized as its string.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Chain {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Chain {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Chain, D::Error> {
+        let s = <String as serde::Deserialize>::deserialize(d)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// A single module of a chain with its options, e.g. `std{access=file,dst=out.ts}`.
+///
+/// Options keep their order and may repeat, so editing a parsed module is lossless.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Module {
+    name: String,
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Module {
+    pub fn new(name: &str) -> Module {
+        Module { name: name.to_owned(), options: Vec::new() }
+    }
+
+    /// Append an option with a value.
+    pub fn option<T: ToString>(mut self, name: &str, value: T) -> Module {
+        self.options.push((name.to_owned(), Some(value.to_string())));
+        self
+    }
+
+    /// Append an option without a value.
+    pub fn flag(mut self, name: &str) -> Module {
+        self.options.push((name.to_owned(), None));
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn options(&self) -> &[(String, Option<String>)] {
+        &self.options
+    }
+
+    /// Value of the first option with the given name.
+    /// Returns `Some(None)` for an option present without a value.
+    pub fn get(&self, name: &str) -> Option<Option<&str>> {
+        self.options.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_deref())
+    }
+
+    /// Whether an option is present.
+    pub fn has(&self, name: &str) -> bool {
+        self.options.iter().any(|(n, _)| n == name)
+    }
+
+    /// Replace the value of the first option with the given name, or append it.
+    pub fn set<T: ToString>(&mut self, name: &str, value: Option<T>) {
+        let value = value.map(|v| v.to_string());
+        match self.options.iter_mut().find(|(n, _)| n == name) {
+            Some(option) => option.1 = value,
+            None => self.options.push((name.to_owned(), value)),
+        }
+    }
+
+    /// Remove every option with the given name.
+    pub fn remove(&mut self, name: &str) {
+        self.options.retain(|(n, _)| n != name);
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.get(name).flatten()
+    }
+
+    fn parsed<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.value(name).and_then(|v| v.parse().ok())
+    }
+
+    fn set_opt<T: ToString>(mut self, name: &str, value: Option<T>) -> Module {
+        if let Some(v) = value {
+            self.options.push((name.to_owned(), Some(v.to_string())));
+        }
+        self
+    }
+
+    fn set_flag(mut self, name: &str, on: bool) -> Module {
+        if on {
+            self.options.push((name.to_owned(), None));
+        }
+        self
+    }
+}
+
+impl fmt::Display for Module {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if self.options.is_empty() {
+            return Ok(());
+        }
+        f.write_str("{")?;
+        for (i, (name, value)) in self.options.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            f.write_str(name)?;
+            if let Some(value) = value {
+                f.write_str("=")?;
+                write_value(f, value)?;
+            }
+        }
+        f.write_str("}")
+    }
+}
+
+/// The `transcode` module.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Transcode {
+    pub vcodec: Option<String>,
+    /// Video bitrate in kbit/s.
+    pub vb: Option<u32>,
+    pub scale: Option<f32>,
+    pub fps: Option<f32>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub deinterlace: bool,
+    pub acodec: Option<String>,
+    /// Audio bitrate in kbit/s.
+    pub ab: Option<u32>,
+    pub channels: Option<u32>,
+    pub samplerate: Option<u32>,
+    pub scodec: Option<String>,
+    pub soverlay: bool,
+}
+
+impl Transcode {
+    pub fn new() -> Transcode {
+        Transcode::default()
+    }
+
+    /// Video codec as a FourCC, e.g. `h264`, `VP80`, `theo`.
+    pub fn vcodec(mut self, codec: &str) -> Transcode {
+        self.vcodec = Some(codec.to_owned());
+        self
+    }
+
+    pub fn vb(mut self, kbps: u32) -> Transcode {
+        self.vb = Some(kbps);
+        self
+    }
+
+    pub fn scale(mut self, factor: f32) -> Transcode {
+        self.scale = Some(factor);
+        self
+    }
+
+    pub fn fps(mut self, fps: f32) -> Transcode {
+        self.fps = Some(fps);
+        self
+    }
+
+    pub fn width(mut self, width: u32) -> Transcode {
+        self.width = Some(width);
+        self
+    }
+
+    pub fn height(mut self, height: u32) -> Transcode {
+        self.height = Some(height);
+        self
+    }
+
+    pub fn deinterlace(mut self) -> Transcode {
+        self.deinterlace = true;
+        self
+    }
+
+    /// Audio codec as a FourCC, e.g. `mp4a`, `mp3`, `vorb`.
+    pub fn acodec(mut self, codec: &str) -> Transcode {
+        self.acodec = Some(codec.to_owned());
+        self
+    }
+
+    pub fn ab(mut self, kbps: u32) -> Transcode {
+        self.ab = Some(kbps);
+        self
+    }
+
+    pub fn channels(mut self, channels: u32) -> Transcode {
+        self.channels = Some(channels);
+        self
+    }
+
+    pub fn samplerate(mut self, rate: u32) -> Transcode {
+        self.samplerate = Some(rate);
+        self
+    }
+
+    /// Subtitle codec as a FourCC.
+    pub fn scodec(mut self, codec: &str) -> Transcode {
+        self.scodec = Some(codec.to_owned());
+        self
+    }
+
+    /// Burn the subtitles into the video.
+    pub fn soverlay(mut self) -> Transcode {
+        self.soverlay = true;
+        self
+    }
+
+    /// Read a `transcode` module. Unknown options are ignored.
+    pub fn from_module(module: &Module) -> Option<Transcode> {
+        if module.name != "transcode" {
+            return None;
+        }
+        Some(Transcode {
+            vcodec: module.value("vcodec").map(str::to_owned),
+            vb: module.parsed("vb"),
+            scale: module.parsed("scale"),
+            fps: module.parsed("fps"),
+            width: module.parsed("width"),
+            height: module.parsed("height"),
+            deinterlace: module.has("deinterlace"),
+            acodec: module.value("acodec").map(str::to_owned),
+            ab: module.parsed("ab"),
+            channels: module.parsed("channels"),
+            samplerate: module.parsed("samplerate"),
+            scodec: module.value("scodec").map(str::to_owned),
+            soverlay: module.has("soverlay"),
+        })
+    }
+}
+
+impl From<Transcode> for Module {
+    fn from(t: Transcode) -> Module {
+        Module::new("transcode")
+            .set_opt("vcodec", t.vcodec)
+            .set_opt("vb", t.vb)
+            .set_opt("scale", t.scale)
+            .set_opt("fps", t.fps)
+            .set_opt("width", t.width)
+            .set_opt("height", t.height)
+            .set_flag("deinterlace", t.deinterlace)
+            .set_opt("acodec", t.acodec)
+            .set_opt("ab", t.ab)
+            .set_opt("channels", t.channels)
+            .set_opt("samplerate", t.samplerate)
+            .set_opt("scodec", t.scodec)
+            .set_flag("soverlay", t.soverlay)
+    }
+}
+
+/// The `std` module, writing a muxed stream to a file, HTTP or UDP.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Std {
+    pub access: String,
+    pub mux: Option<String>,
+    pub dst: Option<String>,
+}
+
+impl Std {
+    pub fn new(access: &str) -> Std {
+        Std { access: access.to_owned(), mux: None, dst: None }
+    }
+
+    /// Write to a file.
+    pub fn file<P: AsRef<Path>>(path: P) -> Std {
+        Std::new("file").dst(&path.as_ref().to_string_lossy())
+    }
+
+    /// Serve over HTTP, `dst` being e.g. `:8080/stream`.
+    pub fn http(dst: &str) -> Std {
+        Std::new("http").dst(dst)
+    }
+
+    /// Send over UDP, `dst` being e.g. `239.0.0.1:1234`.
+    pub fn udp(dst: &str) -> Std {
+        Std::new("udp").dst(dst)
+    }
+
+    /// Muxer, e.g. `ts`, `mp4`, `ogg`, `webm`.
+    pub fn mux(mut self, mux: &str) -> Std {
+        self.mux = Some(mux.to_owned());
+        self
+    }
+
+    pub fn dst(mut self, dst: &str) -> Std {
+        self.dst = Some(dst.to_owned());
+        self
+    }
+
+    /// Read a `std` module. Unknown options are ignored.
+    pub fn from_module(module: &Module) -> Option<Std> {
+        if module.name != "std" && module.name != "standard" {
+            return None;
+        }
+        Some(Std {
+            access: module.value("access").unwrap_or_default().to_owned(),
+            mux: module.value("mux").map(str::to_owned),
+            dst: module.value("dst").map(str::to_owned),
+        })
+    }
+}
+
+impl From<Std> for Module {
+    fn from(s: Std) -> Module {
+        Module::new("std")
+            .option("access", s.access)
+            .set_opt("mux", s.mux)
+            .set_opt("dst", s.dst)
+    }
+}
+
+/// The `rtp` module.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Rtp {
+    pub dst: Option<String>,
+    pub port: Option<u16>,
+    pub mux: Option<String>,
+    /// SDP announce URL, e.g. `rtsp://:8554/stream`.
+    pub sdp: Option<String>,
+    pub name: Option<String>,
+}
+
+impl Rtp {
+    pub fn new() -> Rtp {
+        Rtp::default()
+    }
+
+    pub fn dst(mut self, dst: &str) -> Rtp {
+        self.dst = Some(dst.to_owned());
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> Rtp {
+        self.port = Some(port);
+        self
+    }
+
+    pub fn mux(mut self, mux: &str) -> Rtp {
+        self.mux = Some(mux.to_owned());
+        self
+    }
+
+    pub fn sdp(mut self, sdp: &str) -> Rtp {
+        self.sdp = Some(sdp.to_owned());
+        self
+    }
+
+    pub fn name(mut self, name: &str) -> Rtp {
+        self.name = Some(name.to_owned());
+        self
+    }
+
+    /// Read an `rtp` module. Unknown options are ignored.
+    pub fn from_module(module: &Module) -> Option<Rtp> {
+        if module.name != "rtp" {
+            return None;
+        }
+        Some(Rtp {
+            dst: module.value("dst").map(str::to_owned),
+            port: module.parsed("port"),
+            mux: module.value("mux").map(str::to_owned),
+            sdp: module.value("sdp").map(str::to_owned),
+            name: module.value("name").map(str::to_owned),
+        })
+    }
+}
+
+impl From<Rtp> for Module {
+    fn from(r: Rtp) -> Module {
+        Module::new("rtp")
+            .set_opt("dst", r.dst)
+            .set_opt("port", r.port)
+            .set_opt("mux", r.mux)
+            .set_opt("sdp", r.sdp)
+            .set_opt("name", r.name)
+    }
+}
+
+/// The `duplicate` module, sending the stream to several nested chains.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Duplicate {
+    /// Destination chains, each with an optional ES selection such as `"es=1"` or `"novideo"`.
+    pub destinations: Vec<(Chain, Option<String>)>,
+}
+
+impl Duplicate {
+    pub fn new() -> Duplicate {
+        Duplicate::default()
+    }
+
+    pub fn dst(mut self, chain: Chain) -> Duplicate {
+        self.destinations.push((chain, None));
+        self
+    }
+
+    /// Add a destination receiving only the ES matching `select`.
+    pub fn dst_select(mut self, chain: Chain, select: &str) -> Duplicate {
+        self.destinations.push((chain, Some(select.to_owned())));
+        self
+    }
+
+    /// Read a `duplicate` module, parsing its nested chains.
+    pub fn from_module(module: &Module) -> Option<Duplicate> {
+        if module.name != "duplicate" {
+            return None;
+        }
+        let mut destinations: Vec<(Chain, Option<String>)> = Vec::new();
+        for (name, value) in &module.options {
+            match (name.as_str(), value) {
+                ("dst", Some(v)) => destinations.push((Chain::parse(v).ok()?, None)),
+                ("select", Some(v)) => {
+                    if let Some(last) = destinations.last_mut() {
+                        last.1 = Some(v.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Some(Duplicate { destinations })
+    }
+}
+
+impl From<Duplicate> for Module {
+    fn from(d: Duplicate) -> Module {
+        let mut module = Module::new("duplicate");
+        for (chain, select) in d.destinations {
+            module = module.option("dst", chain.to_nested_string()).set_opt("select", select);
+        }
+        module
+    }
+}
+
+/// The `display` module, playing the stream locally.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Display {
+    pub audio: bool,
+    pub video: bool,
+    /// Extra delay in milliseconds.
+    pub delay: Option<i32>,
+}
+
+impl Default for Display {
+    fn default() -> Display {
+        Display { audio: true, video: true, delay: None }
+    }
+}
+
+impl Display {
+    pub fn new() -> Display {
+        Display::default()
+    }
+
+    pub fn no_audio(mut self) -> Display {
+        self.audio = false;
+        self
+    }
+
+    pub fn no_video(mut self) -> Display {
+        self.video = false;
+        self
+    }
+
+    pub fn delay(mut self, ms: i32) -> Display {
+        self.delay = Some(ms);
+        self
+    }
+
+    /// Read a `display` module. Unknown options are ignored.
+    pub fn from_module(module: &Module) -> Option<Display> {
+        if module.name != "display" {
+            return None;
+        }
+        Some(Display {
+            audio: !module.has("noaudio"),
+            video: !module.has("novideo"),
+            delay: module.parsed("delay"),
+        })
+    }
+}
+
+impl From<Display> for Module {
+    fn from(d: Display) -> Module {
+        Module::new("display")
+            .set_flag("noaudio", !d.audio)
+            .set_flag("novideo", !d.video)
+            .set_opt("delay", d.delay)
+    }
+}
+
+/// The `es` module, writing each elementary stream separately.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Es {
+    pub access: Option<String>,
+    pub mux: Option<String>,
+    /// Destination for every ES, where `%c` is replaced by the codec and `%i` by the ES id.
+    pub dst: Option<String>,
+    pub dst_audio: Option<String>,
+    pub dst_video: Option<String>,
+}
+
+impl Es {
+    pub fn new() -> Es {
+        Es::default()
+    }
+
+    pub fn access(mut self, access: &str) -> Es {
+        self.access = Some(access.to_owned());
+        self
+    }
+
+    pub fn mux(mut self, mux: &str) -> Es {
+        self.mux = Some(mux.to_owned());
+        self
+    }
+
+    pub fn dst(mut self, dst: &str) -> Es {
+        self.dst = Some(dst.to_owned());
+        self
+    }
+
+    pub fn dst_audio(mut self, dst: &str) -> Es {
+        self.dst_audio = Some(dst.to_owned());
+        self
+    }
+
+    pub fn dst_video(mut self, dst: &str) -> Es {
+        self.dst_video = Some(dst.to_owned());
+        self
+    }
+
+    /// Read an `es` module. Unknown options are ignored.
+    pub fn from_module(module: &Module) -> Option<Es> {
+        if module.name != "es" {
+            return None;
+        }
+        Some(Es {
+            access: module.value("access").map(str::to_owned),
+            mux: module.value("mux").map(str::to_owned),
+            dst: module.value("dst").map(str::to_owned),
+            dst_audio: module.value("dst-audio").map(str::to_owned),
+            dst_video: module.value("dst-video").map(str::to_owned),
+        })
+    }
+}
+
+impl From<Es> for Module {
+    fn from(e: Es) -> Module {
+        Module::new("es")
+            .set_opt("access", e.access)
+            .set_opt("mux", e.mux)
+            .set_opt("dst", e.dst)
+            .set_opt("dst-audio", e.dst_audio)
+            .set_opt("dst-video", e.dst_video)
+    }
+}
+
+/// Error returned when a chain cannot be parsed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseError {
+    /// Byte offset of the error in the input.
+    pub position: usize,
+    message: &'static str,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid sout chain at {}: {}", self.position, self.message)
+    }
+}
+
+impl error::Error for ParseError {}
+
+// Mirrors config_ChainCreate() and ChainGetValue() from libvlccore.
+struct Parser<'a> {
+    b: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.b.get(self.pos).copied()
+    }
+
+    fn skip_space(&mut self) {
+        while let Some(b' ') | Some(b'\t') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn take_until(&mut self, stop: &[u8]) -> &'a str {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if stop.contains(&c) {
+                break;
+            }
+            self.pos += 1;
+        }
+        // Only split on ASCII bytes, so this stays on char boundaries.
+        std::str::from_utf8(&self.b[start..self.pos]).unwrap()
+    }
+
+    fn error(&self, offset: usize, message: &'static str) -> ParseError {
+        ParseError { position: self.pos + offset, message }
+    }
+
+    fn module(&mut self) -> Result<Module, ParseError> {
+        self.skip_space();
+        let name = self.take_until(b"{: \t");
+        if name.is_empty() {
+            return Err(self.error(0, "expected a module name"));
+        }
+        let mut module = Module::new(name);
+        self.skip_space();
+        if self.peek() != Some(b'{') {
+            return Ok(module);
+        }
+        self.pos += 1;
+        self.skip_space();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            self.skip_space();
+            return Ok(module);
+        }
+
+        loop {
+            let name = self.take_until(b",={} \t");
+            if name.is_empty() {
+                return Err(self.error(0, "expected an option name"));
+            }
+            self.skip_space();
+            let value = if self.peek() == Some(b'=') {
+                self.pos += 1;
+                self.value()
+            } else {
+                None
+            };
+            module.options.push((name.to_owned(), value));
+
+            self.skip_space();
+            match self.peek() {
+                Some(b',') => {
+                    self.pos += 1;
+                    self.skip_space();
+                }
+                Some(b'}') => {
+                    self.pos += 1;
+                    self.skip_space();
+                    return Ok(module);
+                }
+                _ => return Err(self.error(0, "expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn value(&mut self) -> Option<String> {
+        let end = value_end(self.b, self.pos);
+        let mut start = self.pos;
+        let mut stop = end;
+        self.pos = end;
+
+        while start < stop && (self.b[start] == b' ' || self.b[start] == b'\t') {
+            start += 1;
+        }
+        while stop > start && (self.b[stop - 1] == b' ' || self.b[stop - 1] == b'\t') {
+            stop -= 1;
+        }
+        // A quoted or braced value is kept even when empty, unlike a missing one.
+        let quoted = start < stop && matches!(self.b[start], b'"' | b'\'' | b'{');
+        if quoted {
+            start += 1;
+            stop = stop.saturating_sub(1).max(start);
+        }
+        if stop <= start && !quoted {
+            return None;
+        }
+
+        Some(unescape(std::str::from_utf8(&self.b[start..stop]).unwrap()))
+    }
+}
+
+fn is_escaped_char(c: u8) -> bool {
+    c == b'"' || c == b'\'' || c == b'\\'
+}
+
+// Find the end of an option value, skipping over quotes and nested braces (ChainGetEnd()).
+fn value_end(b: &[u8], mut p: usize) -> usize {
+    while p < b.len() && (b[p] == b' ' || b[p] == b'\t') {
+        p += 1;
+    }
+    loop {
+        match b.get(p) {
+            None | Some(b',') | Some(b'}') => return p,
+            Some(b'{') | Some(b'"') | Some(b'\'') => break,
+            Some(_) => p += 1,
+        }
+    }
+
+    let close = if b[p] == b'{' { b'}' } else { b[p] };
+    p += 1;
+    loop {
+        match b.get(p) {
+            None => return p,
+            Some(b'\\') if b.get(p + 1).copied().is_some_and(is_escaped_char) => p += 2,
+            Some(&c) if c == close => return value_end(b, p + 1),
+            Some(b'{') if close == b'}' => p = value_end(b, p),
+            Some(_) => p += 1,
+        }
+    }
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(&next) = chars.peek() {
+                if next == '"' || next == '\'' || next == '\\' {
+                    out.push(next);
+                    chars.next();
+                    continue;
+                }
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
+// Write a value so that the parser above reads it back unchanged.
+fn write_value(f: &mut fmt::Formatter, value: &str) -> fmt::Result {
+    let b = value.as_bytes();
+    let needs_quotes = value.is_empty()
+        || value.starts_with('{')
+        || value.starts_with(char::is_whitespace)
+        || value.ends_with(char::is_whitespace)
+        || b.iter().copied().any(is_escaped_char)
+        || value_end(b, 0) != b.len();
+
+    if !needs_quotes {
+        return f.write_str(value);
+    }
+
+    f.write_str("\"")?;
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            f.write_str("\\")?;
+        }
+        write!(f, "{}", c)?;
+    }
+    f.write_str("\"")
+}
@@ -0,0 +1,118 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::{MediaPlayer, MediaPlayerAudioEx, State};
+
+/// A snapshot of the playback state of a [MediaPlayer], see [MediaPlayer::status].
+/// With the `serde` feature, times are serialized as milliseconds.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlaybackStatus {
+    pub state: State,
+    #[cfg_attr(feature = "serde", serde(with = "crate::tools::opt_duration_ms"))]
+    pub time: Option<Duration>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::tools::opt_duration_ms"))]
+    pub length: Option<Duration>,
+    /// Between 0.0 and 1.0.
+    pub position: Option<f32>,
+    pub rate: f32,
+    pub seekable: bool,
+    pub pausable: bool,
+    /// In percent, or -1 if there is no audio output.
+    pub volume: i32,
+    pub muted: Option<bool>,
+    pub chapter: Option<i32>,
+    pub chapter_count: Option<i32>,
+    pub title: Option<i32>,
+    pub title_count: Option<i32>,
+}
+
+impl MediaPlayer {
+    /// Read the playback state at once.
+    /// libvlc has no atomic snapshot, so the values are read again if the state changed meanwhile.
+    pub fn status(&self) -> PlaybackStatus {
+        let mut status = self.read_status();
+        for _ in 0..2 {
+            if self.state() == status.state {
+                break;
+            }
+            status = self.read_status();
+        }
+        status
+    }
+
+    fn read_status(&self) -> PlaybackStatus {
+        PlaybackStatus {
+            state: self.state(),
+            time: self.time(),
+            length: self.length(),
+            position: self.get_position(),
+            rate: self.get_rate(),
+            seekable: self.is_seekable(),
+            pausable: self.can_pause(),
+            volume: self.get_volume(),
+            muted: self.get_mute(),
+            chapter: self.get_chapter(),
+            chapter_count: self.chapter_count(),
+            title: self.get_title(),
+            title_count: self.title_count(),
+        }
+    }
+}
+
+/// Calls back with the [PlaybackStatus] of a player at a fixed interval, from a background thread.
+/// Polling stops when the poller is dropped.
+pub struct StatusPoller {
+    stop: Arc<(Mutex<bool>, Condvar)>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl StatusPoller {
+    /// Start polling `player` every `interval`. The first status is sent right away.
+    pub fn start<F>(player: &MediaPlayer, interval: Duration, mut callback: F) -> StatusPoller
+        where F: FnMut(PlaybackStatus) + Send + 'static
+    {
+        let player = player.clone();
+        let stop = Arc::new((Mutex::new(false), Condvar::new()));
+        let thread_stop = stop.clone();
+
+        let thread = thread::spawn(move || {
+            let (lock, cvar) = &*thread_stop;
+            loop {
+                callback(player.status());
+                let stopped = lock.lock().unwrap();
+                let (stopped, _) = cvar.wait_timeout_while(stopped, interval, |stopped| !*stopped).unwrap();
+                if *stopped {
+                    break;
+                }
+            }
+        });
+
+        StatusPoller { stop, thread: Some(thread) }
+    }
+
+    /// Stop polling and wait for a running callback to return.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        let (lock, cvar) = &*self.stop;
+        *lock.lock().unwrap() = true;
+        cvar.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for StatusPoller {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::{EventManagerApi, EventType, MediaPlayer, MediaPlayerApi};
+#[cfg(feature = "libvlc4")]
+use crate::{MediaTrackUnion, TrackType};
+#[cfg(not(feature = "libvlc4"))]
+use vlc_sys as sys;
+
+// How long seek_precise waits for the seek to land.
+const SEEK_TIMEOUT: Duration = Duration::from_secs(5);
+
+// How far from the target a landed seek may be when the frame rate is unknown.
+const SEEK_SLACK: Duration = Duration::from_millis(20);
+
+/// Frame stepping and precise seeking, for reviewing video.
+///
+/// These calls wait for libvlc and must not be made from an event callback.
+pub trait MediaPlayerStepEx {
+    /// Frame rate of the current video, or None without video.
+    fn fps(&self) -> Option<f32>;
+    /// Pause and show the next frame.
+    fn step_forward(&self);
+    /// Pause and seek one frame back. Fails without video or when the seek does not land.
+    fn step_backward(&self) -> Result<(), ()>;
+    /// Seek to `time` and wait until the player is there, up to 5 seconds.
+    fn seek_precise(&self, time: Duration) -> Result<(), ()>;
+    /// Loop between `a` and `b` until the returned [AbLoop] is dropped.
+    fn ab_loop(&self, a: Duration, b: Duration) -> Result<AbLoop, ()>;
+}
+
+impl MediaPlayerStepEx for MediaPlayer {
+    #[cfg(not(feature = "libvlc4"))]
+    fn fps(&self) -> Option<f32> {
+        let fps = unsafe { sys::libvlc_media_player_get_fps(self.ptr) };
+        if fps > 0.0 { Some(fps) } else { None }
+    }
+
+    // libvlc 4 has no libvlc_media_player_get_fps, the rate is read from the selected video track.
+    #[cfg(feature = "libvlc4")]
+    fn fps(&self) -> Option<f32> {
+        match self.selected_track(TrackType::Video)?.type_specific_data {
+            MediaTrackUnion::Video(video) if video.frame_rate_num > 0 && video.frame_rate_den > 0 => {
+                Some(video.frame_rate_num as f32 / video.frame_rate_den as f32)
+            },
+            _ => None,
+        }
+    }
+
+    fn step_forward(&self) {
+        self.next_frame();
+    }
+
+    fn step_backward(&self) -> Result<(), ()> {
+        let frame = Duration::from_secs_f32(1.0 / self.fps().ok_or(())?);
+        self.set_pause(true);
+        let time = self.time().ok_or(())?;
+        seek_and_wait(self, time.saturating_sub(frame), frame / 2)
+    }
+
+    fn seek_precise(&self, time: Duration) -> Result<(), ()> {
+        let slack = match self.fps() {
+            Some(fps) => Duration::from_secs_f32(0.5 / fps),
+            None => SEEK_SLACK,
+        };
+        seek_and_wait(self, time, slack)
+    }
+
+    fn ab_loop(&self, a: Duration, b: Duration) -> Result<AbLoop, ()> {
+        AbLoop::new(self, a, b)
+    }
+}
+
+// Seek, then wait until the time is within `slack` of `time`, allowing for playback since the seek.
+fn seek_and_wait<P: MediaPlayerApi>(player: &P, time: Duration, slack: Duration) -> Result<(), ()> {
+    let (tx, rx) = channel();
+    let em = player.event_manager();
+    let handle = em.attach(EventType::MediaPlayerTimeChanged, move |_, _| {
+        let _ = tx.send(());
+    })?;
+
+    let start = Instant::now();
+    player.seek(time);
+    let landed = loop {
+        let ahead = if player.is_playing() { start.elapsed().mul_f32(player.get_rate()) } else { Duration::ZERO };
+        if player.time().is_some_and(|t| t + slack >= time && t <= time + slack + ahead) {
+            break true;
+        }
+        let elapsed = start.elapsed();
+        if elapsed >= SEEK_TIMEOUT {
+            break false;
+        }
+        // Time events may not come while paused, so poll as well.
+        let _ = rx.recv_timeout((SEEK_TIMEOUT - elapsed).min(SEEK_SLACK));
+    };
+
+    em.detach(EventType::MediaPlayerTimeChanged, handle);
+    if landed { Ok(()) } else { Err(()) }
+}
+
+enum LoopMessage {
+    Time,
+    Quit,
+}
+
+/// Loops playback between two points: once the time reaches B, the player seeks back to A.
+///
+/// The seeks are made from a background thread, as libvlc must not be called from its own callbacks.
+/// The loop stops when dropped.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use vlc::{Instance, Media, MediaPlayer, MediaPlayerStepEx};
+///
+/// let instance = Instance::new().unwrap();
+/// let player = MediaPlayer::new(&instance).unwrap();
+/// player.set_media(&Media::new_path(&instance, "take3.mp4").unwrap());
+/// player.play().unwrap();
+/// let ab = player.ab_loop(Duration::from_secs(12), Duration::from_secs(15)).unwrap();
+/// // ...
+/// ab.set_points(Duration::from_secs(13), Duration::from_secs(14)).unwrap();
+/// ```
+pub struct AbLoop {
+    points: Arc<Mutex<(Duration, Duration)>>,
+    tx: Sender<LoopMessage>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl AbLoop {
+    /// Start looping `player` between `a` and `b`. Fails unless `a` is before `b`.
+    pub fn new<P>(player: &P, a: Duration, b: Duration) -> Result<AbLoop, ()>
+        where P: MediaPlayerApi + Clone + Send + 'static
+    {
+        if a >= b {
+            return Err(());
+        }
+        let points = Arc::new(Mutex::new((a, b)));
+        let (tx, rx) = channel();
+        let (ready_tx, ready_rx) = channel();
+
+        let player = player.clone();
+        let event_tx = tx.clone();
+        let worker_points = points.clone();
+        // The callback is attached and detached on the worker, which owns its handle.
+        let worker = thread::spawn(move || {
+            let em = player.event_manager();
+            let attached = em.attach(EventType::MediaPlayerTimeChanged, move |_, _| {
+                let _ = event_tx.send(LoopMessage::Time);
+            });
+            let _ = ready_tx.send(attached.is_ok());
+            let Ok(handle) = attached else { return };
+
+            // Set once seeking back, so that the times reported before the seek lands are ignored.
+            let mut seeking = false;
+            while let Ok(LoopMessage::Time) = rx.recv() {
+                let (a, b) = *worker_points.lock().unwrap_or_else(|e| e.into_inner());
+                match player.time() {
+                    Some(time) if time >= b && !seeking => {
+                        seeking = true;
+                        player.seek(a);
+                    },
+                    Some(time) if time < b => seeking = false,
+                    _ => (),
+                }
+            }
+            em.detach(EventType::MediaPlayerTimeChanged, handle);
+        });
+
+        if ready_rx.recv() != Ok(true) {
+            let _ = worker.join();
+            return Err(());
+        }
+        Ok(AbLoop { points, tx, worker: Some(worker) })
+    }
+
+    /// Move the loop points. Fails unless `a` is before `b`.
+    pub fn set_points(&self, a: Duration, b: Duration) -> Result<(), ()> {
+        if a >= b {
+            return Err(());
+        }
+        *self.points.lock().unwrap_or_else(|e| e.into_inner()) = (a, b);
+        Ok(())
+    }
+
+    pub fn points(&self) -> (Duration, Duration) {
+        *self.points.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for AbLoop {
+    fn drop(&mut self) {
+        let _ = self.tx.send(LoopMessage::Quit);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
@@ -0,0 +1,331 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::{Event, EventManagerApi, EventType, MTime, MediaPlayer, MediaPlayerApi, State};
+use crate::tools::ms_to_duration;
+
+// The rate is set to catch up with the drift over about this many seconds.
+const CORRECTION_WINDOW: f32 = 1.0;
+
+/// Drift measurements of a follower against the leader of a [SyncGroup].
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DriftStats {
+    /// Last measured drift in milliseconds, positive when the follower is ahead.
+    pub drift_ms: i64,
+    /// Largest absolute drift in milliseconds.
+    pub max_drift_ms: i64,
+    /// Mean absolute drift in milliseconds.
+    pub mean_drift_ms: f64,
+    pub samples: u64,
+    /// Times the rate was changed to correct the drift.
+    pub rate_corrections: u64,
+    /// Times the follower was seeked to the leader.
+    pub seeks: u64,
+}
+
+impl DriftStats {
+    fn record(&mut self, drift_ms: i64) {
+        self.drift_ms = drift_ms;
+        self.max_drift_ms = self.max_drift_ms.max(drift_ms.abs());
+        self.samples += 1;
+        self.mean_drift_ms += (drift_ms.abs() as f64 - self.mean_drift_ms) / self.samples as f64;
+    }
+}
+
+struct SyncState {
+    interval: Duration,
+    tolerance: Duration,
+    max_rate_offset: f32,
+    seek_threshold: Duration,
+    stats: Vec<DriftStats>,
+}
+
+// The last time a member reported, and when it reported it.
+type Sample = Option<(Duration, MTime)>;
+
+struct Shared<P> {
+    leader: P,
+    followers: Vec<P>,
+    clock: fn() -> MTime,
+    state: Mutex<SyncState>,
+    // The leader's, then each follower's, cleared when it starts playing again or is seeked.
+    samples: Arc<Mutex<Vec<Sample>>>,
+}
+
+impl<P: MediaPlayerApi> Shared<P> {
+    fn lock(&self) -> MutexGuard<'_, SyncState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn members(&self) -> impl Iterator<Item = &P> {
+        Some(&self.leader).into_iter().chain(&self.followers)
+    }
+
+    fn clear_sample(&self, index: usize) {
+        self.samples.lock().unwrap_or_else(|e| e.into_inner())[index] = None;
+    }
+
+    // The time of member `index` now, extrapolated at `rate` from its last report.
+    // libvlc only updates the time every few hundred milliseconds, so reading it would measure when it did.
+    fn estimate(&self, index: usize, player: &P, rate: f32) -> Option<Duration> {
+        let sample = self.samples.lock().unwrap_or_else(|e| e.into_inner())[index];
+        match sample {
+            Some((time, at)) => {
+                let elapsed = (self.clock)().checked_duration_since(at).unwrap_or_default();
+                Some(time + elapsed.mul_f32(rate))
+            },
+            None => player.time(),
+        }
+    }
+
+    // The drift of follower `index` in milliseconds, and the time of the leader it was compared to.
+    fn measure(&self, index: usize, follower: &P, rate: f32) -> Option<(i64, Duration)> {
+        let leader_time = self.estimate(0, &self.leader, rate)?;
+        let follower_time = self.estimate(index + 1, follower, follower.get_rate())?;
+        let drift = follower_time.as_millis() as i64 - leader_time.as_millis() as i64;
+        Some((drift, leader_time))
+    }
+
+    fn sync(&self) {
+        let mut st = self.lock();
+        if self.leader.state() != State::Playing {
+            return;
+        }
+        let rate = self.leader.get_rate();
+        let tolerance = st.tolerance.as_millis() as i64;
+        let threshold = st.seek_threshold.as_millis() as i64;
+        let max_offset = st.max_rate_offset;
+
+        for (index, (follower, stats)) in self.followers.iter().zip(st.stats.iter_mut()).enumerate() {
+            if follower.state() != State::Playing {
+                continue;
+            }
+            let Some((drift, leader_time)) = self.measure(index, follower, rate) else { continue };
+            stats.record(drift);
+
+            let target = if drift.abs() >= threshold {
+                self.clear_sample(index + 1);
+                follower.seek(leader_time);
+                stats.seeks += 1;
+                rate
+            } else if drift.abs() <= tolerance {
+                rate
+            } else {
+                // Slow down when ahead, speed up when behind.
+                let offset = (drift as f32 / 1000.0 / CORRECTION_WINDOW).clamp(-max_offset, max_offset);
+                rate * (1.0 - offset)
+            };
+            if (follower.get_rate() - target).abs() > f32::EPSILON && follower.set_rate(target).is_ok() && target != rate {
+                stats.rate_corrections += 1;
+            }
+        }
+    }
+}
+
+/// Keeps follower players in sync with a leader, e.g. for the screens of a video wall.
+///
+/// Every interval, the time of each playing follower is compared to the leader's. As libvlc only updates
+/// the time of a player every few hundred milliseconds, each time is extrapolated with the player rate
+/// from when it was last reported, against the libvlc clock. Small drifts are corrected by playing the follower slightly faster or slower,
+/// within a bound of the leader rate, and drifts past a threshold by seeking it to the leader.
+/// [play](SyncGroup::play), [set_pause](SyncGroup::set_pause), [seek](SyncGroup::seek), [set_rate](SyncGroup::set_rate)
+/// and [stop](SyncGroup::stop) apply to every member. Followers are set back to the leader rate on drop.
+///
+/// ```no_run
+/// use vlc::{Instance, Media, MediaPlayer, SyncGroup};
+///
+/// let instance = Instance::new().unwrap();
+/// let md = Media::new_path(&instance, "wall.mp4").unwrap();
+/// let players: Vec<MediaPlayer> = (0..4).map(|_| {
+///     let player = MediaPlayer::new(&instance).unwrap();
+///     player.set_media(&md);
+///     player
+/// }).collect();
+/// let group = SyncGroup::new(&players[0], &players[1..]);
+/// group.play().unwrap();
+/// ```
+pub struct SyncGroup<P: MediaPlayerApi = MediaPlayer> {
+    shared: Arc<Shared<P>>,
+    quit: Sender<()>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl<P> SyncGroup<P>
+    where P: MediaPlayerApi + Clone + Send + Sync + 'static
+{
+    /// Sync `followers` to `leader` every 250 ms, correcting drifts over 20 ms with up to 5% of rate
+    /// and seeking past 500 ms.
+    pub fn new(leader: &P, followers: &[P]) -> SyncGroup<P> {
+        SyncGroup::with_clock(leader, followers, MTime::now)
+    }
+
+    /// Like [new](SyncGroup::new), timing the reported times against `clock` instead of the libvlc clock,
+    /// e.g. for players which are not libvlc's.
+    pub fn with_clock(leader: &P, followers: &[P], clock: fn() -> MTime) -> SyncGroup<P> {
+        let shared = Arc::new(Shared {
+            leader: leader.clone(),
+            followers: followers.to_vec(),
+            clock,
+            state: Mutex::new(SyncState {
+                interval: Duration::from_millis(250),
+                tolerance: Duration::from_millis(20),
+                max_rate_offset: 0.05,
+                seek_threshold: Duration::from_millis(500),
+                stats: vec![DriftStats::default(); followers.len()],
+            }),
+            samples: Arc::new(Mutex::new(vec![None; followers.len() + 1])),
+        });
+
+        let (quit, rx) = channel();
+        let (ready_tx, ready_rx) = channel();
+        let worker_shared = shared.clone();
+        // The callbacks are attached and detached on the worker, which owns their handles.
+        let worker = thread::spawn(move || {
+            let mut attached = Vec::new();
+            for (index, player) in worker_shared.members().enumerate() {
+                let em = player.event_manager();
+                let samples = worker_shared.samples.clone();
+                // Take the time from the event, libvlc must not be called back from here.
+                let handle = em.attach(EventType::MediaPlayerTimeChanged, move |e, _| {
+                    if let Event::MediaPlayerTimeChanged(time) = e {
+                        let sample = ms_to_duration(time).map(|time| (time, clock()));
+                        samples.lock().unwrap_or_else(|e| e.into_inner())[index] = sample;
+                    }
+                });
+                // The time did not move while paused or stopped, until it is reported again.
+                let samples = worker_shared.samples.clone();
+                let cleared = em.attach(EventType::MediaPlayerPlaying, move |_, _| {
+                    samples.lock().unwrap_or_else(|e| e.into_inner())[index] = None;
+                });
+                attached.push((em, handle, cleared));
+            }
+            let _ = ready_tx.send(());
+
+            loop {
+                let interval = worker_shared.lock().interval;
+                match rx.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => worker_shared.sync(),
+                    _ => break,
+                }
+            }
+            for (em, handle, cleared) in attached {
+                if let Ok(handle) = handle {
+                    em.detach(EventType::MediaPlayerTimeChanged, handle);
+                }
+                if let Ok(cleared) = cleared {
+                    em.detach(EventType::MediaPlayerPlaying, cleared);
+                }
+            }
+        });
+        let _ = ready_rx.recv();
+
+        SyncGroup { shared, quit, worker: Some(worker) }
+    }
+
+    pub fn leader(&self) -> &P {
+        &self.shared.leader
+    }
+
+    pub fn followers(&self) -> &[P] {
+        &self.shared.followers
+    }
+
+    fn members(&self) -> impl Iterator<Item = &P> {
+        self.shared.members()
+    }
+
+    /// Play every member, failing if any of them fails.
+    pub fn play(&self) -> Result<(), ()> {
+        let mut result = Ok(());
+        for p in self.members() {
+            if p.play().is_err() {
+                result = Err(());
+            }
+        }
+        result
+    }
+
+    pub fn set_pause(&self, pause: bool) {
+        self.members().for_each(|p| p.set_pause(pause));
+    }
+
+    pub fn stop(&self) {
+        self.members().for_each(|p| p.stop());
+    }
+
+    pub fn seek(&self, time: Duration) {
+        for (index, p) in self.members().enumerate() {
+            self.shared.clear_sample(index);
+            p.seek(time);
+        }
+    }
+
+    /// Set the rate of every member. Followers are then corrected around it.
+    pub fn set_rate(&self, rate: f32) -> Result<(), ()> {
+        let mut result = Ok(());
+        for p in self.members() {
+            if p.set_rate(rate).is_err() {
+                result = Err(());
+            }
+        }
+        result
+    }
+
+    /// Compare and correct the followers now, without waiting for the next interval.
+    pub fn sync_now(&self) {
+        self.shared.sync();
+    }
+
+    /// Drift measurements of each follower, in the order they were given.
+    pub fn stats(&self) -> Vec<DriftStats> {
+        self.shared.lock().stats.clone()
+    }
+
+    pub fn reset_stats(&self) {
+        for stats in self.shared.lock().stats.iter_mut() {
+            *stats = DriftStats::default();
+        }
+    }
+
+    /// How often the followers are compared to the leader.
+    pub fn set_interval(&self, interval: Duration) {
+        self.shared.lock().interval = interval;
+    }
+
+    /// Drifts up to `tolerance` are not corrected.
+    pub fn set_tolerance(&self, tolerance: Duration) {
+        self.shared.lock().tolerance = tolerance;
+    }
+
+    /// How far from the leader rate a follower may play to catch up, e.g. 0.05 for 5%.
+    pub fn set_max_rate_offset(&self, offset: f32) {
+        self.shared.lock().max_rate_offset = offset.clamp(0.0, 0.5);
+    }
+
+    /// Drifts from `threshold` are corrected by seeking instead of changing the rate.
+    pub fn set_seek_threshold(&self, threshold: Duration) {
+        self.shared.lock().seek_threshold = threshold;
+    }
+}
+
+impl<P: MediaPlayerApi> Drop for SyncGroup<P> {
+    fn drop(&mut self) {
+        let _ = self.quit.send(());
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        let rate = self.shared.leader.get_rate();
+        for follower in &self.shared.followers {
+            if (follower.get_rate() - rate).abs() > f32::EPSILON {
+                let _ = follower.set_rate(rate);
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::ops::{Add, Sub};
+use std::time::Duration;
+
+use vlc_sys as sys;
+
+/// A time on the libvlc clock, in microseconds, such as the pts handed to audio callbacks.
+/// With the `serde` feature, serialized as the number of microseconds.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct MTime(i64);
+
+impl MTime {
+    /// The current time of the libvlc clock.
+    /// With the `dynamic` feature, zero if libvlc cannot be loaded.
+    pub fn now() -> MTime {
+        if crate::core::ensure_loaded().is_err() {
+            return MTime(0);
+        }
+        MTime(unsafe{ sys::libvlc_clock() })
+    }
+
+    pub fn from_micros(us: i64) -> MTime {
+        MTime(us)
+    }
+
+    pub fn as_micros(self) -> i64 {
+        self.0
+    }
+
+    /// Time left until this time is reached on the libvlc clock, or None if it has passed.
+    pub fn delay(self) -> Option<Duration> {
+        self.checked_duration_since(MTime::now())
+    }
+
+    /// Time elapsed from `earlier` to this time, or None if `earlier` is later.
+    pub fn checked_duration_since(self, earlier: MTime) -> Option<Duration> {
+        self.0.checked_sub(earlier.0)
+            .filter(|&us| us >= 0)
+            .map(|us| Duration::from_micros(us as u64))
+    }
+}
+
+fn duration_to_us(d: Duration) -> i64 {
+    d.as_micros().min(i64::MAX as u128) as i64
+}
+
+impl Add<Duration> for MTime {
+    type Output = MTime;
+
+    fn add(self, d: Duration) -> MTime {
+        MTime(self.0.saturating_add(duration_to_us(d)))
+    }
+}
+
+impl Sub<Duration> for MTime {
+    type Output = MTime;
+
+    fn sub(self, d: Duration) -> MTime {
+        MTime(self.0.saturating_sub(duration_to_us(d)))
+    }
+}
@@ -5,6 +5,7 @@
 use std::ffi::{CString, CStr, NulError};
 use std::path::Path;
 use std::borrow::Cow;
+use std::time::Duration;
 use libc::c_char;
 
 // Convert String to CString.
@@ -41,3 +42,31 @@ pub fn path_to_cstr(path: &Path) -> Result<CString, NulError> {
 
     Ok(path)
 }
+
+// Convert a libvlc time in ms to Duration, negative values meaning none.
+pub fn ms_to_duration(ms: i64) -> Option<Duration> {
+    if ms < 0 { None }else{ Some(Duration::from_millis(ms as u64)) }
+}
+
+// Convert Duration to a libvlc time in ms, saturating.
+pub fn duration_to_ms(d: Duration) -> i64 {
+    d.as_millis().min(i64::MAX as u128) as i64
+}
+
+// Serialize Option<Duration> as an optional number of milliseconds.
+#[cfg(feature = "serde")]
+pub mod opt_duration_ms {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(d: &Option<Duration>, s: S) -> Result<S::Ok, S::Error> {
+        match d {
+            Some(d) => s.serialize_some(&super::duration_to_ms(*d)),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<i64>::deserialize(d)?.and_then(super::ms_to_duration))
+    }
+}
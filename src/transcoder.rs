@@ -0,0 +1,261 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use libc::c_void;
+
+use crate::sout::{Chain, Std, Transcode};
+use crate::{Event, EventType, Instance, Media, MediaPlayer};
+
+/// Output container and codecs of a transcoding job.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TranscodeProfile {
+    /// Muxer of the `std` output, e.g. `mp4`, `webm`, `ogg`, `ts`, `raw`.
+    pub mux: String,
+    pub transcode: Transcode,
+    /// Leave the video and subtitles out of the output. `transcode` passes through the streams it has no codec for.
+    pub audio_only: bool,
+}
+
+impl TranscodeProfile {
+    pub fn new(mux: &str, transcode: Transcode) -> TranscodeProfile {
+        TranscodeProfile { mux: mux.to_owned(), transcode, audio_only: false }
+    }
+
+    /// H.264 video and AAC audio in MP4.
+    pub fn mp4_h264_aac() -> TranscodeProfile {
+        TranscodeProfile::new("mp4", Transcode::new().vcodec("h264").acodec("mp4a").ab(128))
+    }
+
+    /// VP8 video and Vorbis audio in WebM.
+    pub fn webm_vp8_vorbis() -> TranscodeProfile {
+        TranscodeProfile::new("webm", Transcode::new().vcodec("VP80").acodec("vorb").ab(128))
+    }
+
+    /// Theora video and Vorbis audio in Ogg.
+    pub fn ogg_theora_vorbis() -> TranscodeProfile {
+        TranscodeProfile::new("ogg", Transcode::new().vcodec("theo").acodec("vorb").ab(128))
+    }
+
+    /// MP3 audio only, `bitrate` in kbit/s.
+    pub fn mp3(bitrate: u32) -> TranscodeProfile {
+        TranscodeProfile { audio_only: true, ..TranscodeProfile::new("raw", Transcode::new().acodec("mp3").ab(bitrate)) }
+    }
+
+    /// The sout chain writing this profile to `output`.
+    /// It has no `display` module, so nothing is rendered while transcoding.
+    /// [TranscodeProfile::media_options] have to be added to the media as well.
+    pub fn chain<P: AsRef<Path>>(&self, output: P) -> Chain {
+        Chain::new()
+            .then(self.transcode.clone())
+            .then(Std::file(output).mux(&self.mux))
+    }
+
+    /// The media options this profile needs besides the sout chain.
+    pub fn media_options(&self) -> Vec<&'static str> {
+        if self.audio_only { vec![":no-sout-video", ":no-sout-spu"] } else { Vec::new() }
+    }
+}
+
+/// State of a [Transcoder].
+/// With the `serde` feature, serialized like [Event](crate::Event), e.g. `{"type": "running", "data": 0.5}`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "data", rename_all = "snake_case"))]
+pub enum TranscodeState {
+    /// Not started yet.
+    Idle,
+    /// Running, with the position between 0.0 and 1.0.
+    Running(f32),
+    /// The whole input was transcoded.
+    Done,
+    /// libvlc reported an error.
+    Failed,
+    /// Stopped by [Transcoder::cancel].
+    Cancelled,
+}
+
+impl TranscodeState {
+    /// Whether the job has finished, successfully or not.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, TranscodeState::Done | TranscodeState::Failed | TranscodeState::Cancelled)
+    }
+}
+
+struct Shared {
+    state: Mutex<TranscodeState>,
+    // The last position reported, kept once the job has finished.
+    position: Mutex<f32>,
+    cond: Condvar,
+}
+
+impl Shared {
+    fn set(&self, state: TranscodeState) {
+        let mut current = self.state.lock().unwrap();
+        // The first terminal state wins, e.g. Stopped after EndReached.
+        if !current.is_finished() {
+            if let TranscodeState::Running(pos) = state {
+                *self.position.lock().unwrap() = pos;
+            }
+            *current = state;
+            self.cond.notify_all();
+        }
+    }
+}
+
+/// Transcode a media to a file through a [MediaPlayer] and a sout chain.
+pub struct Transcoder {
+    player: MediaPlayer,
+    output: PathBuf,
+    shared: Arc<Shared>,
+    callbacks: Vec<(EventType, *mut c_void)>,
+}
+
+// The callback pointers are only used to detach them from the player's event manager.
+unsafe impl Send for Transcoder {}
+
+impl Transcoder {
+    /// Prepare a job transcoding `input` to `output` with `profile`.
+    /// `input` itself is left untouched; the job uses a copy of it, keeping its options.
+    pub fn new<P: AsRef<Path>>(instance: &Instance, input: &Media, output: P, profile: &TranscodeProfile) -> Option<Transcoder> {
+        let media = input.duplicate()?;
+        media.add_option(&profile.chain(output.as_ref()).to_option());
+        for option in profile.media_options() {
+            media.add_option(option);
+        }
+
+        let player = MediaPlayer::new(instance)?;
+        player.set_media(&media);
+
+        let mut transcoder = Transcoder {
+            player,
+            output: output.as_ref().to_owned(),
+            shared: Arc::new(Shared {
+                state: Mutex::new(TranscodeState::Idle),
+                position: Mutex::new(0.0),
+                cond: Condvar::new(),
+            }),
+            callbacks: Vec::new(),
+        };
+        transcoder.attach(EventType::MediaPlayerPositionChanged, None).ok()?;
+        // With libvlc 4 this is Stopping, the player stops by itself at the end of the input.
+        transcoder.attach(EventType::MediaPlayerEndReached, Some(TranscodeState::Done)).ok()?;
+        transcoder.attach(EventType::MediaPlayerEncounteredError, Some(TranscodeState::Failed)).ok()?;
+        Some(transcoder)
+    }
+
+    fn attach(&mut self, event_type: EventType, terminal: Option<TranscodeState>) -> Result<(), ()> {
+        let shared = self.shared.clone();
+        let raw = self.player.event_manager().attach(event_type, move |e, _| {
+            match (e, terminal) {
+                (Event::MediaPlayerPositionChanged(pos), _) => shared.set(TranscodeState::Running(pos)),
+                (_, Some(state)) => shared.set(state),
+                _ => (),
+            }
+        })?;
+        self.callbacks.push((event_type, raw));
+        Ok(())
+    }
+
+    /// Start transcoding. The job is Failed if the player cannot start.
+    pub fn start(&self) -> Result<(), ()> {
+        self.shared.set(TranscodeState::Running(0.0));
+        let result = self.player.play();
+        if result.is_err() {
+            self.shared.set(TranscodeState::Failed);
+        }
+        result
+    }
+
+    /// Stop transcoding. The output file is left as far as it was written.
+    pub fn cancel(&self) {
+        self.shared.set(TranscodeState::Cancelled);
+        self.player.stop();
+    }
+
+    /// Current state of the job.
+    pub fn state(&self) -> TranscodeState {
+        *self.shared.state.lock().unwrap()
+    }
+
+    /// Current position between 0.0 and 1.0.
+    /// A failed or cancelled job keeps the last position reached.
+    pub fn progress(&self) -> f32 {
+        match self.state() {
+            TranscodeState::Done => 1.0,
+            _ => *self.shared.position.lock().unwrap(),
+        }
+    }
+
+    /// Block until the job finishes, calling `on_progress` with each new position.
+    /// The output file is finalized before returning.
+    /// Returns Idle at once if the job was not started.
+    pub fn wait<F: FnMut(f32)>(&self, mut on_progress: F) -> TranscodeState {
+        let mut state = self.shared.state.lock().unwrap();
+        let mut last = None;
+        loop {
+            match *state {
+                TranscodeState::Running(pos) if last != Some(pos) => {
+                    last = Some(pos);
+                    // Don't block the event callbacks while the caller handles progress.
+                    drop(state);
+                    on_progress(pos);
+                    state = self.shared.state.lock().unwrap();
+                    continue;
+                }
+                TranscodeState::Idle => return TranscodeState::Idle,
+                TranscodeState::Running(_) => {}
+                finished => {
+                    drop(state);
+                    self.finalize();
+                    return finished;
+                }
+            }
+            state = self.shared.cond.wait(state).unwrap();
+        }
+    }
+
+    /// Like [Transcoder::wait], but give up after `timeout` and return the current state.
+    pub fn wait_timeout(&self, timeout: Duration) -> TranscodeState {
+        let state = {
+            let guard = self.shared.state.lock().unwrap();
+            let (guard, _) = self.shared.cond
+                .wait_timeout_while(guard, timeout, |s| matches!(s, TranscodeState::Running(_)))
+                .unwrap();
+            *guard
+        };
+        if state.is_finished() {
+            self.finalize();
+        }
+        state
+    }
+
+    // Stopping closes the sout chain, which writes the container trailer.
+    fn finalize(&self) {
+        self.player.stop();
+    }
+
+    /// The output file.
+    pub fn output(&self) -> &Path {
+        &self.output
+    }
+
+    /// The player running the job.
+    pub fn player(&self) -> &MediaPlayer {
+        &self.player
+    }
+}
+
+impl Drop for Transcoder {
+    fn drop(&mut self) {
+        self.player.stop();
+        let em = self.player.event_manager();
+        for (event_type, raw) in self.callbacks.drain(..) {
+            unsafe { em.detach_and_free(event_type, raw) };
+        }
+    }
+}
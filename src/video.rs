@@ -8,6 +8,8 @@ use crate::enums::VideoAdjustOption;
 use crate::tools::{from_cstr, to_cstr};
 use crate::MediaPlayer;
 use crate::TrackDescription;
+#[cfg(feature = "libvlc4")]
+use crate::TrackType;
 use libc::c_void;
 use vlc_sys as sys;
 
@@ -159,6 +161,11 @@ impl MediaPlayerVideoEx for MediaPlayer {
             sys::libvlc_video_set_scale(self.ptr, factor);
         }
     }
+    #[cfg(feature = "libvlc4")]
+    fn get_video_track(&self) -> Option<i32> {
+        self.selected_track_id(TrackType::Video)
+    }
+    #[cfg(not(feature = "libvlc4"))]
     fn get_video_track(&self) -> Option<i32> {
         unsafe {
             let track = sys::libvlc_video_get_track(self.ptr);
@@ -169,6 +176,11 @@ impl MediaPlayerVideoEx for MediaPlayer {
             }
         }
     }
+    #[cfg(feature = "libvlc4")]
+    fn set_video_track(&self, track: i32) {
+        self.select_track_by_es_id(TrackType::Video, track);
+    }
+    #[cfg(not(feature = "libvlc4"))]
     fn set_video_track(&self, track: i32) {
         unsafe {
             sys::libvlc_video_set_track(self.ptr, track);
@@ -193,6 +205,11 @@ impl MediaPlayerVideoEx for MediaPlayer {
             }
         }
     }
+    #[cfg(feature = "libvlc4")]
+    fn get_video_track_description(&self) -> Option<Vec<TrackDescription>> {
+        self.track_descriptions(TrackType::Video)
+    }
+    #[cfg(not(feature = "libvlc4"))]
     fn get_video_track_description(&self) -> Option<Vec<TrackDescription>> {
         unsafe {
             let p0 = sys::libvlc_video_get_track_description(self.ptr);
@@ -230,6 +247,11 @@ impl MediaPlayerVideoEx for MediaPlayer {
         }
     }
 
+    #[cfg(feature = "libvlc4")]
+    fn get_spu_track_description(&self) -> Option<Vec<TrackDescription>> {
+        self.track_descriptions(TrackType::Text)
+    }
+    #[cfg(not(feature = "libvlc4"))]
     fn get_spu_track_description(&self) -> Option<Vec<TrackDescription>> {
         unsafe {
             let tracks = sys::libvlc_video_get_spu_description(self.ptr);
@@ -254,6 +276,11 @@ impl MediaPlayerVideoEx for MediaPlayer {
         }
     }
 
+    #[cfg(feature = "libvlc4")]
+    fn get_spu_track(&self) -> Option<i32> {
+        self.selected_track_id(TrackType::Text)
+    }
+    #[cfg(not(feature = "libvlc4"))]
     fn get_spu_track(&self) -> Option<i32> {
         unsafe {
             let track = sys::libvlc_video_get_spu(self.ptr);
@@ -265,6 +292,11 @@ impl MediaPlayerVideoEx for MediaPlayer {
         }
     }
 
+    #[cfg(feature = "libvlc4")]
+    fn set_spu_track(&self, track: i32) {
+        self.select_track_by_es_id(TrackType::Text, track);
+    }
+    #[cfg(not(feature = "libvlc4"))]
     fn set_spu_track(&self, track: i32) {
         unsafe {
             sys::libvlc_video_set_spu(self.ptr, track);
@@ -361,6 +393,7 @@ impl MediaPlayerVideoEx for MediaPlayer {
         }
     }
 
+    #[cfg(not(feature = "libvlc4"))]
     fn set_deinterlace(&self, mode: Option<&str>) {
         if let Some(mode) = mode {
             unsafe {
@@ -372,4 +405,17 @@ impl MediaPlayerVideoEx for MediaPlayer {
             }
         }
     }
+
+    #[cfg(feature = "libvlc4")]
+    fn set_deinterlace(&self, mode: Option<&str>) {
+        if let Some(mode) = mode {
+            unsafe {
+                sys::libvlc_video_set_deinterlace(self.ptr, 1, to_cstr(mode).as_ptr());
+            }
+        } else {
+            unsafe {
+                sys::libvlc_video_set_deinterlace(self.ptr, 0, ::std::ptr::null());
+            }
+        }
+    }
 }
@@ -1,10 +1,13 @@
 use std::ffi::CString;
 use std::os::raw::c_char;
 use std::ptr;
+use std::time::Duration;
 
 use vlc_sys as sys;
 use crate::Instance;
 use crate::tools::{from_cstr, to_cstr};
+#[cfg(feature = "serde")]
+use crate::VlmMediaInfo;
 
 pub trait Vlm {
     fn add_broadcast(&self, name: &str, input: &str, output: &str, options: Option<Vec<String>>, enabled: bool, loop_broadcast: bool, ) -> Result<(), ()>;
@@ -19,13 +22,60 @@ pub trait Vlm {
 
     fn get_media_instance_position(&self, name: &str, instance: i32) -> Result<f32, ()>;
 
+    #[deprecated(note = "use `Vlm::media_instance_length`")]
     fn get_media_instance_length(&self, name: &str, instance: i32) -> Result<i32, ()>;
 
+    #[deprecated(note = "use `Vlm::media_instance_time`")]
     fn get_media_instance_time(&self, name: &str, instance: i32) -> Result<i32, ()>;
 
+    /// Length of an instance of a media.
+    /// libvlc returns it as 32 bits of microseconds, so it wraps after about 35 minutes.
+    fn media_instance_length(&self, name: &str, instance: i32) -> Result<Duration, ()>;
+
+    /// Time of an instance of a media, see [Vlm::media_instance_length].
+    fn media_instance_time(&self, name: &str, instance: i32) -> Result<Duration, ()>;
+
     fn get_media_instance_rate(&self, name: &str, instance: i32) -> Result<i32, ()>;
 
     fn show_media(&self, name: &str) -> Result<String, ()>;
+
+    /// Return information about the named media, parsed from [Vlm::show_media].
+    #[cfg(feature = "serde")]
+    fn show_media_info(&self, name: &str) -> Result<VlmMediaInfo, ()>;
+
+    /// Return the names of every configured media, parsed from the listing of all media.
+    #[cfg(feature = "serde")]
+    fn list_media(&self) -> Vec<String>;
+
+    /// Release the vlm instance related to the libvlc instance.
+    fn vlm_release(&self);
+
+    /// Delete a media (VOD or broadcast).
+    fn del_media(&self, name: &str) -> Result<(), ()>;
+
+    /// Enable or disable a media (VOD or broadcast).
+    fn set_enabled(&self, name: &str, enabled: bool) -> Result<(), ()>;
+
+    /// Set the output for a media.
+    fn set_output(&self, name: &str, output: &str) -> Result<(), ()>;
+
+    /// Set a media's input MRL. This will delete all existing inputs and add the specified one.
+    fn set_input(&self, name: &str, input: &str) -> Result<(), ()>;
+
+    /// Add a media's input MRL. This will add the specified one.
+    fn add_input(&self, name: &str, input: &str) -> Result<(), ()>;
+
+    /// Set a media's loop status.
+    fn set_loop(&self, name: &str, loop_broadcast: bool) -> Result<(), ()>;
+
+    /// Set a media's vod muxer.
+    fn set_mux(&self, name: &str, mux: &str) -> Result<(), ()>;
+
+    /// Edit the parameters of a media. This will delete all existing inputs and add the specified one.
+    fn change_media(&self, name: &str, input: &str, output: &str, options: Option<Vec<String>>, enabled: bool, loop_broadcast: bool) -> Result<(), ()>;
+
+    /// Seek in the named broadcast, `percentage` being between 0.0 and 100.0.
+    fn seek_media(&self, name: &str, percentage: f32) -> Result<(), ()>;
 }
 
 impl Vlm for Instance {
@@ -33,17 +83,10 @@ impl Vlm for Instance {
         let name = to_cstr(name);
         let input = to_cstr(input);
         let output = to_cstr(output);
-        let opts_c_ptr: Vec<*const c_char>;
-        let opts_c: Vec<CString>;
+        let opts_c = options_to_cstr(options);
+        let opts_c_ptr: Vec<*const c_char> = opts_c.iter().map(|x| x.as_ptr()).collect();
         let enabled = if enabled { 1 } else { 0 };
         let loop_broadcast = if loop_broadcast { 1 } else { 0 };
-        if let Some(vec) = options {
-            opts_c = vec.into_iter()
-                .map(|x| CString::new(x).expect("Error: Unexpected null byte")).collect();
-            opts_c_ptr = opts_c.iter().map(|x| x.as_ptr()).collect();
-        } else {
-            opts_c_ptr = Vec::new();
-        }
         let result = unsafe {
             if opts_c_ptr.is_empty() {
                 sys::libvlc_vlm_add_broadcast(self.ptr, name.as_ptr(), input.as_ptr(), output.as_ptr(), 0, ptr::null(), enabled, loop_broadcast)
@@ -51,23 +94,20 @@ impl Vlm for Instance {
                 sys::libvlc_vlm_add_broadcast(self.ptr, name.as_ptr(), input.as_ptr(), output.as_ptr(), opts_c_ptr.len() as i32, opts_c_ptr.as_ptr(), enabled, loop_broadcast)
             }
         };
-        if result == 0 { Ok(()) } else { Err(()) }
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(())
+        }
     }
 
     fn add_vod(&self, name: &str, input: &str, mux: &str, options: Option<Vec<String>>, enabled: bool) -> Result<(), ()> {
         let name = to_cstr(name);
         let input = to_cstr(input);
         let mux = to_cstr(mux);
-        let opts_c_ptr: Vec<*const c_char>;
-        let opts_c: Vec<CString>;
+        let opts_c = options_to_cstr(options);
+        let opts_c_ptr: Vec<*const c_char> = opts_c.iter().map(|x| x.as_ptr()).collect();
         let enabled = if enabled { 1 } else { 0 };
-        if let Some(vec) = options {
-            opts_c = vec.into_iter()
-                .map(|x| CString::new(x).expect("Error: Unexpected null byte")).collect();
-            opts_c_ptr = opts_c.iter().map(|x| x.as_ptr()).collect();
-        } else {
-            opts_c_ptr = Vec::new();
-        }
         let result = unsafe {
             if opts_c_ptr.is_empty() {
                 sys::libvlc_vlm_add_vod(self.ptr, name.as_ptr(), input.as_ptr(), 0, ptr::null(), enabled, mux.as_ptr())
@@ -75,7 +115,11 @@ impl Vlm for Instance {
                 sys::libvlc_vlm_add_vod(self.ptr, name.as_ptr(), input.as_ptr(), opts_c_ptr.len() as i32, opts_c_ptr.as_ptr(), enabled, mux.as_ptr())
             }
         };
-        if result == 0 { Ok(()) } else { Err(()) }
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(())
+        }
     }
 
     fn play_media(&self, name: &str) -> Result<(), ()> {
@@ -126,6 +170,22 @@ impl Vlm for Instance {
         if result != -1 { Ok(result) } else { Err(()) }
     }
 
+    fn media_instance_length(&self, name: &str, instance: i32) -> Result<Duration, ()> {
+        let name = to_cstr(name);
+        let result = unsafe {
+            sys::libvlc_vlm_get_media_instance_length(self.ptr, name.as_ptr(), instance)
+        };
+        if result >= 0 { Ok(Duration::from_micros(result as u64)) } else { Err(()) }
+    }
+
+    fn media_instance_time(&self, name: &str, instance: i32) -> Result<Duration, ()> {
+        let name = to_cstr(name);
+        let result = unsafe {
+            sys::libvlc_vlm_get_media_instance_time(self.ptr, name.as_ptr(), instance)
+        };
+        if result >= 0 { Ok(Duration::from_micros(result as u64)) } else { Err(()) }
+    }
+
     fn get_media_instance_rate(&self, name: &str, instance: i32) -> Result<i32, ()> {
         let name = to_cstr(name);
         let result = unsafe {
@@ -137,12 +197,258 @@ impl Vlm for Instance {
     fn show_media(&self, name: &str) -> Result<String, ()> {
         let name = to_cstr(name);
         let result = unsafe {
-            from_cstr(sys::libvlc_vlm_show_media(self.ptr, name.as_ptr()))
+            let p = sys::libvlc_vlm_show_media(self.ptr, name.as_ptr());
+            let s = from_cstr(p);
+            sys::libvlc_free(p as *mut ::libc::c_void);
+            s
         };
         if let Some(data) = result {
-            Ok(data.to_string())
+            Ok(data)
+        } else {
+            Err(())
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn show_media_info(&self, name: &str) -> Result<VlmMediaInfo, ()> {
+        VlmMediaInfo::parse(&self.show_media(name)?).map_err(|_| ())
+    }
+
+    #[cfg(feature = "serde")]
+    fn list_media(&self) -> Vec<String> {
+        // An empty name shows every media.
+        self.show_media("").ok().and_then(|s| VlmMediaInfo::parse_names(&s).ok()).unwrap_or_default()
+    }
+
+    fn vlm_release(&self) {
+        unsafe {
+            sys::libvlc_vlm_release(self.ptr);
+        }
+    }
+
+    fn del_media(&self, name: &str) -> Result<(), ()> {
+        let name_c = to_cstr(name);
+        let result = unsafe {
+            sys::libvlc_vlm_del_media(self.ptr, name_c.as_ptr())
+        };
+        if result == 0 {
+            Ok(())
         } else {
             Err(())
         }
     }
+
+    fn set_enabled(&self, name: &str, enabled: bool) -> Result<(), ()> {
+        let name = to_cstr(name);
+        let enabled = if enabled { 1 } else { 0 };
+        let result = unsafe {
+            sys::libvlc_vlm_set_enabled(self.ptr, name.as_ptr(), enabled)
+        };
+        if result == 0 { Ok(()) } else { Err(()) }
+    }
+
+    fn set_output(&self, name: &str, output: &str) -> Result<(), ()> {
+        let name = to_cstr(name);
+        let output = to_cstr(output);
+        let result = unsafe {
+            sys::libvlc_vlm_set_output(self.ptr, name.as_ptr(), output.as_ptr())
+        };
+        if result == 0 { Ok(()) } else { Err(()) }
+    }
+
+    fn set_input(&self, name: &str, input: &str) -> Result<(), ()> {
+        let name = to_cstr(name);
+        let input = to_cstr(input);
+        let result = unsafe {
+            sys::libvlc_vlm_set_input(self.ptr, name.as_ptr(), input.as_ptr())
+        };
+        if result == 0 { Ok(()) } else { Err(()) }
+    }
+
+    fn add_input(&self, name: &str, input: &str) -> Result<(), ()> {
+        let name = to_cstr(name);
+        let input = to_cstr(input);
+        let result = unsafe {
+            sys::libvlc_vlm_add_input(self.ptr, name.as_ptr(), input.as_ptr())
+        };
+        if result == 0 { Ok(()) } else { Err(()) }
+    }
+
+    fn set_loop(&self, name: &str, loop_broadcast: bool) -> Result<(), ()> {
+        let name = to_cstr(name);
+        let loop_broadcast = if loop_broadcast { 1 } else { 0 };
+        let result = unsafe {
+            sys::libvlc_vlm_set_loop(self.ptr, name.as_ptr(), loop_broadcast)
+        };
+        if result == 0 { Ok(()) } else { Err(()) }
+    }
+
+    fn set_mux(&self, name: &str, mux: &str) -> Result<(), ()> {
+        let name = to_cstr(name);
+        let mux = to_cstr(mux);
+        let result = unsafe {
+            sys::libvlc_vlm_set_mux(self.ptr, name.as_ptr(), mux.as_ptr())
+        };
+        if result == 0 { Ok(()) } else { Err(()) }
+    }
+
+    fn change_media(&self, name: &str, input: &str, output: &str, options: Option<Vec<String>>, enabled: bool, loop_broadcast: bool) -> Result<(), ()> {
+        let name = to_cstr(name);
+        let input = to_cstr(input);
+        let output = to_cstr(output);
+        let opts_c = options_to_cstr(options);
+        let opts_c_ptr: Vec<*const c_char> = opts_c.iter().map(|x| x.as_ptr()).collect();
+        let enabled = if enabled { 1 } else { 0 };
+        let loop_broadcast = if loop_broadcast { 1 } else { 0 };
+        let result = unsafe {
+            if opts_c_ptr.is_empty() {
+                sys::libvlc_vlm_change_media(self.ptr, name.as_ptr(), input.as_ptr(), output.as_ptr(), 0, ptr::null(), enabled, loop_broadcast)
+            } else {
+                sys::libvlc_vlm_change_media(self.ptr, name.as_ptr(), input.as_ptr(), output.as_ptr(), opts_c_ptr.len() as i32, opts_c_ptr.as_ptr(), enabled, loop_broadcast)
+            }
+        };
+        if result == 0 { Ok(()) } else { Err(()) }
+    }
+
+    fn seek_media(&self, name: &str, percentage: f32) -> Result<(), ()> {
+        let name = to_cstr(name);
+        let result = unsafe {
+            sys::libvlc_vlm_seek_media(self.ptr, name.as_ptr(), percentage)
+        };
+        if result == 0 { Ok(()) } else { Err(()) }
+    }
+}
+
+// Convert VLM media options to C strings.
+// Panic if an option includes null bytes.
+fn options_to_cstr(options: Option<Vec<String>>) -> Vec<CString> {
+    options.unwrap_or_default().into_iter()
+        .map(|x| CString::new(x).expect("Error: Unexpected null byte")).collect()
+}
+
+/// A named VLM media (broadcast or VOD) configured on an [Instance].
+///
+/// A `VlmMedia` created with [VlmMedia::new_broadcast] or [VlmMedia::new_vod] owns the media
+/// and deletes it from the VLM when dropped. One obtained with [VlmMedia::from_name] only
+/// refers to an existing media and leaves it in place.
+pub struct VlmMedia {
+    instance: Instance,
+    name: String,
+    owned: bool,
+}
+
+impl VlmMedia {
+    /// Add a broadcast, with one input, and take ownership of it.
+    pub fn new_broadcast(instance: &Instance, name: &str, input: &str, output: &str, options: Option<Vec<String>>, enabled: bool, loop_broadcast: bool) -> Result<VlmMedia, ()> {
+        instance.add_broadcast(name, input, output, options, enabled, loop_broadcast)?;
+        Ok(VlmMedia { instance: instance.clone(), name: name.to_owned(), owned: true })
+    }
+
+    /// Add a VOD, with one input, and take ownership of it.
+    pub fn new_vod(instance: &Instance, name: &str, input: &str, mux: &str, options: Option<Vec<String>>, enabled: bool) -> Result<VlmMedia, ()> {
+        instance.add_vod(name, input, mux, options, enabled)?;
+        Ok(VlmMedia { instance: instance.clone(), name: name.to_owned(), owned: true })
+    }
+
+    /// Refer to an already configured media without taking ownership of it.
+    pub fn from_name(instance: &Instance, name: &str) -> VlmMedia {
+        VlmMedia { instance: instance.clone(), name: name.to_owned(), owned: false }
+    }
+
+    /// The name of the media.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the media is deleted when this handle is dropped.
+    pub fn is_owned(&self) -> bool {
+        self.owned
+    }
+
+    /// Give up ownership, leaving the media configured, and return its name.
+    pub fn into_name(mut self) -> String {
+        self.owned = false;
+        ::std::mem::take(&mut self.name)
+    }
+
+    /// Delete the media now.
+    pub fn delete(mut self) -> Result<(), ()> {
+        self.owned = false;
+        self.instance.del_media(&self.name)
+    }
+
+    pub fn play(&self) -> Result<(), ()> {
+        self.instance.play_media(&self.name)
+    }
+
+    pub fn pause(&self) -> Result<(), ()> {
+        self.instance.pause_media(&self.name)
+    }
+
+    pub fn stop(&self) -> Result<(), ()> {
+        self.instance.stop_media(&self.name)
+    }
+
+    /// Seek, `percentage` being between 0.0 and 100.0.
+    pub fn seek(&self, percentage: f32) -> Result<(), ()> {
+        self.instance.seek_media(&self.name, percentage)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) -> Result<(), ()> {
+        self.instance.set_enabled(&self.name, enabled)
+    }
+
+    pub fn set_output(&self, output: &str) -> Result<(), ()> {
+        self.instance.set_output(&self.name, output)
+    }
+
+    pub fn set_input(&self, input: &str) -> Result<(), ()> {
+        self.instance.set_input(&self.name, input)
+    }
+
+    pub fn add_input(&self, input: &str) -> Result<(), ()> {
+        self.instance.add_input(&self.name, input)
+    }
+
+    pub fn set_loop(&self, loop_broadcast: bool) -> Result<(), ()> {
+        self.instance.set_loop(&self.name, loop_broadcast)
+    }
+
+    pub fn set_mux(&self, mux: &str) -> Result<(), ()> {
+        self.instance.set_mux(&self.name, mux)
+    }
+
+    pub fn change(&self, input: &str, output: &str, options: Option<Vec<String>>, enabled: bool, loop_broadcast: bool) -> Result<(), ()> {
+        self.instance.change_media(&self.name, input, output, options, enabled, loop_broadcast)
+    }
+
+    pub fn show(&self) -> Result<String, ()> {
+        self.instance.show_media(&self.name)
+    }
+
+    pub fn instance_position(&self, instance: i32) -> Result<f32, ()> {
+        self.instance.get_media_instance_position(&self.name, instance)
+    }
+
+    /// Length of an instance, see [Vlm::media_instance_length].
+    pub fn instance_length(&self, instance: i32) -> Result<Duration, ()> {
+        self.instance.media_instance_length(&self.name, instance)
+    }
+
+    /// Time of an instance, see [Vlm::media_instance_length].
+    pub fn instance_time(&self, instance: i32) -> Result<Duration, ()> {
+        self.instance.media_instance_time(&self.name, instance)
+    }
+
+    pub fn instance_rate(&self, instance: i32) -> Result<i32, ()> {
+        self.instance.get_media_instance_rate(&self.name, instance)
+    }
+}
+
+impl Drop for VlmMedia {
+    fn drop(&mut self) {
+        if self.owned {
+            let _ = self.instance.del_media(&self.name);
+        }
+    }
 }
@@ -0,0 +1,255 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::fmt;
+
+use serde::de::{self, Deserializer, MapAccess, Visitor};
+use serde::{Deserialize, Serialize};
+
+/// Kind of a VLM media.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VlmMediaType {
+    Broadcast,
+    Vod,
+}
+
+/// Playback state of a running VLM media instance.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VlmInstanceState {
+    Playing,
+    Paused,
+    Stopped,
+}
+
+/// A running instance of a VLM media.
+/// The input values are only present while the instance has an input.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct VlmInstanceInfo {
+    pub name: String,
+    pub state: VlmInstanceState,
+    pub position: Option<f32>,
+    /// Current time in microseconds.
+    pub time: Option<i64>,
+    /// Length in microseconds.
+    pub length: Option<i64>,
+    pub rate: Option<f32>,
+    pub title: Option<i64>,
+    pub chapter: Option<i64>,
+    pub can_seek: Option<bool>,
+    /// One-based index of the input being played.
+    pub playlist_index: Option<i32>,
+}
+
+/// Configuration and status of a VLM media, as returned by `Vlm::show_media_info`.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct VlmMediaInfo {
+    pub media_type: VlmMediaType,
+    pub enabled: bool,
+    /// Only meaningful for broadcasts.
+    pub loop_broadcast: bool,
+    /// Only set for VOD.
+    pub mux: Option<String>,
+    pub inputs: Vec<String>,
+    pub output: Option<String>,
+    /// libvlc 3 does not print option values, so this is usually empty even when options are set.
+    pub options: Vec<String>,
+    pub instances: Vec<VlmInstanceInfo>,
+}
+
+impl VlmMediaInfo {
+    /// Parse the string returned by `Vlm::show_media` for a single media.
+    pub fn parse(s: &str) -> Result<VlmMediaInfo, serde_json::Error> {
+        let raw: RawMedia = serde_json::from_str(&escape_values(s))?;
+        raw.into_info()
+    }
+
+    /// Parse the names of the media in the listing returned by `Vlm::show_media("")`.
+    pub fn parse_names(s: &str) -> Result<Vec<String>, serde_json::Error> {
+        let raw: RawListing = serde_json::from_str(&escape_values(s))?;
+        if !raw.media.is_array() {
+            return Ok(Vec::new());
+        }
+        let media: Vec<RawName> = serde_json::from_value(raw.media)?;
+        Ok(media.into_iter().map(|m| m.name).collect())
+    }
+}
+
+// libvlc writes the values verbatim between quotes, so sout chains with quotes or
+// Windows paths produce invalid JSON. Re-escape every "key": "value" line.
+fn escape_values(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for line in s.lines() {
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let body = line.trim_start();
+        let (body, sep) = match body.strip_suffix(',') {
+            Some(b) => (b, ","),
+            None => (body.trim_end(), ""),
+        };
+
+        let (key, value) = match body.find("\": \"") {
+            Some(i) if body.starts_with('"') => (&body[..i + 3], &body[i + 3..]),
+            _ if body.starts_with('"') => ("", body),
+            _ => {
+                out.push_str(line);
+                out.push('\n');
+                continue;
+            }
+        };
+
+        match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+            Some(v) => {
+                out.push_str(indent);
+                out.push_str(key);
+                out.push('"');
+                for c in v.chars() {
+                    match c {
+                        '"' => out.push_str("\\\""),
+                        '\\' => out.push_str("\\\\"),
+                        '\t' => out.push_str("\\t"),
+                        c => out.push(c),
+                    }
+                }
+                out.push('"');
+                out.push_str(sep);
+            }
+            None => out.push_str(line),
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[derive(Deserialize)]
+struct RawMedia {
+    #[serde(rename = "type")]
+    media_type: VlmMediaType,
+    enabled: String,
+    #[serde(rename = "loop")]
+    loop_broadcast: Option<String>,
+    mux: Option<String>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    inputs: Vec<String>,
+    output: Option<String>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    options: Vec<serde_json::Value>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    instances: RawInstances,
+}
+
+// libvlc prints a node without children as `null`, e.g. `"instances": null` when nothing runs.
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where D: Deserializer<'de>, T: Deserialize<'de> + Default
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+// The listing of every media. libvlc prints "media" as a list of objects with a "name",
+// or as a count like "( 0 broadcast - 0 vod )" when there is none.
+#[derive(Deserialize)]
+struct RawListing {
+    media: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct RawName {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct RawInstance {
+    name: String,
+    state: VlmInstanceState,
+    position: Option<String>,
+    time: Option<String>,
+    length: Option<String>,
+    rate: Option<String>,
+    title: Option<String>,
+    chapter: Option<String>,
+    #[serde(rename = "can-seek")]
+    can_seek: Option<String>,
+    playlistindex: Option<String>,
+}
+
+// libvlc prints instances as an object with a repeated "instance" key.
+#[derive(Default)]
+struct RawInstances(Vec<RawInstance>);
+
+impl<'de> Deserialize<'de> for RawInstances {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct InstancesVisitor;
+
+        impl<'de> Visitor<'de> for InstancesVisitor {
+            type Value = RawInstances;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of VLM media instances")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<RawInstances, A::Error> {
+                let mut instances = Vec::new();
+                while let Some((_, instance)) = map.next_entry::<de::IgnoredAny, RawInstance>()? {
+                    instances.push(instance);
+                }
+                Ok(RawInstances(instances))
+            }
+        }
+
+        deserializer.deserialize_map(InstancesVisitor)
+    }
+}
+
+impl RawMedia {
+    fn into_info(self) -> Result<VlmMediaInfo, serde_json::Error> {
+        Ok(VlmMediaInfo {
+            media_type: self.media_type,
+            enabled: parse_bool(&self.enabled)?,
+            loop_broadcast: self.loop_broadcast.as_deref().map(parse_bool).transpose()?.unwrap_or(false),
+            mux: self.mux,
+            inputs: self.inputs,
+            output: self.output.filter(|o| !o.is_empty()),
+            options: self.options.into_iter()
+                .filter_map(|o| o.as_str().map(str::to_owned))
+                .collect(),
+            instances: self.instances.0.into_iter()
+                .map(RawInstance::into_info)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl RawInstance {
+    fn into_info(self) -> Result<VlmInstanceInfo, serde_json::Error> {
+        Ok(VlmInstanceInfo {
+            name: self.name,
+            state: self.state,
+            position: parse_opt(self.position)?,
+            time: parse_opt(self.time)?,
+            length: parse_opt(self.length)?,
+            rate: parse_opt(self.rate)?,
+            title: parse_opt(self.title)?,
+            chapter: parse_opt(self.chapter)?,
+            can_seek: self.can_seek.map(|b| b != "0"),
+            playlist_index: parse_opt(self.playlistindex)?,
+        })
+    }
+}
+
+fn parse_bool(s: &str) -> Result<bool, serde_json::Error> {
+    match s {
+        "yes" => Ok(true),
+        "no" => Ok(false),
+        _ => Err(de::Error::invalid_value(de::Unexpected::Str(s), &"\"yes\" or \"no\"")),
+    }
+}
+
+fn parse_opt<T: std::str::FromStr>(s: Option<String>) -> Result<Option<T>, serde_json::Error> {
+    match s {
+        Some(s) => s.trim().parse()
+            .map(Some)
+            .map_err(|_| de::Error::invalid_value(de::Unexpected::Str(&s), &"a number")),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+#![cfg(feature = "mock")]
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use vlc::mock::{MockMedia, MockMediaPlayer};
+use vlc::{AbLoop, MediaPlayerApi};
+
+// The loop seeks from a background thread.
+fn wait_for_time(player: &MockMediaPlayer, below: Duration) {
+    let start = Instant::now();
+    while !player.time().is_some_and(|t| t < below) {
+        assert!(start.elapsed() < Duration::from_secs(5), "no seek back, time {:?}", player.time());
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn ab_loop() {
+    let player = MockMediaPlayer::new();
+    player.set_media(&MockMedia::new("file:///take.mp4", Some(Duration::from_secs(60))));
+    player.play().unwrap();
+    player.advance(Duration::ZERO);
+
+    assert!(AbLoop::new(&player, Duration::from_secs(5), Duration::from_secs(5)).is_err());
+    let ab = AbLoop::new(&player, Duration::from_secs(10), Duration::from_secs(12)).unwrap();
+
+    // Before A, playback goes on until B.
+    player.advance(Duration::from_secs(11));
+    assert_eq!(player.time(), Some(Duration::from_secs(11)));
+    player.advance(Duration::from_millis(1500));
+    wait_for_time(&player, Duration::from_secs(11));
+    assert_eq!(player.time(), Some(Duration::from_secs(10)));
+
+    ab.set_points(Duration::from_secs(20), Duration::from_secs(21)).unwrap();
+    assert!(ab.set_points(Duration::from_secs(2), Duration::from_secs(1)).is_err());
+    assert_eq!(ab.points(), (Duration::from_secs(20), Duration::from_secs(21)));
+    player.advance(Duration::from_secs(12));
+    wait_for_time(&player, Duration::from_secs(21));
+    assert_eq!(player.time(), Some(Duration::from_secs(20)));
+
+    drop(ab);
+    player.advance(Duration::from_secs(2));
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(player.time(), Some(Duration::from_secs(22)));
+}
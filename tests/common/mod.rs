@@ -0,0 +1,110 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+// Shared helpers of the integration tests: an instance with dummy outputs and
+// media fixtures generated on the fly.
+
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+use vlc::Instance;
+
+/// How long to wait for libvlc to report something before failing.
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Arguments keeping libvlc off the sound card and the display.
+pub fn args() -> Vec<String> {
+    ["--aout=dummy", "--vout=dummy", "--no-video-title-show", "--quiet"]
+        .iter().map(|s| s.to_string()).collect()
+}
+
+/// A new instance with dummy outputs.
+/// Returns None, so that the test is skipped, if libvlc could not be loaded with the `dynamic` feature.
+pub fn instance() -> Option<Instance> {
+    #[cfg(feature = "dynamic")]
+    {
+        if vlc::load_library::<&str>(&[]).is_err() {
+            eprintln!("libvlc not found, skipping");
+            return None;
+        }
+    }
+    Some(Instance::with_args(Some(args())).expect("failed to create an instance"))
+}
+
+/// Wait for the next message, failing the test after [TIMEOUT].
+pub fn recv<T>(rx: &Receiver<T>) -> T {
+    rx.recv_timeout(TIMEOUT).expect("timed out waiting for libvlc")
+}
+
+/// A directory removed with its content when dropped.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new() -> TempDir {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "vlc-rs-test-{}-{}", process::id(), COUNT.fetch_add(1, Ordering::SeqCst)));
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    pub fn join<P: AsRef<Path>>(&self, name: P) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Write a mono 16-bit PCM WAV file with a 440 Hz tone.
+pub fn write_wav<P: AsRef<Path>>(path: P, duration_ms: u32, rate: u32) {
+    let samples = rate * duration_ms / 1000;
+    let data_len = samples * 2;
+
+    let mut buf = Vec::with_capacity(44 + data_len as usize);
+    buf.extend_from_slice(b"RIFF");
+    buf.extend_from_slice(&(36 + data_len).to_le_bytes());
+    buf.extend_from_slice(b"WAVEfmt ");
+    buf.extend_from_slice(&16u32.to_le_bytes());
+    buf.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    buf.extend_from_slice(&1u16.to_le_bytes()); // channels
+    buf.extend_from_slice(&rate.to_le_bytes());
+    buf.extend_from_slice(&(rate * 2).to_le_bytes()); // byte rate
+    buf.extend_from_slice(&2u16.to_le_bytes()); // block align
+    buf.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+    buf.extend_from_slice(b"data");
+    buf.extend_from_slice(&data_len.to_le_bytes());
+    for i in 0..samples {
+        let t = i as f64 / rate as f64;
+        let v = ((t * 440.0 * 2.0 * std::f64::consts::PI).sin() * 8000.0) as i16;
+        buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fs::write(path, buf).unwrap();
+}
+
+/// Write a YUV4MPEG2 (4:2:0) file of `frames` frames shifting from black to white.
+pub fn write_y4m<P: AsRef<Path>>(path: P, width: u32, height: u32, frames: u32, fps: u32) {
+    let luma = (width * height) as usize;
+    let chroma = ((width / 2) * (height / 2)) as usize;
+
+    let mut buf = format!("YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C420jpeg\n", width, height, fps).into_bytes();
+    for i in 0..frames {
+        buf.extend_from_slice(b"FRAME\n");
+        let y = (i * 255 / frames.max(1)) as u8;
+        buf.extend(std::iter::repeat(y).take(luma));
+        buf.extend(std::iter::repeat(128u8).take(chroma * 2));
+    }
+
+    fs::write(path, buf).unwrap();
+}
@@ -0,0 +1,121 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+#![cfg(feature = "mock")]
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use vlc::mock::{MockMedia, MockMediaList, MockMediaPlayer};
+use vlc::{CrossfadePlayer, FadeCurve, MediaApi, MediaListApi, MediaPlayerApi, MediaPlayerAudioEx, State};
+
+fn setup(durations: &[u64]) -> (MockMediaPlayer, MockMediaPlayer, CrossfadePlayer<MockMediaPlayer, MockMediaList>) {
+    let list = MockMediaList::new();
+    for (i, &secs) in durations.iter().enumerate() {
+        list.add_media(&MockMedia::new(&format!("file:///{}.ogg", i), Some(Duration::from_secs(secs)))).unwrap();
+    }
+    let (a, b) = (MockMediaPlayer::new(), MockMediaPlayer::new());
+    let player = CrossfadePlayer::with_players(a.clone(), b.clone());
+    player.set_media_list(&list);
+    (a, b, player)
+}
+
+// The players are driven from a background thread.
+fn wait_until<F: Fn() -> bool>(what: &str, f: F) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !f() {
+        assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+fn mrl(player: &MockMediaPlayer) -> Option<String> {
+    player.get_media().and_then(|md| md.mrl())
+}
+
+#[test]
+fn curves() {
+    for &curve in &[FadeCurve::Linear, FadeCurve::EqualPower, FadeCurve::SCurve] {
+        assert_eq!(curve.gain(0.0), 0.0);
+        assert!((curve.gain(1.0) - 1.0).abs() < 1e-6);
+        assert!(curve.gain(0.25) < curve.gain(0.75));
+    }
+    let half = FadeCurve::EqualPower.gain(0.5);
+    assert!((half * half * 2.0 - 1.0).abs() < 1e-4);
+    assert_eq!(FadeCurve::Linear.gain(2.0), 1.0);
+}
+
+#[test]
+fn crossfade() {
+    let (a, b, player) = setup(&[10, 10]);
+    player.set_fade(Duration::from_secs(3));
+    player.set_preroll(Duration::from_secs(2));
+    player.play();
+    a.advance(Duration::ZERO);
+    assert_eq!(mrl(&a).as_deref(), Some("file:///0.ogg"));
+
+    a.advance(Duration::from_millis(5500));
+    wait_until("the next item to open", || b.state() == State::Opening);
+    assert_eq!(mrl(&b).as_deref(), Some("file:///1.ogg"));
+    assert_eq!(b.get_volume(), 0);
+    b.advance(Duration::from_millis(300));
+    wait_until("the next item to be paused", || b.state() == State::Paused);
+
+    a.advance(Duration::from_secs(2));
+    wait_until("the fade to start", || b.state() == State::Playing && b.get_volume() > 0);
+    assert_eq!(b.time(), Some(Duration::ZERO));
+    wait_until("the volumes to ramp", || a.get_volume() < 100);
+    assert!(b.get_volume() < a.get_volume());
+    assert_eq!(player.current_index(), Some(0));
+
+    a.advance(Duration::from_secs(3));
+    wait_until("the next item to take over", || player.current_index() == Some(1));
+    assert_eq!(b.get_volume(), 100);
+    assert_eq!(mrl(&player.get_media_player().unwrap()).as_deref(), Some("file:///1.ogg"));
+
+    b.advance(Duration::from_secs(20));
+    wait_until("the end of the list", || !player.is_playing() && a.state() == State::Stopped);
+    assert_eq!(player.current_index(), Some(1));
+}
+
+#[test]
+fn controls() {
+    let (a, b, player) = setup(&[10, 10, 10]);
+    player.set_fade(Duration::ZERO);
+    player.set_preroll(Duration::from_secs(1));
+    player.play();
+    a.advance(Duration::ZERO);
+    player.next().unwrap();
+    assert_eq!(player.current_index(), Some(1));
+    assert_eq!(mrl(&a).as_deref(), Some("file:///1.ogg"));
+    player.previous().unwrap();
+    assert!(player.previous().is_err());
+    player.play_item_at_index(2).unwrap();
+    assert!(player.next().is_err());
+    a.advance(Duration::ZERO);
+
+    player.set_pause(true);
+    assert_eq!(a.state(), State::Paused);
+    assert!(!player.is_playing());
+    player.play();
+    assert_eq!(a.state(), State::Playing);
+
+    // Without a fade, the next item is opened beforehand and starts at the end.
+    player.set_volume(50).unwrap();
+    assert!(player.set_volume(300).is_err());
+    player.play_item_at_index(0).unwrap();
+    a.advance(Duration::ZERO);
+    a.advance(Duration::from_millis(9500));
+    wait_until("the next item to open", || b.state() == State::Opening);
+    b.advance(Duration::ZERO);
+    wait_until("the next item to be paused", || b.state() == State::Paused);
+    a.advance(Duration::from_secs(1));
+    wait_until("the next item to start", || b.state() == State::Playing);
+    assert_eq!(b.get_volume(), 50);
+    assert_eq!(player.current_index(), Some(1));
+
+    player.stop();
+    assert_eq!(b.state(), State::Stopped);
+    assert!(!player.is_playing());
+}
@@ -0,0 +1,26 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use vlc::FourCC;
+
+#[test]
+fn display() {
+    assert_eq!(FourCC(875967080).to_string(), "h264");
+    assert_eq!(FourCC::new(*b"mp3 ").to_string(), "mp3");
+    assert_eq!(FourCC(0).to_string(), "0x00000000");
+}
+
+#[test]
+fn from_str() {
+    assert_eq!("h264".parse(), Ok(FourCC::H264));
+    assert_eq!("mp3".parse(), Ok(FourCC::new(*b"mp3 ")));
+    assert_eq!("0x00000000".parse(), Ok(FourCC(0)));
+    assert_eq!("".parse::<FourCC>(), Err(()));
+    assert_eq!("h2645".parse::<FourCC>(), Err(()));
+    assert_eq!("h\u{e9}".parse::<FourCC>(), Err(()));
+
+    for codec in &[FourCC::H264, FourCC::HEVC, FourCC::VP9, FourCC::AV1, FourCC::AAC, FourCC::OPUS, FourCC::MP3, FourCC::FLAC] {
+        assert_eq!(codec.to_string().parse(), Ok(*codec));
+    }
+}
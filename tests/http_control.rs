@@ -0,0 +1,134 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+#![cfg(feature = "http-control")]
+
+mod common;
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use vlc::http_control::{ControlServer, Controls};
+use vlc::{MediaList, MediaListPlayer, MediaPlayer};
+
+use common::TempDir;
+
+fn request(addr: SocketAddr, method: &str, path: &str, body: Option<Value>) -> (u16, Value) {
+    let body = body.map(|b| b.to_string()).unwrap_or_default();
+    let headers = format!("Host: localhost:{}\r\nContent-Type: application/json\r\n", addr.port());
+    raw_request(addr, method, path, &headers, &body)
+}
+
+fn raw_request(addr: SocketAddr, method: &str, path: &str, headers: &str, body: &str) -> (u16, Value) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "{} {} HTTP/1.1\r\n{}Content-Length: {}\r\n\r\n{}", method, path, headers, body.len(), body).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let status = response[9..12].parse().unwrap();
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+    (status, serde_json::from_str(body).unwrap())
+}
+
+#[test]
+fn control() {
+    let Some(instance) = common::instance() else { return };
+    let dir = TempDir::new();
+    common::write_wav(dir.join("a.wav"), 3000, 8000);
+
+    let list = MediaList::new(&instance).unwrap();
+    let list_player = MediaListPlayer::new(&instance).unwrap();
+    list_player.set_media_list(&list);
+    let player = list_player.get_media_player().unwrap();
+    let server = ControlServer::localhost(0, Controls::new(&instance, &player).with_playlist(&list_player, &list)).unwrap();
+    let addr = server.local_addr();
+
+    let (status, body) = request(addr, "POST", "/playlist", Some(json!({ "path": dir.join("a.wav") })));
+    assert_eq!(status, 201);
+    assert_eq!(body["index"], 0);
+    let (status, body) = request(addr, "POST", "/playlist", Some(json!({ "mrl": "http://example.com/radio", "index": 0 })));
+    assert_eq!(status, 201);
+    assert_eq!(body["index"], 0);
+    let (_, items) = request(addr, "GET", "/playlist", None);
+    assert_eq!(items.as_array().unwrap().len(), 2);
+    assert!(items[1]["mrl"].as_str().unwrap().ends_with("/a.wav"), "{}", items);
+    assert_eq!(request(addr, "DELETE", "/playlist/0", None).0, 200);
+    assert_eq!(request(addr, "GET", "/playlist/1", None).0, 404);
+
+    let (status, body) = request(addr, "GET", "/status", None);
+    assert_eq!(status, 200);
+    assert_eq!(body["state"], "nothing_special");
+    assert!(body["tracks"]["audio"].is_object());
+
+    // The event stream reports the playback started.
+    let mut events = TcpStream::connect(addr).unwrap();
+    events.set_read_timeout(Some(common::TIMEOUT)).unwrap();
+    write!(events, "GET /events HTTP/1.1\r\nHost: {}\r\n\r\n", addr).unwrap();
+    let mut events = BufReader::new(events);
+    let mut line = String::new();
+    events.read_line(&mut line).unwrap();
+    assert!(line.starts_with("HTTP/1.1 200"), "{}", line);
+
+    assert_eq!(request(addr, "POST", "/playlist/0/play", None).0, 200);
+    loop {
+        line.clear();
+        events.read_line(&mut line).unwrap();
+        if line.contains("\"media_player_playing\"") {
+            break;
+        }
+    }
+    assert_eq!(request(addr, "POST", "/volume", Some(json!({ "volume": 50 }))).0, 200);
+    assert_eq!(request(addr, "POST", "/seek", Some(json!({ "time": 1000 }))).0, 200);
+    assert_eq!(request(addr, "POST", "/seek", Some(json!({ "at": 1 }))).0, 400);
+    assert_eq!(request(addr, "GET", "/pause", None).0, 405);
+    assert_eq!(request(addr, "GET", "/nowhere", None).0, 404);
+    assert_eq!(request(addr, "POST", "/stop", None).0, 200);
+
+    server.stop();
+    assert!(TcpStream::connect_timeout(&addr, Duration::from_millis(500)).is_err());
+}
+
+fn server() -> Option<ControlServer> {
+    let instance = common::instance()?;
+    let player = MediaPlayer::new(&instance).unwrap();
+    Some(ControlServer::localhost(0, Controls::new(&instance, &player)).unwrap())
+}
+
+#[test]
+fn rejects_foreign_host() {
+    let Some(server) = server() else { return };
+    let addr = server.local_addr();
+    let port = addr.port();
+    for host in [format!("127.0.0.1:{}", port), format!("LOCALHOST:{}", port), "localhost".to_owned()] {
+        assert_eq!(raw_request(addr, "GET", "/status", &format!("Host: {}\r\n", host), "").0, 200, "{}", host);
+    }
+    // A page on a name rebound to the loopback address sends its own name.
+    for host in [format!("evil.example:{}", port), format!("localhost:{}", port + 1), "[::1]".to_owned()] {
+        assert_eq!(raw_request(addr, "GET", "/status", &format!("Host: {}\r\n", host), "").0, 403, "{}", host);
+    }
+    assert_eq!(raw_request(addr, "GET", "/status", "", "").0, 403);
+}
+
+#[test]
+fn rejects_cross_origin() {
+    let Some(server) = server() else { return };
+    let addr = server.local_addr();
+    let headers = |origin: &str| format!("Host: {}\r\nOrigin: {}\r\n", addr, origin);
+    assert_eq!(raw_request(addr, "POST", "/stop", &headers("http://evil.example"), "").0, 403);
+    assert_eq!(raw_request(addr, "POST", "/stop", &headers("null"), "").0, 403);
+    assert_eq!(raw_request(addr, "POST", "/stop", &headers(&format!("http://{}", addr)), "").0, 200);
+}
+
+#[test]
+fn rejects_non_json_body() {
+    let Some(server) = server() else { return };
+    let addr = server.local_addr();
+    let body = json!({ "time": 1000 }).to_string();
+    let host = format!("Host: {}\r\n", addr);
+    assert_eq!(raw_request(addr, "POST", "/seek", &format!("{}Content-Type: text/plain\r\n", host), &body).0, 415);
+    assert_eq!(raw_request(addr, "POST", "/seek", &host, &body).0, 415);
+    let json_type = format!("{}Content-Type: application/json; charset=utf-8\r\n", host);
+    assert_eq!(raw_request(addr, "POST", "/seek", &json_type, &body).0, 200);
+}
@@ -0,0 +1,41 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+mod common;
+
+use vlc::Instance;
+
+#[test]
+fn create_with_args() {
+    let Some(instance) = common::instance() else { return };
+    let _clone = instance.clone();
+
+    // libvlc refuses unknown options.
+    assert!(Instance::with_args(Some(vec!["--no-such-option-for-vlc-rs".to_owned()])).is_none());
+}
+
+#[test]
+fn version_info() {
+    let Some(_instance) = common::instance() else { return };
+
+    assert!(!vlc::version().is_empty());
+    let info = vlc::version_info().expect("unparsable libvlc version");
+    assert!(info.at_least(2, 0, 0));
+    #[cfg(feature = "libvlc4")]
+    assert_eq!(info.major, 4);
+    #[cfg(not(feature = "libvlc4"))]
+    assert!(info.major < 4);
+}
+
+#[cfg(feature = "dynamic")]
+#[test]
+fn missing_library() {
+    // Only meaningful where libvlc is not installed.
+    if vlc::load_library::<&str>(&[]).is_ok() { return }
+
+    assert!(matches!(vlc::try_version(), Err(vlc::Error::LibraryNotFound(_))));
+    assert!(vlc::version().is_empty());
+    assert!(vlc::version_info().is_none());
+    assert!(matches!(Instance::try_with_args(None), Err(vlc::Error::LibraryNotFound(_))));
+}
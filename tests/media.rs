@@ -0,0 +1,197 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+mod common;
+
+use std::path::Path;
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+use vlc::{Event, EventType, Instance, Media, MediaTrackUnion, MediaType, Meta, ParseFlag, ParsedStatus, TrackType};
+
+use common::TempDir;
+
+fn parse(instance: &Instance, path: &Path) -> Media {
+    let md = Media::new_path(instance, path).unwrap();
+    let (tx, rx) = channel();
+    md.event_manager().attach(EventType::MediaParsedChanged, move |_, _| {
+        let _ = tx.send(());
+    }).unwrap();
+    md.parse_request(instance, &[ParseFlag::ParseLocal], -1).unwrap();
+    common::recv(&rx);
+    assert_eq!(md.parsed_status(), Some(ParsedStatus::Done));
+    md
+}
+
+#[test]
+fn parse_wav() {
+    let Some(instance) = common::instance() else { return };
+    let dir = TempDir::new();
+    let path = dir.join("tone.wav");
+    common::write_wav(&path, 1000, 8000);
+
+    let md = parse(&instance, &path);
+    let length = md.length().unwrap();
+    assert!(length > Duration::from_millis(900) && length < Duration::from_millis(1100), "length {:?}", length);
+
+    let tracks = md.tracks().unwrap();
+    assert_eq!(tracks.len(), 1);
+    assert_eq!(tracks[0].track_type, TrackType::Audio);
+    assert_eq!(tracks[0].codec_fourcc().to_string(), "s16l");
+    #[cfg(vlc_3_0)]
+    assert!(tracks[0].codec_description().is_some());
+    match tracks[0].type_specific_data {
+        MediaTrackUnion::Audio(ref audio) => {
+            assert_eq!(audio.channels, 1);
+            assert_eq!(audio.rate, 8000);
+        },
+        ref other => panic!("not an audio track: {:?}", other),
+    }
+}
+
+#[test]
+fn parse_y4m() {
+    let Some(instance) = common::instance() else { return };
+    let dir = TempDir::new();
+    let path = dir.join("frames.y4m");
+    common::write_y4m(&path, 32, 24, 10, 10);
+
+    let md = parse(&instance, &path);
+    let tracks = md.tracks().unwrap();
+    let video = tracks.iter().find(|t| t.track_type == TrackType::Video).expect("no video track");
+    match video.type_specific_data {
+        MediaTrackUnion::Video(ref video) => {
+            assert_eq!(video.width, 32);
+            assert_eq!(video.height, 24);
+        },
+        ref other => panic!("not a video track: {:?}", other),
+    }
+}
+
+#[test]
+fn meta_round_trip() {
+    let Some(instance) = common::instance() else { return };
+    let dir = TempDir::new();
+    let path = dir.join("meta.wav");
+    common::write_wav(&path, 500, 8000);
+
+    let md = parse(&instance, &path);
+    md.set_meta(Meta::Title, "vlc-rs title");
+    md.set_meta(Meta::Artist, "vlc-rs artist");
+    assert_eq!(md.get_meta(Meta::Title).as_deref(), Some("vlc-rs title"));
+    assert_eq!(md.get_meta(Meta::Artist).as_deref(), Some("vlc-rs artist"));
+
+    // Writing depends on the taglib module being available.
+    if md.save_meta() {
+        drop(md);
+        let md = parse(&instance, &path);
+        assert_eq!(md.get_meta(Meta::Title).as_deref(), Some("vlc-rs title"));
+        assert_eq!(md.get_meta(Meta::Artist).as_deref(), Some("vlc-rs artist"));
+    }
+}
+
+#[test]
+fn playlist_subitems() {
+    let Some(instance) = common::instance() else { return };
+    let dir = TempDir::new();
+    common::write_wav(dir.join("a.wav"), 200, 8000);
+    common::write_wav(dir.join("b.wav"), 200, 8000);
+    std::fs::write(dir.join("list.m3u"), "#EXTM3U\na.wav\nb.wav\n").unwrap();
+
+    let md = parse(&instance, &dir.join("list.m3u"));
+    let list = md.subitems().unwrap();
+    assert_eq!(list.count(), 2);
+    let mrl = list.item_at_index(1).unwrap().mrl().unwrap();
+    assert!(mrl.ends_with("b.wav"), "mrl {}", mrl);
+}
+
+#[test]
+fn expand_nested_playlists() {
+    let Some(instance) = common::instance() else { return };
+    let dir = TempDir::new();
+    for name in &["a.wav", "b.wav", "c.wav"] {
+        common::write_wav(dir.join(name), 200, 8000);
+    }
+    std::fs::write(dir.join("inner.m3u"), "c.wav\n").unwrap();
+    std::fs::write(dir.join("list.m3u"), "a.wav\ninner.m3u\nb.wav\n").unwrap();
+
+    let names = |items: Vec<Media>| -> Vec<String> {
+        items.iter().map(|md| md.mrl().unwrap().rsplit('/').next().unwrap().to_owned()).collect()
+    };
+
+    let md = Media::new_path(&instance, dir.join("list.m3u")).unwrap();
+    let (tx, rx) = channel();
+    md.event_manager().attach(EventType::MediaSubItemAdded, move |e, _| {
+        if let Event::MediaSubItemAdded(Some(item)) = e {
+            let _ = tx.send(item);
+        }
+    }).unwrap();
+    assert_eq!(names(md.expand(&instance, 4, common::TIMEOUT)), ["a.wav", "c.wav", "b.wav"]);
+    assert_eq!(names(rx.try_iter().collect()), ["a.wav", "inner.m3u", "b.wav"]);
+
+    let md = Media::new_path(&instance, dir.join("list.m3u")).unwrap();
+    assert_eq!(names(md.expand(&instance, 0, common::TIMEOUT)), ["a.wav", "inner.m3u", "b.wav"]);
+}
+
+#[test]
+fn node() {
+    let Some(instance) = common::instance() else { return };
+    let node = Media::new_as_node(&instance, "node").unwrap();
+    let item = Media::new_location(&instance, "file:///a.wav").unwrap();
+    node.subitems().unwrap().add_media(&item).unwrap();
+
+    let list = node.subitems().unwrap();
+    assert_eq!(list.count(), 1);
+    assert_eq!(list.item_at_index(0).unwrap().mrl().as_deref(), Some("file:///a.wav"));
+}
+
+#[test]
+fn media_type_and_duplicate() {
+    let Some(instance) = common::instance() else { return };
+    let dir = TempDir::new();
+    common::write_wav(dir.join("a.wav"), 200, 8000);
+    std::fs::write(dir.join("list.m3u"), "a.wav\n").unwrap();
+
+    let md = parse(&instance, &dir.join("a.wav"));
+    assert_eq!(md.media_type(), MediaType::File);
+    let copy = md.duplicate().unwrap();
+    assert_eq!(copy.mrl(), md.mrl());
+
+    let list = parse(&instance, &dir.join("list.m3u"));
+    assert_eq!(list.media_type(), MediaType::Playlist);
+}
+
+#[cfg(not(feature = "libvlc4"))]
+#[test]
+fn user_data() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    struct Flag(Arc<AtomicBool>);
+    impl Drop for Flag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    let Some(instance) = common::instance() else { return };
+    let md = Media::new_location(&instance, "file:///a.wav").unwrap();
+    assert_eq!(md.user_data::<u64>(), None);
+
+    md.set_user_data(42u64);
+    let list = vlc::MediaList::new(&instance).unwrap();
+    list.add_media(&md).unwrap();
+    assert_eq!(list.item_at_index(0).unwrap().user_data::<u64>(), Some(42));
+    assert_eq!(md.user_data::<String>(), None);
+    assert_eq!(md.take_user_data::<String>(), None);
+    assert_eq!(md.take_user_data::<u64>(), Some(42));
+    assert_eq!(md.user_data::<u64>(), None);
+
+    let dropped = Arc::new(AtomicBool::new(false));
+    md.set_user_data(Flag(dropped.clone()));
+    drop(list);
+    assert!(!dropped.load(Ordering::SeqCst));
+    drop(md);
+    assert!(dropped.load(Ordering::SeqCst));
+}
@@ -0,0 +1,110 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+mod common;
+
+use vlc::{Media, MediaList};
+
+use common::TempDir;
+
+fn file_name(md: &Media) -> String {
+    md.mrl().unwrap().rsplit('/').next().unwrap().to_owned()
+}
+
+#[test]
+fn list_ordering() {
+    let Some(instance) = common::instance() else { return };
+    let dir = TempDir::new();
+    let media: Vec<Media> = ["a.wav", "b.wav", "c.wav"].iter().map(|name| {
+        let path = dir.join(name);
+        common::write_wav(&path, 100, 8000);
+        Media::new_path(&instance, &path).unwrap()
+    }).collect();
+
+    let list = MediaList::new(&instance).unwrap();
+    list.add_media(&media[0]).unwrap();
+    list.add_media(&media[1]).unwrap();
+    list.insert_media(&media[2], 0).unwrap();
+    assert_eq!(list.count(), 3);
+
+    let names: Vec<String> = (0..list.count()).map(|i| file_name(&list.item_at_index(i).unwrap())).collect();
+    assert_eq!(names, ["c.wav", "a.wav", "b.wav"]);
+    assert_eq!(list.index_of_item(&media[1]), Some(2));
+
+    list.remove_index(1).unwrap();
+    assert_eq!(list.count(), 2);
+    assert_eq!(list.index_of_item(&media[0]), None);
+    assert!(list.item_at_index(2).is_none());
+}
+
+#[test]
+fn bulk_operations() {
+    let Some(instance) = common::instance() else { return };
+    let media: Vec<Media> = ["a", "b", "c", "d"].iter()
+        .map(|name| Media::new_location(&instance, &format!("file:///{}.wav", name)).unwrap())
+        .collect();
+    let names = |list: &MediaList| -> Vec<String> { list.iter().map(|md| file_name(&md)).collect() };
+
+    let mut list = MediaList::new(&instance).unwrap();
+    list.extend(&media);
+    assert_eq!(names(&list), ["a.wav", "b.wav", "c.wav", "d.wav"]);
+
+    list.move_item(0, 2).unwrap();
+    assert_eq!(names(&list), ["b.wav", "c.wav", "a.wav", "d.wav"]);
+    list.move_item(3, 0).unwrap();
+    assert_eq!(names(&list), ["d.wav", "b.wav", "c.wav", "a.wav"]);
+    list.swap(3, 1).unwrap();
+    assert_eq!(names(&list), ["d.wav", "a.wav", "c.wav", "b.wav"]);
+    assert!(list.move_item(0, 4).is_err());
+    assert!(list.swap(-1, 0).is_err());
+
+    list.retain(|md| file_name(md) != "c.wav").unwrap();
+    assert_eq!(names(&list), ["d.wav", "a.wav", "b.wav"]);
+    assert_eq!((&list).into_iter().len(), 3);
+
+    {
+        let guard = list.lock();
+        guard.insert_media(&media[2], 0).unwrap();
+        guard.remove_index(guard.count() - 1).unwrap();
+        assert_eq!(guard.index_of_item(&media[2]), Some(0));
+    }
+    assert_eq!(names(&list), ["c.wav", "d.wav", "a.wav"]);
+
+    list.clear().unwrap();
+    assert_eq!(list.count(), 0);
+}
+
+// libvlc 4 has no media state events to follow the items with.
+#[cfg(not(feature = "libvlc4"))]
+#[test]
+fn list_player_order() {
+    use std::sync::mpsc::channel;
+    use vlc::{Event, EventType, MediaListPlayer, State};
+
+    let Some(instance) = common::instance() else { return };
+    let dir = TempDir::new();
+    let list = MediaList::new(&instance).unwrap();
+    let (tx, rx) = channel();
+
+    for i in 0..3 {
+        let path = dir.join(format!("{}.wav", i));
+        common::write_wav(&path, 200, 8000);
+        let md = Media::new_path(&instance, &path).unwrap();
+        let tx = tx.clone();
+        md.event_manager().attach(EventType::MediaStateChanged, move |e, _| {
+            if let Event::MediaStateChanged(State::Ended) = e {
+                let _ = tx.send(i);
+            }
+        }).unwrap();
+        list.add_media(&md).unwrap();
+    }
+
+    let player = MediaListPlayer::new(&instance).unwrap();
+    player.set_media_list(&list);
+    player.play();
+
+    let order: Vec<i32> = (0..3).map(|_| common::recv(&rx)).collect();
+    assert_eq!(order, [0, 1, 2]);
+    player.stop();
+}
@@ -0,0 +1,67 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+#![cfg(feature = "mock")]
+
+use std::sync::{Arc, Mutex};
+
+use vlc::mock::MockEventManager;
+use vlc::{Event, EventManagerApi, EventType};
+
+fn recorder(em: &MockEventManager, event_type: EventType, log: &Arc<Mutex<Vec<String>>>, name: &str) -> usize {
+    let log = log.clone();
+    let name = name.to_owned();
+    em.attach(event_type, move |e, _| log.lock().unwrap().push(format!("{} {:?}", name, e))).unwrap()
+}
+
+#[test]
+fn dispatch() {
+    let em = MockEventManager::new();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    recorder(&em, EventType::MediaPlayerPlaying, &log, "a");
+    recorder(&em, EventType::MediaPlayerPlaying, &log, "b");
+    recorder(&em, EventType::MediaPlayerPaused, &log, "c");
+
+    em.emit(Event::MediaPlayerPlaying);
+    em.emit(Event::MediaPlayerStopped);
+    assert_eq!(*log.lock().unwrap(), ["a MediaPlayerPlaying", "b MediaPlayerPlaying"]);
+    let emitted: Vec<EventType> = em.take_emitted().iter().map(Event::event_type).collect();
+    assert_eq!(emitted, [EventType::MediaPlayerPlaying, EventType::MediaPlayerStopped]);
+    assert!(em.take_emitted().is_empty());
+}
+
+#[test]
+fn nested_emit() {
+    let em = MockEventManager::new();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let inner = em.clone();
+    let inner_log = log.clone();
+    em.attach(EventType::MediaPlayerOpening, move |_, _| {
+        inner.emit(Event::MediaPlayerPlaying);
+        inner_log.lock().unwrap().push("opening done".to_owned());
+    }).unwrap();
+    recorder(&em, EventType::MediaPlayerPlaying, &log, "a");
+
+    // Events emitted from a callback are delivered once it returns.
+    em.emit(Event::MediaPlayerOpening);
+    assert_eq!(*log.lock().unwrap(), ["opening done", "a MediaPlayerPlaying"]);
+}
+
+#[test]
+fn detach() {
+    let em = MockEventManager::new();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let a = recorder(&em, EventType::MediaPlayerPlaying, &log, "a");
+    let b = recorder(&em, EventType::MediaPlayerPlaying, &log, "b");
+
+    em.detach(EventType::MediaPlayerPlaying, a);
+    // A handle only detaches the event type it was attached for.
+    em.detach(EventType::MediaPlayerPaused, b);
+    em.emit(Event::MediaPlayerPlaying);
+    assert_eq!(*log.lock().unwrap(), ["b MediaPlayerPlaying"]);
+
+    em.detach(EventType::MediaPlayerPlaying, b);
+    em.emit(Event::MediaPlayerPlaying);
+    assert_eq!(log.lock().unwrap().len(), 1);
+}
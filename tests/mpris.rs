@@ -0,0 +1,209 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+#![cfg(all(feature = "mpris", feature = "mock"))]
+
+use std::collections::HashMap;
+use std::convert::{TryFrom, TryInto};
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use vlc::mock::{MockMedia, MockMediaList, MockMediaListPlayer, MockMediaPlayer};
+use vlc::mpris::Mpris;
+use vlc::{MediaApi, MediaListApi, MediaListPlayerApi, MediaPlayerApi, MediaPlayerAudioEx, Meta, State};
+use zbus::blocking::{connection, fdo::DBusProxy, proxy, Connection, Proxy};
+use zbus::proxy::CacheProperties;
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue};
+
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.vlc_rs_test";
+const PATH: &str = "/org/mpris/MediaPlayer2";
+
+// A private bus, killed when dropped.
+struct Bus {
+    daemon: Child,
+    address: String,
+}
+
+impl Bus {
+    // None when dbus-daemon is not installed.
+    fn start() -> Option<Bus> {
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--print-address", "--nofork"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.take().unwrap()).read_line(&mut address).unwrap();
+        Some(Bus { daemon, address: address.trim().to_owned() })
+    }
+
+    fn connect(&self) -> Connection {
+        connection::Builder::address(self.address.as_str()).unwrap().build().unwrap()
+    }
+}
+
+impl Drop for Bus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
+
+// Properties are read from the player each time, unless `cached` to follow the change signals.
+fn proxy<'a>(conn: &Connection, interface: &'a str, cached: bool) -> Proxy<'a> {
+    let cache = if cached { CacheProperties::Yes } else { CacheProperties::No };
+    proxy::Builder::new(conn)
+        .destination(BUS_NAME).unwrap()
+        .path(PATH).unwrap()
+        .interface(interface).unwrap()
+        .cache_properties(cache)
+        .build()
+        .unwrap()
+}
+
+fn wait_until<F: Fn() -> bool>(what: &str, f: F) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !f() {
+        assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+fn track(index: usize) -> OwnedObjectPath {
+    OwnedObjectPath::try_from(format!("/org/videolan/vlc_rs/track/{}", index)).unwrap()
+}
+
+fn get<T: TryFrom<OwnedValue>>(metadata: &HashMap<String, OwnedValue>, key: &str) -> T {
+    T::try_from(metadata[key].try_clone().unwrap()).ok().unwrap()
+}
+
+fn setup(bus: &Bus) -> (MockMediaList, MockMediaListPlayer, MockMediaPlayer, vlc::mpris::MprisServer) {
+    let list = MockMediaList::new();
+    for (i, title) in ["One", "Two"].iter().enumerate() {
+        let md = MockMedia::new(&format!("file:///{}.ogg", i), Some(Duration::from_secs(10)));
+        md.set_meta(Meta::Title, title);
+        md.set_meta(Meta::Artist, "Band");
+        list.add_media(&md).unwrap();
+    }
+    let list_player = MockMediaListPlayer::new();
+    let player = list_player.get_media_player().unwrap();
+    let server = Mpris::new("vlc_rs_test", &player)
+        .with_identity("Test Player")
+        .with_track_list(&list_player, &list)
+        .with_opener(|uri| Some(MockMedia::new(uri, None)))
+        .start_at(&bus.address)
+        .unwrap();
+    (list, list_player, player, server)
+}
+
+#[test]
+fn player() {
+    let Some(bus) = Bus::start() else { return };
+    let (_list, list_player, player, server) = setup(&bus);
+    assert_eq!(server.bus_name(), BUS_NAME);
+
+    let conn = bus.connect();
+    let root = proxy(&conn, "org.mpris.MediaPlayer2", false);
+    assert_eq!(root.get_property::<String>("Identity").unwrap(), "Test Player");
+    assert!(root.get_property::<bool>("HasTrackList").unwrap());
+
+    let mpris = proxy(&conn, "org.mpris.MediaPlayer2.Player", false);
+    let signalled = proxy(&conn, "org.mpris.MediaPlayer2.Player", true);
+    assert_eq!(mpris.get_property::<String>("PlaybackStatus").unwrap(), "Stopped");
+    assert!(mpris.get_property::<bool>("CanGoNext").unwrap());
+
+    mpris.call_method("Play", &()).unwrap();
+    list_player.advance(Duration::ZERO);
+    assert_eq!(player.state(), State::Playing);
+    // Changes are signalled, which updates the cached properties.
+    wait_until("the status to be signalled", || {
+        signalled.cached_property::<String>("PlaybackStatus").unwrap().as_deref() == Some("Playing")
+    });
+    let metadata: HashMap<String, OwnedValue> = mpris.get_property("Metadata").unwrap();
+    assert_eq!(get::<String>(&metadata, "xesam:title"), "One");
+    assert_eq!(get::<Vec<String>>(&metadata, "xesam:artist"), ["Band"]);
+    assert_eq!(get::<i64>(&metadata, "mpris:length"), 10_000_000);
+    assert_eq!(get::<OwnedObjectPath>(&metadata, "mpris:trackid"), track(0));
+
+    mpris.set_property("Volume", 0.5).unwrap();
+    assert_eq!(player.get_volume(), 50);
+    // Changes made on the player are signalled too.
+    player.set_volume(30).unwrap();
+    wait_until("the volume to be signalled", || signalled.cached_property::<f64>("Volume").unwrap() == Some(0.3));
+    mpris.call_method("Seek", &(2_000_000i64)).unwrap();
+    assert_eq!(player.time(), Some(Duration::from_secs(2)));
+    assert_eq!(mpris.get_property::<i64>("Position").unwrap(), 2_000_000);
+    // A position for another track is ignored.
+    mpris.call_method("SetPosition", &(track(1).as_ref(), 5_000_000i64)).unwrap();
+    assert_eq!(player.time(), Some(Duration::from_secs(2)));
+    mpris.call_method("SetPosition", &(track(0).as_ref(), 5_000_000i64)).unwrap();
+    assert_eq!(player.time(), Some(Duration::from_secs(5)));
+
+    mpris.call_method("Pause", &()).unwrap();
+    assert_eq!(mpris.get_property::<String>("PlaybackStatus").unwrap(), "Paused");
+    mpris.call_method("PlayPause", &()).unwrap();
+    assert_eq!(player.state(), State::Playing);
+
+    mpris.call_method("Next", &()).unwrap();
+    let metadata: HashMap<String, OwnedValue> = mpris.get_property("Metadata").unwrap();
+    assert_eq!(get::<String>(&metadata, "xesam:title"), "Two");
+    wait_until("the track to be signalled", || {
+        signalled.cached_property::<HashMap<String, OwnedValue>>("Metadata").unwrap()
+            .is_some_and(|m| get::<OwnedObjectPath>(&m, "mpris:trackid") == track(1))
+    });
+
+    mpris.set_property("LoopStatus", "Playlist").unwrap();
+    assert_eq!(mpris.get_property::<String>("LoopStatus").unwrap(), "Playlist");
+    assert!(mpris.set_property("LoopStatus", "Forever").is_err());
+
+    mpris.call_method("OpenUri", &("http://example.com/radio")).unwrap();
+    assert_eq!(player.get_media().and_then(|md| md.mrl()).as_deref(), Some("http://example.com/radio"));
+    mpris.call_method("Stop", &()).unwrap();
+    assert_eq!(mpris.get_property::<String>("PlaybackStatus").unwrap(), "Stopped");
+
+    server.stop();
+    assert!(!DBusProxy::new(&conn).unwrap().name_has_owner(BUS_NAME.try_into().unwrap()).unwrap());
+}
+
+#[test]
+fn track_list() {
+    let Some(bus) = Bus::start() else { return };
+    let (list, list_player, player, _server) = setup(&bus);
+    let conn = bus.connect();
+    let tracks = proxy(&conn, "org.mpris.MediaPlayer2.TrackList", false);
+
+    assert_eq!(tracks.get_property::<Vec<OwnedObjectPath>>("Tracks").unwrap(), [track(0), track(1)]);
+    assert!(tracks.get_property::<bool>("CanEditTracks").unwrap());
+    let metadata: Vec<HashMap<String, OwnedValue>> = tracks.call("GetTracksMetadata", &(vec![track(1)])).unwrap();
+    assert_eq!(metadata.len(), 1);
+    assert_eq!(get::<String>(&metadata[0], "xesam:title"), "Two");
+
+    let replaced = tracks.receive_signal("TrackListReplaced").unwrap();
+    tracks.call_method("AddTrack", &("file:///new.ogg", track(0).as_ref(), false)).unwrap();
+    assert_eq!(list.count(), 3);
+    assert_eq!(list.item_at_index(1).and_then(|md| md.mrl()).as_deref(), Some("file:///new.ogg"));
+    let (ids, current): (Vec<OwnedObjectPath>, OwnedObjectPath) = replaced.take(1).next().unwrap().body().deserialize().unwrap();
+    // The other tracks keep their ids.
+    assert_eq!(ids, [track(0), track(2), track(1)]);
+    assert_eq!(current.as_str(), "/org/mpris/MediaPlayer2/TrackList/NoTrack");
+
+    tracks.call_method("GoTo", &(track(1).as_ref())).unwrap();
+    list_player.advance(Duration::ZERO);
+    assert_eq!(player.get_media().and_then(|md| md.get_meta(Meta::Title)).as_deref(), Some("Two"));
+    tracks.call_method("RemoveTrack", &(track(2).as_ref())).unwrap();
+    assert_eq!(list.count(), 2);
+    assert_eq!(tracks.get_property::<Vec<OwnedObjectPath>>("Tracks").unwrap(), [track(0), track(1)]);
+    let metadata: HashMap<String, OwnedValue> = proxy(&conn, "org.mpris.MediaPlayer2.Player", false).get_property("Metadata").unwrap();
+    assert_eq!(get::<OwnedObjectPath>(&metadata, "mpris:trackid"), track(1));
+    assert!(tracks.call_method("GoTo", &(ObjectPath::try_from("/org/videolan/vlc_rs/track/7").unwrap())).is_err());
+
+    // The same media twice is two tracks.
+    list.add_media(&list.item_at_index(0).unwrap()).unwrap();
+    assert_eq!(tracks.get_property::<Vec<OwnedObjectPath>>("Tracks").unwrap(), [track(0), track(1), track(3)]);
+    tracks.call_method("RemoveTrack", &(track(0).as_ref())).unwrap();
+    assert_eq!(tracks.get_property::<Vec<OwnedObjectPath>>("Tracks").unwrap(), [track(1), track(3)]);
+}
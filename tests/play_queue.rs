@@ -0,0 +1,154 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+#![cfg(feature = "mock")]
+
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+use vlc::mock::{MockMedia, MockMediaPlayer};
+use vlc::{MediaApi, MediaPlayerApi, PlayQueue, QueueEvent, RepeatMode};
+
+fn queue(n: usize) -> (MockMediaPlayer, PlayQueue<MockMediaPlayer>, Receiver<Option<usize>>) {
+    let player = MockMediaPlayer::new();
+    let queue = PlayQueue::new(&player);
+    for i in 0..n {
+        queue.enqueue(MockMedia::new(&format!("file:///{}.ogg", i), Some(Duration::from_secs(1))));
+    }
+    let (tx, rx) = channel();
+    queue.attach(move |e| {
+        let _ = tx.send(match e {
+            QueueEvent::NowPlaying { index, .. } => Some(index),
+            QueueEvent::Finished => None,
+        });
+    });
+    (player, queue, rx)
+}
+
+fn recv(rx: &Receiver<Option<usize>>) -> Option<usize> {
+    rx.recv_timeout(Duration::from_secs(5)).expect("no queue event")
+}
+
+// Play the current item to its end, and wait for the queue to move on.
+fn finish(player: &MockMediaPlayer, rx: &Receiver<Option<usize>>) -> Option<usize> {
+    player.advance(Duration::ZERO);
+    player.advance(Duration::from_secs(2));
+    recv(rx)
+}
+
+#[test]
+fn plays_in_order() {
+    let (player, queue, rx) = queue(3);
+    queue.play().unwrap();
+    assert_eq!(recv(&rx), Some(0));
+    assert_eq!(player.get_media().unwrap().mrl().as_deref(), Some("file:///0.ogg"));
+    assert_eq!(finish(&player, &rx), Some(1));
+    assert_eq!(finish(&player, &rx), Some(2));
+    assert_eq!(finish(&player, &rx), None);
+    assert!(queue.current().is_none());
+
+    queue.next().unwrap();
+    assert_eq!(recv(&rx), Some(0));
+    queue.next().unwrap();
+    assert_eq!(recv(&rx), Some(1));
+    queue.previous().unwrap();
+    assert_eq!(recv(&rx), Some(0));
+    assert!(queue.previous().is_err());
+}
+
+#[test]
+fn shuffle_is_reproducible() {
+    let (_, a, _) = queue(20);
+    let (_, b, _) = queue(20);
+    a.set_shuffle(Some(7));
+    b.set_shuffle(Some(7));
+    assert_eq!(a.order(), b.order());
+    assert_ne!(a.order(), (0..20).collect::<Vec<_>>());
+    b.set_shuffle(Some(8));
+    assert_ne!(a.order(), b.order());
+}
+
+#[test]
+fn shuffle_plays_everything_once() {
+    let (player, queue, rx) = queue(5);
+    queue.set_shuffle(Some(1));
+    queue.set_repeat(RepeatMode::All);
+    queue.play().unwrap();
+
+    let mut seen = vec![recv(&rx).unwrap()];
+    for _ in 0..4 {
+        seen.push(finish(&player, &rx).unwrap());
+    }
+    let first_round = seen.clone();
+    seen.sort_unstable();
+    assert_eq!(seen, [0, 1, 2, 3, 4]);
+
+    // The next round is another order, not starting with the last item.
+    let next = finish(&player, &rx).unwrap();
+    assert_ne!(Some(&next), first_round.last());
+    assert_eq!(queue.current().map(|(i, _)| i), Some(next));
+}
+
+#[test]
+fn repeat_one() {
+    let (player, queue, rx) = queue(2);
+    queue.set_repeat(RepeatMode::One);
+    queue.play().unwrap();
+    assert_eq!(recv(&rx), Some(0));
+    assert_eq!(finish(&player, &rx), Some(0));
+    queue.next().unwrap();
+    assert_eq!(recv(&rx), Some(1));
+    assert_eq!(finish(&player, &rx), Some(1));
+}
+
+#[test]
+fn insertion() {
+    let (player, queue, rx) = queue(3);
+    queue.play().unwrap();
+    assert_eq!(recv(&rx), Some(0));
+
+    // The item is added last, so the other indices stay the same.
+    queue.play_next(MockMedia::new("file:///next.ogg", Some(Duration::from_secs(1))));
+    assert_eq!(queue.len(), 4);
+    assert_eq!(queue.order(), [0, 3, 1, 2]);
+    assert_eq!(finish(&player, &rx), Some(3));
+    assert_eq!(queue.current().unwrap().1.mrl().as_deref(), Some("file:///next.ogg"));
+
+    // Removing the current item keeps playing it, then goes on after it.
+    queue.remove(3).unwrap();
+    assert!(queue.current().is_none());
+    assert_eq!(finish(&player, &rx), Some(1));
+    assert_eq!(queue.current().unwrap().1.mrl().as_deref(), Some("file:///1.ogg"));
+
+    queue.set_shuffle(Some(3));
+    queue.play_index(0).unwrap();
+    assert_eq!(recv(&rx), Some(0));
+    queue.previous().unwrap();
+    assert_eq!(recv(&rx), Some(1));
+}
+
+#[test]
+fn cursor() {
+    let (player, queue, rx) = queue(6);
+    queue.set_shuffle(Some(11));
+    queue.set_repeat(RepeatMode::All);
+    queue.play().unwrap();
+    recv(&rx);
+    let current = finish(&player, &rx);
+    let cursor = queue.cursor();
+
+    let (_, restored, rx) = self::queue(6);
+    restored.restore(&cursor).unwrap();
+    assert_eq!(restored.order(), queue.order());
+    assert_eq!(restored.repeat(), RepeatMode::All);
+    assert!(restored.shuffle());
+    restored.play().unwrap();
+    assert_eq!(recv(&rx), current);
+
+    let mut bad = cursor.clone();
+    bad.order[0] = bad.order[1];
+    assert!(restored.restore(&bad).is_err());
+    bad.order.pop();
+    assert!(restored.restore(&bad).is_err());
+}
@@ -0,0 +1,154 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+mod common;
+
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use vlc::{EventType, Media, MediaPlayer, MediaPlayerStepEx, State, StatusPoller};
+
+use common::TempDir;
+
+#[cfg(not(feature = "libvlc4"))]
+const END: EventType = EventType::MediaPlayerEndReached;
+#[cfg(feature = "libvlc4")]
+const END: EventType = EventType::MediaPlayerStopping;
+
+#[test]
+fn play_to_end() {
+    let Some(instance) = common::instance() else { return };
+    let dir = TempDir::new();
+    let path = dir.join("short.wav");
+    common::write_wav(&path, 300, 8000);
+
+    let md = Media::new_path(&instance, &path).unwrap();
+    let mdp = MediaPlayer::new(&instance).unwrap();
+    mdp.set_media(&md);
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let (tx, rx) = channel();
+    let em = mdp.event_manager();
+    for &event_type in &[EventType::MediaPlayerOpening, EventType::MediaPlayerPlaying, END] {
+        let seen = seen.clone();
+        let tx = tx.clone();
+        em.attach(event_type, move |e, _| {
+            seen.lock().unwrap().push(e.event_type());
+            if e.event_type() == END {
+                let _ = tx.send(());
+            }
+        }).unwrap();
+    }
+
+    mdp.play().unwrap();
+    common::recv(&rx);
+
+    assert_eq!(*seen.lock().unwrap(), vec![EventType::MediaPlayerOpening, EventType::MediaPlayerPlaying, END]);
+    #[cfg(not(feature = "libvlc4"))]
+    assert_eq!(mdp.state(), State::Ended);
+}
+
+#[test]
+fn no_title_without_input() {
+    let Some(instance) = common::instance() else { return };
+    let mdp = MediaPlayer::new(&instance).unwrap();
+
+    assert_eq!(mdp.title_count(), None);
+    assert_eq!(mdp.time(), None);
+    assert!(!mdp.is_playing());
+}
+
+#[test]
+fn seek() {
+    let Some(instance) = common::instance() else { return };
+    let dir = TempDir::new();
+    let path = dir.join("long.wav");
+    common::write_wav(&path, 3000, 8000);
+
+    let md = Media::new_path(&instance, &path).unwrap();
+    let mdp = MediaPlayer::new(&instance).unwrap();
+    mdp.set_media(&md);
+
+    let (tx, rx) = channel();
+    mdp.event_manager().attach(EventType::MediaPlayerPlaying, move |_, _| {
+        let _ = tx.send(());
+    }).unwrap();
+    mdp.play().unwrap();
+    common::recv(&rx);
+
+    assert!(mdp.is_seekable());
+    mdp.seek(Duration::from_secs(2));
+
+    let start = Instant::now();
+    while mdp.time().unwrap_or_default() < Duration::from_secs(2) {
+        assert!(start.elapsed() < common::TIMEOUT, "seek did not happen");
+        thread::sleep(Duration::from_millis(20));
+    }
+    assert!(mdp.get_position().unwrap() > 0.6);
+    mdp.stop();
+}
+
+#[test]
+fn seek_precise() {
+    let Some(instance) = common::instance() else { return };
+    let dir = TempDir::new();
+    let path = dir.join("precise.wav");
+    common::write_wav(&path, 3000, 8000);
+
+    let md = Media::new_path(&instance, &path).unwrap();
+    let mdp = MediaPlayer::new(&instance).unwrap();
+    mdp.set_media(&md);
+
+    let (tx, rx) = channel();
+    mdp.event_manager().attach(EventType::MediaPlayerPlaying, move |_, _| {
+        let _ = tx.send(());
+    }).unwrap();
+    mdp.play().unwrap();
+    common::recv(&rx);
+
+    mdp.seek_precise(Duration::from_millis(1500)).unwrap();
+    assert!(mdp.time().unwrap() >= Duration::from_millis(1480));
+
+    // No video, no frames to step through.
+    assert_eq!(mdp.fps(), None);
+    assert!(mdp.step_backward().is_err());
+    mdp.stop();
+}
+
+#[test]
+fn status() {
+    let Some(instance) = common::instance() else { return };
+    let dir = TempDir::new();
+    let path = dir.join("status.wav");
+    common::write_wav(&path, 3000, 8000);
+
+    let md = Media::new_path(&instance, &path).unwrap();
+    let mdp = MediaPlayer::new(&instance).unwrap();
+    assert_eq!(mdp.status().time, None);
+    mdp.set_media(&md);
+
+    let (tx, rx) = channel();
+    mdp.event_manager().attach(EventType::MediaPlayerPlaying, move |_, _| {
+        let _ = tx.send(());
+    }).unwrap();
+    mdp.play().unwrap();
+    common::recv(&rx);
+
+    let status = mdp.status();
+    assert_eq!(status.state, State::Playing);
+    let length = status.length.unwrap();
+    assert!(length > Duration::from_millis(2900) && length < Duration::from_millis(3100), "length {:?}", length);
+    assert!(status.seekable);
+
+    let (tx, rx) = channel();
+    let poller = StatusPoller::start(&mdp, Duration::from_millis(20), move |status| {
+        let _ = tx.send(status.state);
+    });
+    assert_eq!(common::recv(&rx), State::Playing);
+    common::recv(&rx);
+    poller.stop();
+    mdp.stop();
+}
@@ -0,0 +1,117 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+mod common;
+
+use std::path::Path;
+use std::time::Duration;
+
+use vlc::playlist::{self, Entry, Format, Location};
+use vlc::{Media, MediaList, Meta};
+
+use common::TempDir;
+
+fn entries() -> Vec<Entry> {
+    vec![
+        Entry {
+            location: Location::Path("/music/Café & Bar/01 <intro>.flac".into()),
+            title: Some("Intro".to_owned()),
+            artist: Some("Band".to_owned()),
+            length: Some(Duration::from_secs(95)),
+        },
+        Entry {
+            location: Location::Path("/elsewhere/b.ogg".into()),
+            title: Some("B".to_owned()),
+            artist: None,
+            length: None,
+        },
+        Entry::new(Location::Url("http://example.com/radio?a=1&b=2".to_owned())),
+    ]
+}
+
+#[test]
+fn round_trip() {
+    let base = Path::new("/music");
+    for &format in &[Format::M3u, Format::Pls, Format::Xspf] {
+        let text = playlist::write(format, &entries(), base);
+        let mut expected = entries();
+        if format == Format::Pls {
+            // PLS has no artist.
+            expected[0].artist = None;
+        }
+        assert_eq!(playlist::parse(format, &text, base).unwrap(), expected, "{:?}:\n{}", format, text);
+    }
+}
+
+#[test]
+fn relative_paths() {
+    let base = Path::new("/music");
+    let m3u = playlist::write(Format::M3u, &entries(), base);
+    assert!(m3u.contains("\nCafé & Bar/01 <intro>.flac\n"), "{}", m3u);
+    assert!(m3u.contains("\n/elsewhere/b.ogg\n"), "{}", m3u);
+
+    let xspf = playlist::write(Format::Xspf, &entries(), base);
+    assert!(xspf.contains("<location>Caf%C3%A9%20%26%20Bar/01%20%3Cintro%3E.flac</location>"), "{}", xspf);
+    assert!(xspf.contains("<location>file:///elsewhere/b.ogg</location>"), "{}", xspf);
+    assert!(xspf.contains("<location>http://example.com/radio?a=1&amp;b=2</location>"), "{}", xspf);
+}
+
+#[test]
+fn parse_others() {
+    let pls = "[Playlist]\nNumberOfEntries=2\nFile2=b.mp3\nTitle2=B\nFile1=http://radio/a\nLength1=-1\nVersion=2\n";
+    let entries = playlist::parse(Format::Pls, pls, Path::new("/dir")).unwrap();
+    assert_eq!(entries, [
+        Entry::new(Location::Url("http://radio/a".to_owned())),
+        Entry { title: Some("B".to_owned()), ..Entry::new(Location::Path("/dir/b.mp3".into())) },
+    ]);
+    assert_eq!(playlist::parse(Format::Pls, "#EXTM3U\n", Path::new("/dir")), None);
+
+    let xspf = r#"<?xml version="1.0"?>
+        <playlist version="1" xmlns="http://xspf.org/ns/0/"><trackList>
+        <track><title><![CDATA[A & B]]></title><location>file:///a%20b.wav</location><duration>1500</duration></track>
+        <track><title>no location</title></track>
+        </trackList></playlist>"#;
+    let entries = playlist::parse(Format::Xspf, xspf, Path::new("/dir")).unwrap();
+    assert_eq!(entries, [Entry {
+        location: Location::Path("/a b.wav".into()),
+        title: Some("A & B".to_owned()),
+        artist: None,
+        length: Some(Duration::from_millis(1500)),
+    }]);
+
+    let m3u = "a.mp3\r\n#EXTINF:12.5 tvg-id=\"x\",Just a title\r\nC:\\b.mp3\r\n";
+    let entries = playlist::parse(Format::M3u, m3u, Path::new("/dir")).unwrap();
+    assert_eq!(entries[0], Entry::new(Location::Path("/dir/a.mp3".into())));
+    assert_eq!(entries[1].title.as_deref(), Some("Just a title"));
+    assert_eq!(entries[1].length, Some(Duration::from_millis(12500)));
+
+    assert_eq!(Format::from_path("a/list.M3U8"), Some(Format::M3u));
+    assert_eq!(Format::from_path("list.txt"), None);
+}
+
+#[test]
+fn media_list() {
+    let Some(instance) = common::instance() else { return };
+    let dir = TempDir::new();
+    common::write_wav(dir.join("a.wav"), 200, 8000);
+
+    let list = MediaList::new(&instance).unwrap();
+    let md = Media::new_path(&instance, dir.join("a.wav")).unwrap();
+    md.set_meta(Meta::Title, "Tone");
+    md.set_meta(Meta::Artist, "Generator");
+    list.add_media(&md).unwrap();
+    list.add_media(&Media::new_location(&instance, "http://example.com/radio").unwrap()).unwrap();
+
+    for (name, format) in &[("list.m3u", Format::M3u), ("list.pls", Format::Pls), ("list.xspf", Format::Xspf)] {
+        let path = dir.join(name);
+        list.save_playlist(&path, *format).unwrap();
+
+        let loaded = MediaList::load_playlist(&instance, &path).unwrap();
+        assert_eq!(loaded.count(), 2);
+        let first = loaded.item_at_index(0).unwrap();
+        assert_eq!(first.mrl(), md.mrl());
+        assert_eq!(first.get_meta(Meta::Title).as_deref(), Some("Tone"));
+        assert_eq!(loaded.item_at_index(1).unwrap().mrl().as_deref(), Some("http://example.com/radio"));
+    }
+}
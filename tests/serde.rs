@@ -0,0 +1,72 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+#![cfg(feature = "serde")]
+
+use serde_json::json;
+
+use vlc::sout::Chain;
+use vlc::{AudioTrack, Event, FourCC, MediaTrack, MediaTrackUnion, Meta, State, TrackType};
+
+fn round_trip<T>(value: &T, expected: serde_json::Value)
+    where T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug
+{
+    assert_eq!(serde_json::to_value(value).unwrap(), expected);
+    assert_eq!(&serde_json::from_value::<T>(expected).unwrap(), value);
+}
+
+#[test]
+fn enums() {
+    round_trip(&State::NothingSpecial, json!("nothing_special"));
+    round_trip(&Meta::ArtworkURL, json!("artwork_url"));
+    round_trip(&Meta::TrackID, json!("track_id"));
+    round_trip(&TrackType::Audio, json!("audio"));
+}
+
+#[test]
+fn tracks() {
+    let track = MediaTrack {
+        codec: FourCC::AAC.into(),
+        original_fourcc: 0,
+        id: 1,
+        track_type: TrackType::Audio,
+        profile: 0,
+        level: 0,
+        bitrate: 128000,
+        language: Some("en".to_owned()),
+        description: None,
+        type_specific_data: MediaTrackUnion::Audio(AudioTrack { channels: 2, rate: 44100 }),
+        #[cfg(feature = "libvlc4")]
+        str_id: None,
+        #[cfg(feature = "libvlc4")]
+        name: None,
+        #[cfg(feature = "libvlc4")]
+        selected: false,
+    };
+    let value = serde_json::to_value(&track).unwrap();
+    assert_eq!(value["codec"], json!(0x6134706d));
+    assert_eq!(value["track_type"], json!("audio"));
+    assert_eq!(value["type_specific_data"], json!({"type": "audio", "channels": 2, "rate": 44100}));
+    assert_eq!(serde_json::from_value::<MediaTrack>(value).unwrap(), track);
+
+    round_trip(&MediaTrackUnion::None, json!({"type": "none"}));
+}
+
+#[test]
+fn events() {
+    let value = serde_json::to_value(Event::MediaPlayerPositionChanged(0.5)).unwrap();
+    assert_eq!(value, json!({"type": "media_player_position_changed", "data": 0.5}));
+    let value = serde_json::to_value(Event::MediaPlayerPlaying).unwrap();
+    assert_eq!(value, json!({"type": "media_player_playing"}));
+
+    let event: Event = serde_json::from_value(json!({"type": "media_meta_changed", "data": "title"})).unwrap();
+    assert!(matches!(event, Event::MediaMetaChanged(Meta::Title)));
+}
+
+#[test]
+fn chain() {
+    let chain: Chain = "#std{access=file,mux=ts,dst=out.ts}".parse().unwrap();
+    round_trip(&chain, json!("#std{access=file,mux=ts,dst=out.ts}"));
+    assert!(serde_json::from_value::<Chain>(json!("#std{")).is_err());
+}
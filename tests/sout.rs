@@ -0,0 +1,108 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use vlc::sout::{Chain, Duplicate, Module, Std, Transcode};
+
+fn module(s: &str) -> Module {
+    let chain = Chain::parse(s).unwrap();
+    assert_eq!(chain.modules().len(), 1, "{}", s);
+    chain.modules()[0].clone()
+}
+
+#[test]
+fn prefixes() {
+    let chain = Chain::parse(":sout=#std{access=file,dst=out.ts}").unwrap();
+    assert_eq!(chain, Chain::parse("std{access=file,dst=out.ts}").unwrap());
+    assert_eq!(chain.to_option(), ":sout=#std{access=file,dst=out.ts}");
+    assert_eq!(Chain::parse("").unwrap().modules().len(), 0);
+}
+
+#[test]
+fn quoted_values() {
+    let std = module(r#"std{access=file, dst="my out.ts" ,mux='ts'}"#);
+    assert_eq!(std.get("dst"), Some(Some("my out.ts")));
+    assert_eq!(std.get("mux"), Some(Some("ts")));
+    // Commas and braces inside quotes are part of the value.
+    let std = module(r#"std{dst="a,b}c",mux=ts}"#);
+    assert_eq!(std.get("dst"), Some(Some("a,b}c")));
+    assert_eq!(std.get("mux"), Some(Some("ts")));
+}
+
+#[test]
+fn escapes() {
+    let std = module(r#"std{dst="say \"hi\" \\ bye",mux=ts}"#);
+    assert_eq!(std.get("dst"), Some(Some(r#"say "hi" \ bye"#)));
+    // Other backslashes are kept.
+    let std = module(r#"std{dst="C:\dir\out.ts"}"#);
+    assert_eq!(std.get("dst"), Some(Some(r"C:\dir\out.ts")));
+}
+
+#[test]
+fn nested_chains() {
+    let chain = Chain::parse(
+        "#duplicate{dst=transcode{vcodec=h264,vb=800}:std{access=file,dst=a.ts},select=\"es=1\",dst=display}"
+    ).unwrap();
+    let duplicate = Duplicate::from_module(chain.module("duplicate").unwrap()).unwrap();
+    assert_eq!(duplicate.destinations.len(), 2);
+
+    let (first, select) = &duplicate.destinations[0];
+    assert_eq!(select.as_deref(), Some("es=1"));
+    let transcode = Transcode::from_module(first.module("transcode").unwrap()).unwrap();
+    assert_eq!(transcode, Transcode::new().vcodec("h264").vb(800));
+    assert_eq!(Std::from_module(first.module("std").unwrap()).unwrap(), Std::file("a.ts"));
+
+    let (second, select) = &duplicate.destinations[1];
+    assert_eq!(select, &None);
+    assert!(second.module("display").is_some());
+}
+
+#[test]
+fn empty_options() {
+    assert_eq!(module("display").options().len(), 0);
+    assert_eq!(module("display{}").options().len(), 0);
+    assert_eq!(module("display { }").options().len(), 0);
+
+    let display = module(r#"display{noaudio,delay=,dst=""}"#);
+    assert_eq!(display.get("noaudio"), Some(None));
+    assert_eq!(display.get("delay"), Some(None));
+    assert_eq!(display.get("dst"), Some(Some("")));
+    assert_eq!(display.get("missing"), None);
+}
+
+#[test]
+fn malformed() {
+    for s in &[
+        "std{access=file",
+        "std{dst=\"out.ts}",
+        "std{dst='out.ts}",
+        "duplicate{dst=std{access=file}",
+        "std{access=file}}",
+        "std{=file}",
+        "std{access=file,}",
+        "std{a=b} std",
+        ":",
+        "std::display",
+    ] {
+        assert!(Chain::parse(s).is_err(), "{} parsed", s);
+    }
+    let err = Chain::parse(":sout=#std{access=file").unwrap_err();
+    assert_eq!(err.position, 22);
+}
+
+#[test]
+fn round_trip() {
+    let chain = Chain::new()
+        .then(Transcode::new().vcodec("h264").acodec("mp4a"))
+        .then(Duplicate::new()
+            .dst(Chain::new().then(Std::file("with space, comma}.ts").mux("ts")))
+            .dst_select(Chain::new().then(Module::new("display").flag("noaudio")), "es=1"))
+        .then(Module::new("custom")
+            .option("quoted", r#"a "b" \c"#)
+            .option("braced", "{x}")
+            .option("empty", "")
+            .flag("flag"));
+    let s = chain.to_string();
+    assert_eq!(Chain::parse(&s).unwrap(), chain, "{}", s);
+    assert_eq!(Chain::parse(&chain.to_option()).unwrap(), chain);
+}
@@ -0,0 +1,131 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+#![cfg(feature = "mock")]
+
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Duration;
+
+use vlc::mock::{MockMedia, MockMediaPlayer};
+use vlc::{MTime, MediaPlayerApi, State, SyncGroup};
+
+fn clock() -> MTime {
+    MTime::from_micros(0)
+}
+
+// Only used by `extrapolation`, which moves it.
+static NOW: AtomicI64 = AtomicI64::new(0);
+
+fn moving_clock() -> MTime {
+    MTime::from_micros(NOW.load(Ordering::SeqCst))
+}
+
+fn group(n: usize) -> (Vec<MockMediaPlayer>, SyncGroup<MockMediaPlayer>) {
+    group_with_clock(n, clock)
+}
+
+fn group_with_clock(n: usize, clock: fn() -> MTime) -> (Vec<MockMediaPlayer>, SyncGroup<MockMediaPlayer>) {
+    let md = MockMedia::new("file:///wall.mp4", Some(Duration::from_secs(600)));
+    let players: Vec<MockMediaPlayer> = (0..n).map(|_| {
+        let player = MockMediaPlayer::new();
+        player.set_media(&md);
+        player
+    }).collect();
+    let group = SyncGroup::with_clock(&players[0], &players[1..], clock);
+    // Only sync when asked to.
+    group.set_interval(Duration::from_secs(3600));
+    (players, group)
+}
+
+#[test]
+fn fan_out() {
+    let (players, group) = group(3);
+    group.play().unwrap();
+    players.iter().for_each(|p| p.advance(Duration::ZERO));
+    assert!(players.iter().all(|p| p.state() == State::Playing));
+
+    group.seek(Duration::from_secs(30));
+    assert!(players.iter().all(|p| p.time() == Some(Duration::from_secs(30))));
+    group.set_rate(2.0).unwrap();
+    assert!(players.iter().all(|p| p.get_rate() == 2.0));
+    group.set_pause(true);
+    assert!(players.iter().all(|p| p.state() == State::Paused));
+    group.stop();
+    assert!(players.iter().all(|p| p.state() == State::Stopped));
+}
+
+#[test]
+fn drift_correction() {
+    let (players, group) = group(3);
+    let (leader, a, b) = (&players[0], &players[1], &players[2]);
+    group.play().unwrap();
+    players.iter().for_each(|p| p.advance(Duration::ZERO));
+
+    leader.advance(Duration::from_secs(10));
+    a.advance(Duration::from_millis(9900));
+    b.advance(Duration::from_millis(10010));
+    group.sync_now();
+    // Behind by 100 ms: faster, within 5%.
+    assert_eq!(a.get_rate(), 1.05);
+    // Within the tolerance.
+    assert_eq!(b.get_rate(), 1.0);
+
+    let stats = group.stats();
+    assert_eq!(stats[0].drift_ms, -100);
+    assert_eq!(stats[0].rate_corrections, 1);
+    assert_eq!(stats[1].drift_ms, 10);
+
+    leader.advance(Duration::from_secs(2));
+    a.advance(Duration::from_secs(2));
+    b.advance(Duration::from_secs(3));
+    group.sync_now();
+    // Caught up, then back at the leader rate.
+    assert_eq!(a.get_rate(), 1.0);
+    // Ahead by more than the threshold: seeked back.
+    assert_eq!(b.time(), leader.time());
+    assert_eq!(b.get_rate(), 1.0);
+
+    let stats = group.stats();
+    // The mock rounds the time down at 1.05.
+    assert!(stats[0].drift_ms.abs() <= 1, "{:?}", stats[0]);
+    assert_eq!(stats[0].max_drift_ms, 100);
+    assert!((stats[0].mean_drift_ms - 50.0).abs() <= 1.0);
+    assert_eq!(stats[1].seeks, 1);
+    assert_eq!(stats[1].samples, 2);
+
+    // Nothing to do while the leader is paused.
+    leader.set_pause(true);
+    a.advance(Duration::from_secs(5));
+    group.sync_now();
+    assert_eq!(group.stats()[0].samples, 2);
+    group.reset_stats();
+    assert_eq!(group.stats()[0].samples, 0);
+}
+
+#[test]
+fn extrapolation() {
+    let (players, group) = group_with_clock(2, moving_clock);
+    let (leader, follower) = (&players[0], &players[1]);
+    group.play().unwrap();
+    players.iter().for_each(|p| p.advance(Duration::ZERO));
+    leader.advance(Duration::from_secs(10));
+    follower.advance(Duration::from_secs(10));
+
+    // The leader has not reported its time for 200 ms, it is taken as having played on.
+    NOW.store(200_000, Ordering::SeqCst);
+    follower.advance(Duration::from_millis(200));
+    group.sync_now();
+    assert_eq!(group.stats()[0].drift_ms, 0);
+    assert_eq!(follower.get_rate(), 1.0);
+
+    // Behind by 100 ms once the leader reports.
+    leader.advance(Duration::from_millis(300));
+    group.sync_now();
+    assert_eq!(group.stats()[0].drift_ms, -100);
+    assert_eq!(follower.get_rate(), 1.05);
+
+    // Back at the leader rate once the group is gone.
+    drop(group);
+    assert_eq!(follower.get_rate(), 1.0);
+}
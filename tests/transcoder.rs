@@ -0,0 +1,67 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+mod common;
+
+use std::fs;
+
+use vlc::sout::Transcode;
+use vlc::{Media, TranscodeProfile, TranscodeState, Transcoder};
+
+use common::TempDir;
+
+// Re-encoding to PCM only needs the codecs every libvlc build has.
+fn wav_profile() -> TranscodeProfile {
+    TranscodeProfile { audio_only: true, ..TranscodeProfile::new("wav", Transcode::new().acodec("s16l")) }
+}
+
+#[test]
+fn profiles() {
+    let mp3 = TranscodeProfile::mp3(192);
+    assert!(mp3.audio_only);
+    assert_eq!(mp3.media_options(), vec![":no-sout-video", ":no-sout-spu"]);
+    let chain = mp3.chain("/tmp/out.mp3").to_option();
+    assert!(!chain.contains("vcodec"), "{}", chain);
+    assert!(chain.contains("acodec=mp3"), "{}", chain);
+
+    let mp4 = TranscodeProfile::mp4_h264_aac();
+    assert!(mp4.media_options().is_empty());
+    assert!(!TranscodeProfile::new("ts", Transcode::new()).audio_only);
+}
+
+#[test]
+fn transcode_wav() {
+    let Some(instance) = common::instance() else { return };
+    let dir = TempDir::new();
+    let input = dir.join("in.wav");
+    let output = dir.join("out.wav");
+    common::write_wav(&input, 2000, 8000);
+
+    let md = Media::new_path(&instance, &input).unwrap();
+    let job = Transcoder::new(&instance, &md, &output, &wav_profile()).unwrap();
+    assert_eq!(job.wait(|_| ()), TranscodeState::Idle);
+
+    job.start().unwrap();
+    let mut positions = Vec::new();
+    assert_eq!(job.wait(|pos| positions.push(pos)), TranscodeState::Done);
+    assert!(positions.windows(2).all(|w| w[0] <= w[1]), "{:?}", positions);
+    assert_eq!(job.progress(), 1.0);
+    // More than the 44 bytes of the header.
+    assert!(fs::metadata(&output).unwrap().len() > 44);
+}
+
+#[test]
+fn cancel() {
+    let Some(instance) = common::instance() else { return };
+    let dir = TempDir::new();
+    let input = dir.join("in.wav");
+    common::write_wav(&input, 120_000, 8000);
+
+    let md = Media::new_path(&instance, &input).unwrap();
+    let job = Transcoder::new(&instance, &md, dir.join("out.wav"), &wav_profile()).unwrap();
+    job.start().unwrap();
+    job.cancel();
+    assert_eq!(job.wait_timeout(common::TIMEOUT), TranscodeState::Cancelled);
+    assert!(job.progress() < 1.0);
+}
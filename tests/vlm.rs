@@ -0,0 +1,56 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+// VLM was removed from libvlc 4.
+#![cfg(not(feature = "libvlc4"))]
+
+mod common;
+
+use std::fs;
+use std::sync::mpsc::channel;
+
+use vlc::sout::{Chain, Std};
+use vlc::{EventType, Vlm, VlmMedia};
+
+use common::TempDir;
+
+#[test]
+fn broadcast_to_file() {
+    let Some(instance) = common::instance() else { return };
+    let dir = TempDir::new();
+    let input = dir.join("in.wav");
+    let output = dir.join("out.wav");
+    common::write_wav(&input, 500, 8000);
+
+    let (tx, rx) = channel();
+    instance.vlm_event_manager().attach(EventType::VlmMediaInstanceStatusEnd, move |_, _| {
+        let _ = tx.send(());
+    }).unwrap();
+
+    let chain = Chain::new().then(Std::file(&output).mux("wav"));
+    let broadcast = VlmMedia::new_broadcast(
+        &instance, "vlc-rs-test", input.to_str().unwrap(), &chain.to_string(), None, true, false).unwrap();
+    broadcast.play().unwrap();
+    common::recv(&rx);
+    broadcast.stop().unwrap();
+
+    // More than the 44 bytes of the header.
+    assert!(fs::metadata(&output).unwrap().len() > 44);
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn list_media() {
+    let Some(instance) = common::instance() else { return };
+    let output = Chain::new().then(Std::file("/dev/null")).to_string();
+    for name in ["vlc-rs-list-a", "vlc-rs list b"] {
+        instance.add_broadcast(name, "file:///in.wav", &output, None, false, false).unwrap();
+    }
+    let names = instance.list_media();
+    assert!(names.iter().any(|n| n == "vlc-rs-list-a"), "{:?}", names);
+    assert!(names.iter().any(|n| n == "vlc-rs list b"), "{:?}", names);
+
+    instance.del_media("vlc-rs-list-a").unwrap();
+    assert!(!instance.list_media().iter().any(|n| n == "vlc-rs-list-a"));
+}
@@ -0,0 +1,214 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+// The strings below are laid out as libvlc 3 prints them, tabs and trailing spaces included.
+#![cfg(all(feature = "serde", not(feature = "libvlc4")))]
+
+use vlc::{VlmInstanceState, VlmMediaInfo, VlmMediaType};
+
+// show idle, a broadcast that never ran.
+const IDLE: &str = r##"{
+"name": "idle",
+	"type": "broadcast",
+	"enabled": "yes",
+	"loop": "no",
+	"inputs": [
+	"file:///in.wav" 
+		
+	],
+	"output": "",
+	"options": null,
+	"instances": null 
+	
+} 
+
+"##;
+
+// show playing, with two inputs and a Windows path in the output.
+const PLAYING: &str = r##"{
+"name": "playing",
+	"type": "broadcast",
+	"enabled": "yes",
+	"loop": "yes",
+	"inputs": [
+	"file:///a.wav",
+		"file:///b.wav" 
+		
+	],
+	"output": "#std{access=file,mux=wav,dst="C:\out dir\out.wav"}",
+	"options": null,
+	"instances": {
+	"instance": {
+		"name": "default",
+			"state": "playing",
+			"position": "0.250000",
+			"time": "1500000",
+			"length": "6000000",
+			"rate": "1.000000",
+			"title": "0",
+			"chapter": "0",
+			"can-seek": "1",
+			"playlistindex": "1" 
+			
+		} 
+		
+	} 
+	
+} 
+
+"##;
+
+// show vod
+const VOD: &str = r##"{
+"name": "vod",
+	"type": "vod",
+	"enabled": "yes",
+	"mux": "mp2t",
+	"inputs": [
+	"file:///movie.ts" 
+		
+	],
+	"output": "",
+	"options": null,
+	"instances": null 
+	
+} 
+
+"##;
+
+// show opts, disabled, without inputs, with two options.
+const OPTIONS: &str = r##"{
+"name": "opts",
+	"type": "broadcast",
+	"enabled": "no",
+	"loop": "no",
+	"inputs": null,
+	"output": "",
+	"options": [
+	null,
+		null 
+		
+	],
+	"instances": null 
+	
+} 
+
+"##;
+
+// show, with three media.
+const LISTING: &str = r##"{
+	"media": [
+	{
+		"name": "idle",
+			"type": "broadcast",
+			"enabled": "yes",
+			"loop": "no",
+			"inputs": [
+			"file:///in.wav" 
+				
+			],
+			"output": "",
+			"options": null,
+			"instances": null 
+			
+		},
+		{
+		"name": "with space, and "quotes"",
+			"type": "broadcast",
+			"enabled": "yes",
+			"loop": "no",
+			"inputs": null,
+			"output": "",
+			"options": null,
+			"instances": null 
+			
+		},
+		{
+		"name": "vod",
+			"type": "vod",
+			"enabled": "yes",
+			"mux": "mp2t",
+			"inputs": [
+			"file:///movie.ts" 
+				
+			],
+			"output": "",
+			"options": null,
+			"instances": null 
+			
+		} 
+		
+	],
+	"schedule": null 
+	
+}
+"##;
+
+// show, without any media.
+const EMPTY_LISTING: &str = r##"{
+	"media": "( 0 broadcast - 0 vod )",
+	"schedule": null 
+	
+}
+"##;
+
+#[test]
+fn idle_broadcast() {
+    let info = VlmMediaInfo::parse(IDLE).unwrap();
+    assert_eq!(info.media_type, VlmMediaType::Broadcast);
+    assert!(info.enabled);
+    assert!(!info.loop_broadcast);
+    assert_eq!(info.mux, None);
+    assert_eq!(info.inputs, vec!["file:///in.wav"]);
+    assert_eq!(info.output, None);
+    assert!(info.options.is_empty());
+    assert!(info.instances.is_empty());
+}
+
+#[test]
+fn running_instance() {
+    let info = VlmMediaInfo::parse(PLAYING).unwrap();
+    assert!(info.loop_broadcast);
+    assert_eq!(info.inputs, vec!["file:///a.wav", "file:///b.wav"]);
+    assert_eq!(info.output.as_deref(), Some(r#"#std{access=file,mux=wav,dst="C:\out dir\out.wav"}"#));
+    assert_eq!(info.instances.len(), 1);
+
+    let instance = &info.instances[0];
+    assert_eq!(instance.name, "default");
+    assert_eq!(instance.state, VlmInstanceState::Playing);
+    assert_eq!(instance.position, Some(0.25));
+    assert_eq!(instance.time, Some(1_500_000));
+    assert_eq!(instance.length, Some(6_000_000));
+    assert_eq!(instance.rate, Some(1.0));
+    assert_eq!(instance.can_seek, Some(true));
+    assert_eq!(instance.playlist_index, Some(1));
+}
+
+#[test]
+fn vod() {
+    let info = VlmMediaInfo::parse(VOD).unwrap();
+    assert_eq!(info.media_type, VlmMediaType::Vod);
+    assert_eq!(info.mux.as_deref(), Some("mp2t"));
+    assert_eq!(info.inputs, vec!["file:///movie.ts"]);
+}
+
+#[test]
+fn options() {
+    let info = VlmMediaInfo::parse(OPTIONS).unwrap();
+    assert!(!info.enabled);
+    assert!(info.inputs.is_empty());
+    // libvlc 3 prints each option as null.
+    assert!(info.options.is_empty());
+    assert!(info.instances.is_empty());
+}
+
+#[test]
+fn names() {
+    assert_eq!(
+        VlmMediaInfo::parse_names(LISTING).unwrap(),
+        vec!["idle", r#"with space, and "quotes""#, "vod"]
+    );
+    assert!(VlmMediaInfo::parse_names(EMPTY_LISTING).unwrap().is_empty());
+    assert!(VlmMediaInfo::parse_names("not json").is_err());
+}